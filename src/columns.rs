@@ -0,0 +1,270 @@
+use std::io::Write;
+
+use crossterm::{
+    cursor, queue,
+    style::{ContentStyle, Print, PrintStyledContent},
+    terminal,
+};
+use serde_json::Value;
+use unicode_width::UnicodeWidthStr;
+
+use crate::json::{summarize, token_label, Json, Token};
+use crate::style::{
+    fit, styled, take_width, truncate, Line, StyleClass, StyledString, STYLE_COLUMN_BORDER,
+    STYLE_COLUMN_PARENT, STYLE_LINE_NUMBER, STYLE_SELECTED_ENTRY,
+};
+
+/// One entry of a column: a member or element, or the root.
+struct Entry<'a> {
+    token: Option<Token>,
+    value: &'a Value,
+}
+
+/// What a column lists, and which entry is highlighted with what style.
+struct Column<'a> {
+    entries: Vec<Entry<'a>>,
+    selected: Option<(usize, ContentStyle)>,
+}
+
+/// Renders the selection as Miller columns: the level of its parent on the left, its own
+/// level in the middle and a preview of the selected value on the right.
+pub fn render_columns<W: Write>(
+    out: &mut W,
+    json: &Json,
+    offset: (usize, usize),
+    size: (usize, usize),
+) -> anyhow::Result<()> {
+    let tokens = json.tokens();
+    let (width, height) = size;
+    let left_width = width / 4;
+    let middle_width = width * 3 / 8;
+    let right_width = width.saturating_sub(left_width + middle_width + 2);
+
+    let left = match tokens.split_last() {
+        Some((_, parent)) => level(json, parent, STYLE_COLUMN_PARENT),
+        None => Column {
+            entries: vec![],
+            selected: None,
+        },
+    };
+    let middle = level(json, &tokens, STYLE_SELECTED_ENTRY);
+
+    let left = column_lines(&left, left_width, height);
+    let middle = column_lines(&middle, middle_width, height);
+    let right = preview(json, &tokens, right_width, height);
+
+    for y in 0..height {
+        queue!(
+            out,
+            cursor::MoveTo(offset.0 as u16, (offset.1 + y) as u16),
+            terminal::Clear(terminal::ClearType::CurrentLine)
+        )?;
+        for (i, lines) in [&left, &middle, &right].into_iter().enumerate() {
+            if i > 0 {
+                queue!(out, PrintStyledContent(styled(STYLE_COLUMN_BORDER, "│")))?;
+            }
+            for (text, style) in lines.get(y).map_or(&[][..], |l| &l[..]) {
+                queue!(out, PrintStyledContent(styled(*style, text)))?;
+            }
+            let used: usize = lines
+                .get(y)
+                .map_or(0, |l| l.iter().map(|(t, _)| t.width()).sum());
+            let column_width = [left_width, middle_width, right_width][i];
+            queue!(out, Print(" ".repeat(column_width.saturating_sub(used))))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The entries next to the last token of `tokens`, or the root if there are none.
+fn level<'a>(json: &'a Json, tokens: &[Token], style: ContentStyle) -> Column<'a> {
+    let Some((last, parent)) = tokens.split_last() else {
+        return Column {
+            entries: vec![Entry {
+                token: None,
+                value: &json.value,
+            }],
+            selected: Some((0, style)),
+        };
+    };
+
+    let entries: Vec<Entry> = json
+        .children(parent)
+        .into_iter()
+        .map(|(token, value)| Entry {
+            token: Some(token),
+            value,
+        })
+        .collect();
+    let selected = entries
+        .iter()
+        .position(|e| e.token.as_ref() == Some(last))
+        .map(|i| (i, style));
+
+    Column { entries, selected }
+}
+
+/// The members of the selected container, or the full text of the selected primitive.
+fn preview(json: &Json, tokens: &[Token], width: usize, height: usize) -> Vec<Line> {
+    match json.value() {
+        Some(Value::Object(_) | Value::Array(_)) => {
+            let entries: Vec<Entry> = json
+                .children(tokens)
+                .into_iter()
+                .map(|(token, value)| Entry {
+                    token: Some(token),
+                    value,
+                })
+                .collect();
+            if entries.is_empty() {
+                vec![vec![(" empty".to_string(), STYLE_LINE_NUMBER)]]
+            } else {
+                let children = Column {
+                    entries,
+                    selected: None,
+                };
+                column_lines(&children, width, height)
+            }
+        }
+        Some(value) => wrap_lines(value, width, height),
+        None => vec![],
+    }
+}
+
+/// Lays out the entries of a column in `height` lines, scrolled to keep the highlighted one
+/// near the middle.
+fn column_lines(column: &Column, width: usize, height: usize) -> Vec<Line> {
+    let len = column.entries.len();
+    let selected = column.selected.map_or(0, |(i, _)| i);
+    let top = if len <= height {
+        0
+    } else {
+        selected.saturating_sub(height / 2).min(len - height)
+    };
+
+    column
+        .entries
+        .iter()
+        .enumerate()
+        .skip(top)
+        .take(height)
+        .map(|(i, entry)| {
+            let label = entry.token.as_ref().map_or(".".to_string(), token_label);
+            let StyledString(summary, class) = summarize(entry.value);
+            let label = truncate(&format!(" {} ", label), width);
+            let summary = truncate(&summary, width - label.width());
+
+            match column.selected {
+                Some((s, style)) if s == i => {
+                    let text = format!("{}{}", label, summary);
+                    vec![(fit(&text, width), style)]
+                }
+                _ => vec![(label, StyleClass::Key.style()), (summary, class.style())],
+            }
+        })
+        .collect()
+}
+
+/// The full text of a primitive value, wrapped to `width`, up to `height` lines.
+fn wrap_lines(value: &Value, width: usize, height: usize) -> Vec<Line> {
+    let StyledString(text, class) = match value {
+        // Show strings decoded, except for control characters
+        Value::String(s) => StyledString(
+            s.chars()
+                .flat_map(|c| match c {
+                    '\n' => vec!['\n'],
+                    c if c.is_control() => c.escape_default().collect(),
+                    c => vec![c],
+                })
+                .collect(),
+            StyleClass::String,
+        ),
+        _ => summarize(value),
+    };
+
+    let mut wrapped = vec![];
+    for line in text.split('\n') {
+        if line.is_empty() {
+            wrapped.push(vec![]);
+        }
+        let mut rest = line;
+        while !rest.is_empty() {
+            let mut chunk = take_width(rest, width.saturating_sub(1));
+            if chunk.is_empty() {
+                // Too narrow for the next char, which still has to go somewhere
+                chunk = &rest[..rest.chars().next().map_or(0, char::len_utf8)];
+            }
+            wrapped.push(vec![(format!(" {}", chunk), class.style())]);
+            rest = &rest[chunk.len()..];
+        }
+    }
+    wrapped.truncate(height);
+    wrapped
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn texts(lines: &[Line]) -> Vec<String> {
+        lines
+            .iter()
+            .map(|l| l.iter().map(|(t, _)| t.as_str()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn levels() {
+        let json = Json::from(json!({"a": 1, "b": {"c": "x"}, "d": []}));
+        let root = level(&json, &[], STYLE_SELECTED_ENTRY);
+        assert_eq!(root.entries.len(), 1);
+        assert_eq!(root.selected.map(|(i, _)| i), Some(0));
+
+        let b = vec![Token::Key("b".to_string())];
+        let column = level(&json, &b, STYLE_SELECTED_ENTRY);
+        assert_eq!(column.entries.len(), 3);
+        assert_eq!(column.selected.map(|(i, _)| i), Some(1));
+        // The selected entry is one line padded to the width, the others a label and a summary
+        let lines = column_lines(&column, 8, 5);
+        assert_eq!(texts(&lines), vec![" a 1", " b {1}  ", " d [0]"]);
+        assert_eq!(lines[1].len(), 1);
+        assert_eq!(lines[0].len(), 2);
+        // Entries are truncated to the width
+        assert_eq!(texts(&column_lines(&column, 4, 5))[1], " b …");
+    }
+
+    #[test]
+    fn scrolling() {
+        let json = Json::from(json!((0..10).collect::<Vec<_>>()));
+        let at = |i| level(&json, &[Token::Index(i)], STYLE_SELECTED_ENTRY);
+
+        // The selection stays near the middle, without scrolling past either end
+        let first = |i| texts(&column_lines(&at(i), 10, 4))[0].clone();
+        assert_eq!(first(1), " [0] 0");
+        assert_eq!(first(5), " [3] 3");
+        assert_eq!(first(9), " [6] 6");
+    }
+
+    #[test]
+    fn empty_container() {
+        let mut json = Json::from(json!({"a": {}, "b": "text"}));
+        json.set_selection(vec![Token::Key("a".to_string())]);
+        assert_eq!(
+            texts(&preview(&json, &json.tokens(), 10, 5)),
+            vec![" empty"]
+        );
+
+        json.set_selection(vec![Token::Key("b".to_string())]);
+        assert_eq!(texts(&preview(&json, &json.tokens(), 10, 5)), vec![" text"]);
+
+        // Wide characters are wrapped by the columns they take
+        let value = json!("日本語のテキスト\nab");
+        assert_eq!(
+            texts(&wrap_lines(&value, 7, 5)),
+            vec![" 日本語", " のテキ", " スト", " ab"]
+        );
+    }
+}
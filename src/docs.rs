@@ -0,0 +1,135 @@
+use std::io::Write;
+
+use crossterm::{
+    cursor, queue,
+    style::{ContentStyle, PrintStyledContent},
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::json::summarize;
+use crate::schema::Annotation;
+use crate::style::{styled, take_width, Line, StyleClass, STYLE_COLUMN_BORDER, STYLE_LINE_NUMBER};
+
+/// Widest the schema panel gets, border included.
+pub const DOCS_MAX_WIDTH: usize = 40;
+
+/// Renders what the schema says about the selection in a panel on the right of the body.
+pub fn render_docs<W: Write>(
+    out: &mut W,
+    annotation: Option<&Annotation>,
+    offset: (usize, usize),
+    size: (usize, usize),
+) -> anyhow::Result<()> {
+    let (width, height) = size;
+    let text_width = width.saturating_sub(3).max(1);
+    let lines = annotation.map_or_else(
+        || {
+            vec![vec![(
+                "Not described by the schema".to_string(),
+                STYLE_LINE_NUMBER,
+            )]]
+        },
+        |a| annotation_lines(a, text_width),
+    );
+
+    for y in 0..height {
+        queue!(
+            out,
+            cursor::MoveTo(offset.0 as u16, (offset.1 + y) as u16),
+            PrintStyledContent(styled(STYLE_COLUMN_BORDER, "│ "))
+        )?;
+        let mut col = 0;
+        for (text, style) in lines.get(y).into_iter().flatten() {
+            let text = take_width(text, text_width - col);
+            col += text.width();
+            queue!(out, PrintStyledContent(styled(*style, text)))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn annotation_lines(annotation: &Annotation, width: usize) -> Vec<Line> {
+    let mut lines: Vec<Line> = vec![];
+    if let Some(ref title) = annotation.title {
+        lines.extend(
+            wrap(title, width)
+                .into_iter()
+                .map(|l| vec![(l, StyleClass::Key.style())]),
+        );
+    }
+    if !annotation.types.is_empty() {
+        lines.push(vec![
+            ("type ".to_string(), STYLE_LINE_NUMBER),
+            (annotation.types.join(" | "), ContentStyle::new()),
+        ]);
+    }
+    if let Some(ref values) = annotation.values {
+        lines.push(vec![("one of".to_string(), STYLE_LINE_NUMBER)]);
+        for value in values {
+            let class = summarize(value).1;
+            lines.push(vec![(format!("  {}", value), class.style())]);
+        }
+    }
+    if let Some(ref description) = annotation.description {
+        if !lines.is_empty() {
+            lines.push(vec![]);
+        }
+        lines.extend(
+            wrap(description, width)
+                .into_iter()
+                .map(|l| vec![(l, ContentStyle::new())]),
+        );
+    }
+    if lines.is_empty() {
+        lines.push(vec![("No description".to_string(), STYLE_LINE_NUMBER)]);
+    }
+
+    lines
+}
+
+/// Wraps text at spaces to lines of at most `width` columns, breaking longer words.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = vec![];
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let len = line.width();
+            if len > 0 && len + 1 + word.width() > width {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+            while line.width() > width {
+                let mut head = take_width(&line, width).len();
+                if head == 0 {
+                    head = line.chars().next().map_or(0, char::len_utf8);
+                }
+                let rest = line.split_off(head);
+                lines.push(std::mem::replace(&mut line, rest));
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrapping() {
+        assert_eq!(
+            wrap("The port to listen on,\nor 0 for any", 10),
+            vec!["The port", "to listen", "on,", "or 0 for", "any"]
+        );
+        assert_eq!(wrap("abcdefghij klm", 4), vec!["abcd", "efgh", "ij", "klm"]);
+        assert_eq!(
+            wrap("日本語のポート 番号", 5),
+            vec!["日本", "語の", "ポー", "ト", "番号"]
+        );
+    }
+}
@@ -5,6 +5,24 @@ use crossterm::event::{
     self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseEvent, MouseEventKind,
 };
 
+use crate::prompt::Edit;
+
+/// Determines how key presses are interpreted.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Normal,
+    Search,
+    Finder,
+    Results,
+    Goto,
+    SortBy,
+    Query,
+    Schema,
+    Types,
+    Table,
+    Help,
+}
+
 pub enum Direction {
     Up,
     Down,
@@ -27,29 +45,90 @@ pub enum Action {
     ScrollRightMax,
     ToggleFold,
     ToggleFoldAll,
+    UnfoldMatches,
     Sort,
     SortReverse,
+    SortRecursive,
+    SortReverseRecursive,
+    SortByEdit(Edit),
+    SortByConfirm,
+    SortByCancel,
     Search,
     ShowHelp,
     DismissHelp,
     RepeatSearch,
     RepeatSearchBackward,
+    HighlightSame,
+    SiblingPaths,
+    CopySiblingPath,
     Filter,
     ClearSearch,
-    SearchInput(char),
-    SearchBackspace,
+    SearchEdit(Edit),
+    SearchHistory(Direction),
+    SearchToggleDiacritics,
     SearchConfirm,
     SearchCancel,
+    Find,
+    FinderEdit(Edit),
+    FinderSelect(Direction),
+    FinderConfirm,
+    FinderCancel,
+    ToggleResults,
+    ResultsSelect(Direction),
+    ResultsConfirm,
+    ResultsCancel,
+    Goto,
+    GotoEdit(Edit),
+    GotoComplete,
+    GotoConfirm,
+    GotoCancel,
+    Query,
+    QueryEdit(Edit),
+    QueryConfirm,
+    QueryCancel,
+    ToggleQuery,
+    QuitWithQuery,
+    ZoomIn,
+    ZoomOut,
+    InferSchema,
+    OutputTypes,
+    CopyTypes,
+    TypesEdit(Edit),
+    TypesConfirm,
+    TypesCancel,
+    Schema,
+    SchemaEdit(Edit),
+    SchemaConfirm,
+    SchemaCancel,
+    NextError,
+    PrevError,
+    ToggleDocs,
+    FollowRef,
+    JumpBack,
     OutputSelectionPretty,
     OutputValuePretty,
     OutputSelectionRaw,
     OutputValueRaw,
+    OutputMatchPointers,
+    OutputMatchRecords,
     CopySelectionPretty,
     CopyValuePretty,
     CopySelectionRaw,
     CopyValueRaw,
+    CopyMatchPointers,
+    CopyMatchRecords,
+    Table,
+    TableMove(Direction),
+    TablePage(Direction),
+    TableTop,
+    TableBottom,
+    TableResize(Direction),
+    TableHideColumn,
+    TableShowColumns,
+    TableClose,
     ToggleLineNumbers,
     ToggleLineWrapping,
+    CycleView,
     MouseScroll(Direction),
     Ignore,
 }
@@ -57,11 +136,7 @@ pub enum Action {
 use Action::*;
 use KeyCode::*;
 
-pub fn read_event(
-    search_mode: bool,
-    help_mode: bool,
-    timeout: Option<Duration>,
-) -> Result<Option<Action>> {
+pub fn read_event(mode: Mode, timeout: Option<Duration>) -> Result<Option<Action>> {
     if let Some(duration) = timeout {
         if !event::poll(duration)? {
             return Ok(None);
@@ -74,74 +149,191 @@ pub fn read_event(
         Event::Key(KeyEvent {
             kind: KeyEventKind::Press,
             ..
-        }) if help_mode => DismissHelp,
+        }) if mode == Mode::Help => DismissHelp,
 
         Event::Key(KeyEvent {
             code,
             modifiers,
             kind: KeyEventKind::Press,
             ..
-        }) => {
-            if search_mode {
-                match (code, modifiers) {
-                    (Esc, _) | (Char('c'), KeyModifiers::CONTROL) => SearchCancel,
-                    (Enter, _) => SearchConfirm,
-                    (Backspace, _) => SearchBackspace,
-                    (Char(c), KeyModifiers::NONE | KeyModifiers::SHIFT) => SearchInput(c),
-                    _ => Ignore,
+        }) => match mode {
+            Mode::Search => match (code, modifiers) {
+                (Esc, _) | (Char('c'), KeyModifiers::CONTROL) => SearchCancel,
+                (Enter, _) => SearchConfirm,
+                (Up, _) | (Char('p'), KeyModifiers::CONTROL) => SearchHistory(Direction::Up),
+                (Down, _) | (Char('n'), KeyModifiers::CONTROL) => SearchHistory(Direction::Down),
+                (Char('d'), KeyModifiers::ALT) => SearchToggleDiacritics,
+                _ => Edit::from_key(code, modifiers).map_or(Ignore, SearchEdit),
+            },
+            Mode::Finder => match (code, modifiers) {
+                (Esc, _) | (Char('c'), KeyModifiers::CONTROL) => FinderCancel,
+                (Enter, _) => FinderConfirm,
+                (Up, _) | (Char('p' | 'k'), KeyModifiers::CONTROL) => FinderSelect(Direction::Up),
+                (Down, _) | (Char('n' | 'j'), KeyModifiers::CONTROL) => {
+                    FinderSelect(Direction::Down)
                 }
-            } else {
-                match (code, modifiers) {
-                    (Char('q'), _) | (Char('c'), KeyModifiers::CONTROL) => Quit,
-                    (Char('?'), _) => ShowHelp,
-
-                    (Char('h') | Left, _) => Move(Direction::Left),
-                    (Char('j') | Down, _) => Move(Direction::Down),
-                    (Char('k') | Up, _) => Move(Direction::Up),
-                    (Char('l') | Right, _) => Move(Direction::Right),
-
-                    (Char('y'), KeyModifiers::CONTROL) => ScrollLine(Direction::Up),
-                    (Char('e'), KeyModifiers::CONTROL) => ScrollLine(Direction::Down),
-                    (Char('u'), _) => ScrollHalf(Direction::Up),
-                    (Char('d'), _) => ScrollHalf(Direction::Down),
-                    (Char('b'), _) => ScrollFull(Direction::Up),
-                    (Char('f'), _) => ScrollFull(Direction::Down),
-                    (Char('g'), _) => ScrollTop,
-                    (Char('G'), _) => ScrollBottom,
-                    (Char('<'), _) => ScrollLeft,
-                    (Char('>'), _) => ScrollRight,
-                    (Char('^') | Char('H'), _) => ScrollLeftMax,
-                    (Char('$') | Char('L'), _) => ScrollRightMax,
-
-                    (Char(' ') | Enter, _) => ToggleFold,
-                    (Char('z'), _) => ToggleFoldAll,
-
-                    (Char('/'), _) => Search,
-                    (Char('n'), _) => RepeatSearch,
-                    (Char('N'), _) => RepeatSearchBackward,
-                    (Esc, _) => ClearSearch,
-
-                    (Char('s'), _) => Sort,
-                    (Char('S'), _) => SortReverse,
-                    (Char('&'), _) => Filter,
-
-                    (Char('y'), KeyModifiers::NONE) => CopySelectionPretty,
-                    (Char('Y'), KeyModifiers::NONE) => CopyValuePretty,
-                    (Char('y'), KeyModifiers::ALT) => CopySelectionRaw,
-                    (Char('Y'), KeyModifiers::ALT) => CopyValueRaw,
-
-                    (Char('o'), KeyModifiers::NONE) => OutputSelectionPretty,
-                    (Char('O'), KeyModifiers::NONE) => OutputValuePretty,
-                    (Char('o'), KeyModifiers::ALT) => OutputSelectionRaw,
-                    (Char('O'), KeyModifiers::ALT) => OutputValueRaw,
-
-                    (Char('#'), _) => ToggleLineNumbers,
-                    (Char('w'), _) => ToggleLineWrapping,
-
-                    _ => Ignore,
+                _ => Edit::from_key(code, modifiers).map_or(Ignore, FinderEdit),
+            },
+            Mode::Results => match (code, modifiers) {
+                (Esc | Char('q' | 'M'), _) | (Char('c'), KeyModifiers::CONTROL) => ResultsCancel,
+                (Enter, _) => ResultsConfirm,
+                (Up | Char('k'), _) | (Char('p'), KeyModifiers::CONTROL) => {
+                    ResultsSelect(Direction::Up)
                 }
-            }
-        }
+                (Down | Char('j'), _) | (Char('n'), KeyModifiers::CONTROL) => {
+                    ResultsSelect(Direction::Down)
+                }
+                _ => Ignore,
+            },
+            Mode::Goto => match (code, modifiers) {
+                (Esc, _) | (Char('c'), KeyModifiers::CONTROL) => GotoCancel,
+                (Enter, _) => GotoConfirm,
+                (Tab, _) => GotoComplete,
+                _ => Edit::from_key(code, modifiers).map_or(Ignore, GotoEdit),
+            },
+            Mode::SortBy => match (code, modifiers) {
+                (Esc, _) | (Char('c'), KeyModifiers::CONTROL) => SortByCancel,
+                (Enter, _) => SortByConfirm,
+                _ => Edit::from_key(code, modifiers).map_or(Ignore, SortByEdit),
+            },
+            Mode::Query => match (code, modifiers) {
+                (Esc, _) | (Char('c'), KeyModifiers::CONTROL) => QueryCancel,
+                (Enter, _) => QueryConfirm,
+                _ => Edit::from_key(code, modifiers).map_or(Ignore, QueryEdit),
+            },
+            Mode::Schema => match (code, modifiers) {
+                (Esc, _) | (Char('c'), KeyModifiers::CONTROL) => SchemaCancel,
+                (Enter, _) => SchemaConfirm,
+                _ => Edit::from_key(code, modifiers).map_or(Ignore, SchemaEdit),
+            },
+            Mode::Types => match (code, modifiers) {
+                (Esc, _) | (Char('c'), KeyModifiers::CONTROL) => TypesCancel,
+                (Enter, _) => TypesConfirm,
+                _ => Edit::from_key(code, modifiers).map_or(Ignore, TypesEdit),
+            },
+            Mode::Table => match (code, modifiers) {
+                (Esc | Enter | Char('q' | 't'), _) => TableClose,
+                (Char('c'), KeyModifiers::CONTROL) => Quit,
+                (Char('?'), _) => ShowHelp,
+
+                (Char('h') | Left, _) => TableMove(Direction::Left),
+                (Char('j') | Down, _) => TableMove(Direction::Down),
+                (Char('k') | Up, _) => TableMove(Direction::Up),
+                (Char('l') | Right, _) => TableMove(Direction::Right),
+                (Char('u') | PageUp, _) => TablePage(Direction::Up),
+                (Char('d') | PageDown, _) => TablePage(Direction::Down),
+                (Char('g') | Home, _) => TableTop,
+                (Char('G') | End, _) => TableBottom,
+
+                (Char('-'), _) => TableResize(Direction::Left),
+                (Char('+' | '='), _) => TableResize(Direction::Right),
+                (Char('x'), _) => TableHideColumn,
+                (Char('X'), _) => TableShowColumns,
+
+                (Char('y'), KeyModifiers::NONE) => CopySelectionPretty,
+                (Char('Y'), KeyModifiers::NONE) => CopyValuePretty,
+                (Char('y'), KeyModifiers::ALT) => CopySelectionRaw,
+                (Char('Y'), KeyModifiers::ALT) => CopyValueRaw,
+
+                (Char('o'), KeyModifiers::NONE) => OutputSelectionPretty,
+                (Char('O'), KeyModifiers::NONE) => OutputValuePretty,
+                (Char('o'), KeyModifiers::ALT) => OutputSelectionRaw,
+                (Char('O'), KeyModifiers::ALT) => OutputValueRaw,
+
+                _ => Ignore,
+            },
+            Mode::Normal | Mode::Help => match (code, modifiers) {
+                (Char('q'), _) | (Char('c'), KeyModifiers::CONTROL) => Quit,
+                (Char('Q'), _) => QuitWithQuery,
+                (Char('?'), _) => ShowHelp,
+
+                (Char('h') | Left, _) => Move(Direction::Left),
+                (Char('j') | Down, _) => Move(Direction::Down),
+                (Char('k') | Up, _) => Move(Direction::Up),
+                (Char('l') | Right, _) => Move(Direction::Right),
+
+                (Char('y'), KeyModifiers::CONTROL) => ScrollLine(Direction::Up),
+                (Char('e'), KeyModifiers::CONTROL) => ScrollLine(Direction::Down),
+                (Char('u'), _) => ScrollHalf(Direction::Up),
+                (Char('d'), _) => ScrollHalf(Direction::Down),
+                (Char('b'), _) => ScrollFull(Direction::Up),
+                (Char('f'), _) => ScrollFull(Direction::Down),
+                (Char('g'), _) => ScrollTop,
+                (Char('G'), _) => ScrollBottom,
+                (Char('<'), _) => ScrollLeft,
+                (Char('>'), _) => ScrollRight,
+                (Char('^') | Char('H'), _) => ScrollLeftMax,
+                (Char('$') | Char('L'), _) => ScrollRightMax,
+
+                (Char(' ') | Enter, _) => ToggleFold,
+                (Char('z'), _) => ToggleFoldAll,
+                (Char('Z'), _) => UnfoldMatches,
+
+                (Char('/'), _) => Search,
+                (Char('n'), _) => RepeatSearch,
+                (Char('N'), _) => RepeatSearchBackward,
+                (Char('*'), _) => HighlightSame,
+                (Char('a'), _) => SiblingPaths,
+                (Char('A'), _) => CopySiblingPath,
+                (Esc, _) => ClearSearch,
+                (Char('p'), KeyModifiers::NONE) => Find,
+                (Char('M'), _) => ToggleResults,
+                (Char(':'), _) => Goto,
+                (Char('|'), _) => Query,
+                (Tab, _) => ToggleQuery,
+                (Char('+'), _) => ZoomIn,
+                (Char('-'), _) => ZoomOut,
+                (Char('i'), _) => InferSchema,
+                (Char('T'), m) if m.contains(KeyModifiers::ALT) => CopyTypes,
+                (Char('T'), _) => OutputTypes,
+                (Char('V'), _) => Schema,
+                (Char('e'), KeyModifiers::ALT) => PrevError,
+                (Char('e'), _) => NextError,
+                (Char('D'), _) => ToggleDocs,
+                (Char('r'), _) => FollowRef,
+                (Char('R'), _) => JumpBack,
+
+                (Char('s'), KeyModifiers::ALT) => SortRecursive,
+                (Char('S'), m) if m.contains(KeyModifiers::ALT) => SortReverseRecursive,
+                (Char('s'), _) => Sort,
+                (Char('S'), _) => SortReverse,
+                (Char('&'), _) => Filter,
+                (Char('t'), _) => Table,
+
+                (Char('y'), KeyModifiers::NONE) => CopySelectionPretty,
+                (Char('Y'), KeyModifiers::NONE) => CopyValuePretty,
+                (Char('y'), KeyModifiers::ALT) => CopySelectionRaw,
+                (Char('Y'), KeyModifiers::ALT) => CopyValueRaw,
+
+                (Char('o'), KeyModifiers::NONE) => OutputSelectionPretty,
+                (Char('O'), KeyModifiers::NONE) => OutputValuePretty,
+                (Char('o'), KeyModifiers::ALT) => OutputSelectionRaw,
+                (Char('O'), KeyModifiers::ALT) => OutputValueRaw,
+
+                (Char('P'), m) if m.contains(KeyModifiers::ALT) => CopyMatchPointers,
+                (Char('P'), _) => OutputMatchPointers,
+                (Char('E'), m) if m.contains(KeyModifiers::ALT) => CopyMatchRecords,
+                (Char('E'), _) => OutputMatchRecords,
+
+                (Char('#'), _) => ToggleLineNumbers,
+                (Char('w'), _) => ToggleLineWrapping,
+                (Char('v'), _) => CycleView,
+
+                _ => Ignore,
+            },
+        },
+
+        Event::Paste(s) => match mode {
+            Mode::Search => SearchEdit(Edit::Paste(s)),
+            Mode::Finder => FinderEdit(Edit::Paste(s)),
+            Mode::Results => Ignore,
+            Mode::Goto => GotoEdit(Edit::Paste(s)),
+            Mode::SortBy => SortByEdit(Edit::Paste(s)),
+            Mode::Query => QueryEdit(Edit::Paste(s)),
+            Mode::Schema => SchemaEdit(Edit::Paste(s)),
+            Mode::Types => TypesEdit(Edit::Paste(s)),
+            Mode::Normal | Mode::Table | Mode::Help => Ignore,
+        },
 
         Event::Mouse(MouseEvent { kind, .. }) => match kind {
             MouseEventKind::ScrollUp => MouseScroll(Direction::Up),
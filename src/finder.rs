@@ -0,0 +1,317 @@
+use std::collections::HashSet;
+use std::io::Write;
+
+use crossterm::{
+    cursor, queue,
+    style::{Print, PrintStyledContent},
+    terminal,
+};
+use unicode_width::UnicodeWidthChar;
+
+use crate::json::{Pointer, PointerMap, Token};
+use crate::prompt::{Edit, Prompt};
+use crate::style::{
+    styled, STYLE_FINDER_MATCH, STYLE_FINDER_SELECTED, STYLE_SEARCH_STATUS, STYLE_SELECTION_BAR,
+};
+
+/// Maximum number of rows taken up by the finder (prompt included).
+pub const FINDER_HEIGHT: usize = 12;
+
+const SCORE_MATCH: i64 = 16;
+const BONUS_CONSECUTIVE: i64 = 12;
+const BONUS_BOUNDARY: i64 = 10;
+const PENALTY_GAP: i64 = 1;
+
+#[derive(Clone)]
+pub struct FuzzyMatch {
+    pub score: i64,
+    pub positions: Vec<usize>, // Char indices of the matched characters
+}
+
+/// Matches `query` as a subsequence of `candidate`, scoring consecutive runs and
+/// matches at the start of path segments higher.
+/// Matching is case-insensitive unless the query contains an uppercase character.
+pub fn fuzzy_match(candidate: &str, query: &str) -> Option<FuzzyMatch> {
+    let case_sensitive = query.chars().any(char::is_uppercase);
+    let normalize = |c: char| {
+        if case_sensitive {
+            c
+        } else {
+            // The first char of the lowercase form, to keep one position per char
+            c.to_lowercase().next().unwrap_or(c)
+        }
+    };
+
+    let chars: Vec<char> = candidate.chars().collect();
+    let needle: Vec<char> = query.chars().map(normalize).collect();
+
+    if needle.is_empty() {
+        return Some(FuzzyMatch {
+            score: 0,
+            positions: vec![],
+        });
+    }
+
+    // Find the leftmost end of a complete match...
+    let mut qi = 0;
+    let mut end = None;
+    for (i, &c) in chars.iter().enumerate() {
+        if normalize(c) == needle[qi] {
+            qi += 1;
+            if qi == needle.len() {
+                end = Some(i);
+                break;
+            }
+        }
+    }
+    let end = end?;
+
+    // ...then walk back from there to get the tightest window ending at that position.
+    let mut positions = Vec::with_capacity(needle.len());
+    let mut qi = needle.len();
+    for i in (0..=end).rev() {
+        if normalize(chars[i]) == needle[qi - 1] {
+            positions.push(i);
+            qi -= 1;
+            if qi == 0 {
+                break;
+            }
+        }
+    }
+    positions.reverse();
+
+    let mut score = 0;
+    let mut prev: Option<usize> = None;
+    for &pos in &positions {
+        score += SCORE_MATCH;
+        if pos == 0 || matches!(chars[pos - 1], '.' | '_' | '-' | ' ') {
+            score += BONUS_BOUNDARY;
+        }
+        match prev {
+            Some(p) if p + 1 == pos => score += BONUS_CONSECUTIVE,
+            Some(p) => score -= PENALTY_GAP * (pos - p - 1) as i64,
+            None => {}
+        }
+        prev = Some(pos);
+    }
+
+    Some(FuzzyMatch { score, positions })
+}
+
+struct Entry {
+    tokens: Vec<Token>,
+    path: String,
+}
+
+/// A ranked list of every path in the document, filtered by a fuzzy query.
+pub struct Finder {
+    pub query: Prompt,
+    entries: Vec<Entry>,
+    results: Vec<(usize, FuzzyMatch)>,
+    selected: usize,
+    /// Selection and folds to restore if the finder is cancelled
+    pub origin: (Vec<Token>, HashSet<Vec<Token>>),
+}
+
+impl Finder {
+    pub fn new(pointer_map: &PointerMap, origin: (Vec<Token>, HashSet<Vec<Token>>)) -> Self {
+        let mut paths: Vec<(&Vec<Token>, usize)> = pointer_map
+            .iter()
+            .map(|(tokens, data)| (tokens, data.bounds.0))
+            .collect();
+        // Present paths in document order
+        paths.sort_by_key(|&(_, line)| line);
+
+        let entries = paths
+            .into_iter()
+            .map(|(tokens, _)| Entry {
+                tokens: tokens.clone(),
+                path: Pointer::period_path(tokens),
+            })
+            .collect();
+
+        let mut finder = Self {
+            query: Prompt::new(),
+            entries,
+            results: vec![],
+            selected: 0,
+            origin,
+        };
+        finder.update();
+
+        finder
+    }
+
+    /// Re-ranks the entries against the current query.
+    pub fn update(&mut self) {
+        let query = self.query.text();
+        let mut results: Vec<(usize, FuzzyMatch)> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(i, e)| fuzzy_match(&e.path, &query).map(|m| (i, m)))
+            .collect();
+
+        // Best score first; shorter paths, then document order, break ties
+        results.sort_by(|(a, ma), (b, mb)| {
+            mb.score
+                .cmp(&ma.score)
+                .then(
+                    self.entries[*a]
+                        .path
+                        .len()
+                        .cmp(&self.entries[*b].path.len()),
+                )
+                .then(a.cmp(b))
+        });
+
+        self.results = results;
+        self.selected = 0;
+    }
+
+    /// Edits the query, re-ranking the entries if it changed.
+    pub fn edit(&mut self, edit: Edit) -> bool {
+        let changed = self.query.edit(edit);
+        if changed {
+            self.update();
+        }
+        changed
+    }
+
+    pub fn select_next(&mut self) -> bool {
+        if self.selected + 1 < self.results.len() {
+            self.selected += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn select_prev(&mut self) -> bool {
+        if self.selected > 0 {
+            self.selected -= 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns the path of the selected result.
+    pub fn selection(&self) -> Option<Vec<Token>> {
+        self.results
+            .get(self.selected)
+            .map(|(i, _)| self.entries[*i].tokens.clone())
+    }
+
+    pub fn status_text(&self) -> String {
+        format!("{}/{}", self.results.len(), self.entries.len())
+    }
+}
+
+/// Renders the finder prompt and its results, starting at row `top`.
+pub fn render_finder<W: Write>(
+    out: &mut W,
+    finder: &Finder,
+    top: usize,
+    size: (usize, usize),
+) -> anyhow::Result<()> {
+    let (width, height) = size;
+    let rows = height.saturating_sub(1);
+
+    let status = finder.status_text();
+
+    queue!(
+        out,
+        cursor::MoveTo(0, top as u16),
+        terminal::Clear(terminal::ClearType::CurrentLine)
+    )?;
+    finder
+        .query
+        .render(out, "> ", width.saturating_sub(status.len() + 2))?;
+
+    queue!(
+        out,
+        cursor::MoveTo(width.saturating_sub(status.len() + 1) as u16, top as u16),
+        PrintStyledContent(styled(STYLE_SEARCH_STATUS, &status))
+    )?;
+
+    // Keep the selected result in view
+    let offset = finder.selected.saturating_sub(rows.saturating_sub(1));
+
+    for row in 0..rows {
+        let y = (top + 1 + row) as u16;
+        queue!(
+            out,
+            cursor::MoveTo(0, y),
+            terminal::Clear(terminal::ClearType::CurrentLine)
+        )?;
+
+        let Some((idx, m)) = finder.results.get(offset + row) else {
+            continue;
+        };
+        let is_selected = offset + row == finder.selected;
+
+        if is_selected {
+            queue!(
+                out,
+                PrintStyledContent(styled(STYLE_SELECTION_BAR, "┃")),
+                Print(" ")
+            )?;
+        } else {
+            queue!(out, Print("  "))?;
+        }
+
+        // Positions are in order, so the next one to highlight is enough
+        let mut positions = m.positions.iter().peekable();
+        let mut col = 2;
+        for (i, ch) in finder.entries[*idx].path.chars().enumerate() {
+            col += ch.width().unwrap_or(0);
+            if col > width {
+                break;
+            }
+            let matched = positions.next_if_eq(&&i).is_some();
+            let style = if matched {
+                STYLE_FINDER_MATCH
+            } else if is_selected {
+                STYLE_FINDER_SELECTED
+            } else {
+                STYLE_SEARCH_STATUS
+            };
+            queue!(out, PrintStyledContent(styled(style, ch)))?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_subsequence() {
+        assert!(fuzzy_match(".spec.containers.0.image", "sci").is_some());
+        assert!(fuzzy_match(".spec.containers.0.image", "xyz").is_none());
+        assert_eq!(fuzzy_match(".abc", "ac").unwrap().positions, vec![1, 3]);
+    }
+
+    #[test]
+    fn fuzzy_smart_case() {
+        assert!(fuzzy_match(".Name", "name").is_some());
+        assert!(fuzzy_match(".name", "Name").is_none());
+        assert!(fuzzy_match(".Été", "été").is_some());
+        assert!(fuzzy_match(".été", "Été").is_none());
+    }
+
+    #[test]
+    fn fuzzy_prefers_tight_and_boundary_matches() {
+        let tight = fuzzy_match(".image", "img").unwrap();
+        let loose = fuzzy_match(".i.m.g", "img").unwrap();
+        assert!(tight.score > fuzzy_match(".xixmxg", "img").unwrap().score);
+        assert!(loose.score > fuzzy_match(".xixmxg", "img").unwrap().score);
+
+        let consecutive = fuzzy_match(".meta.name", "name").unwrap();
+        let scattered = fuzzy_match(".nxaxmxe", "name").unwrap();
+        assert!(consecutive.score > scattered.score);
+    }
+}
@@ -0,0 +1,47 @@
+use serde_json::Value;
+
+use crate::json::completions;
+use crate::prompt::{Edit, Prompt};
+
+/// State of the `:` prompt used to jump to a path.
+pub struct GotoPrompt {
+    pub input: Prompt,
+    completion: Option<(Vec<String>, usize)>,
+}
+
+impl GotoPrompt {
+    pub fn new() -> Self {
+        Self {
+            input: Prompt::new(),
+            completion: None,
+        }
+    }
+
+    pub fn edit(&mut self, edit: Edit) {
+        if self.input.edit(edit) {
+            self.completion = None;
+        }
+    }
+
+    /// Completes the segment being typed, cycling through the candidates on repeated calls.
+    pub fn complete(&mut self, value: &Value) -> bool {
+        let (candidates, index) = match self.completion {
+            Some((ref candidates, ref mut index)) => {
+                *index = (*index + 1) % candidates.len();
+                (candidates, *index)
+            }
+            None => {
+                let candidates = completions(value, &self.input.text());
+                if candidates.is_empty() {
+                    return false;
+                }
+                let (candidates, _) = self.completion.insert((candidates, 0));
+                (&*candidates, 0)
+            }
+        };
+
+        self.input.set_text(&candidates[index]);
+
+        true
+    }
+}
@@ -20,21 +20,43 @@ const HELP_CONTENT: &[(&str, &str)] = &[
     ("Actions", ""),
     ("Space/Enter", "Toggle fold"),
     ("z", "Toggle all folds"),
-    ("/", "Search"),
+    ("Z", "Unfold only branches with matches"),
+    ("/", "Search (text, $JSONPath, :predicate)"),
     ("n/N", "Next/prev match"),
+    ("*", "Match selected key/value"),
+    ("a/A", "Match/copy path in all elements"),
+    ("M", "List search matches"),
     ("Esc", "Clear search"),
+    ("p", "Find path"),
+    (":", "Go to path"),
+    ("|", "Query (jq)"),
+    ("Tab", "Toggle query result"),
+    ("+/-", "Zoom into selection/back out"),
+    ("i", "Infer JSON Schema of selection"),
+    ("T/A-T", "Output/copy type definitions"),
+    ("V", "Validate against a schema file"),
+    ("e/A-e", "Next/previous schema error"),
+    ("D", "Toggle schema panel"),
+    ("r/R", "Follow $ref/jump back"),
+    ("s/S", "Sort keys, or array by field"),
+    ("A-s/A-S", "Sort keys recursively"),
+    ("t", "Show array as a table"),
     ("", ""),
     ("Output", ""),
     ("o/O", "Output pretty selection/value"),
     ("A-o/A-O", "Output raw selection/value"),
     ("y/Y", "Copy pretty selection/value"),
     ("A-y/A-Y", "Copy raw selection/value"),
+    ("P/A-P", "Output/copy match pointers"),
+    ("E/A-E", "Output/copy match records"),
     ("", ""),
     ("Other", ""),
     ("w", "Toggle line wrap"),
+    ("v", "Switch view (JSON, columns, outline)"),
     ("#", "Toggle line numbers"),
     ("?", "Show this help"),
     ("q/C-c", "Quit"),
+    ("Q", "Quit and print query"),
 ];
 
 pub fn render_help<W: Write>(out: &mut W, screen_size: (usize, usize)) -> anyhow::Result<()> {
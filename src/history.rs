@@ -0,0 +1,134 @@
+use std::fs;
+use std::path::PathBuf;
+
+const MAX_ENTRIES: usize = 500;
+
+/// Previously confirmed prompt inputs, oldest first, optionally persisted to a file.
+pub struct History {
+    entries: Vec<String>,
+    /// Index of the entry being browsed and the input it replaced
+    browsing: Option<(usize, String)>,
+    path: Option<PathBuf>,
+}
+
+impl History {
+    /// Loads the history stored under `name` in the user's data directory.
+    pub fn load(name: &str) -> Self {
+        let path = dirs::data_dir().map(|dir| dir.join("jx").join(name));
+        Self::from_path(path)
+    }
+
+    fn from_path(path: Option<PathBuf>) -> Self {
+        let entries = path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|s| s.lines().map(str::to_owned).collect())
+            .unwrap_or_default();
+
+        Self {
+            entries,
+            browsing: None,
+            path,
+        }
+    }
+
+    /// Records an entry, moving it to the end if already present, and saves the history.
+    pub fn push(&mut self, entry: &str) {
+        self.browsing = None;
+        if entry.is_empty() || entry.contains('\n') {
+            return;
+        }
+
+        self.entries.retain(|e| e != entry);
+        self.entries.push(entry.to_owned());
+        if self.entries.len() > MAX_ENTRIES {
+            self.entries.drain(..self.entries.len() - MAX_ENTRIES);
+        }
+
+        // Failing to persist the history shouldn't get in the way of searching
+        let _ = self.save();
+    }
+
+    fn save(&self) -> std::io::Result<()> {
+        let Some(ref path) = self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        fs::write(path, self.entries.join("\n") + "\n")
+    }
+
+    /// Stops browsing, e.g. when a new prompt is opened.
+    pub fn reset(&mut self) {
+        self.browsing = None;
+    }
+
+    /// Returns the entry before the one being browsed, remembering `input` to come back to.
+    pub fn prev(&mut self, input: &str) -> Option<String> {
+        let index = match self.browsing {
+            None if !self.entries.is_empty() => {
+                self.browsing = Some((self.entries.len() - 1, input.to_owned()));
+                self.entries.len() - 1
+            }
+            Some((ref mut index, _)) if *index > 0 => {
+                *index -= 1;
+                *index
+            }
+            _ => return None,
+        };
+
+        Some(self.entries[index].clone())
+    }
+
+    /// Returns the entry after the one being browsed, or the original input past the end.
+    pub fn next(&mut self) -> Option<String> {
+        match self.browsing.take() {
+            Some((index, input)) if index + 1 >= self.entries.len() => Some(input),
+            Some((index, input)) => {
+                self.browsing = Some((index + 1, input));
+                Some(self.entries[index + 1].clone())
+            }
+            None => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn browse() {
+        let mut history = History::from_path(None);
+        history.push("a");
+        history.push("b");
+        history.push("a");
+
+        assert_eq!(history.next(), None);
+        assert_eq!(history.prev("draft").as_deref(), Some("a"));
+        assert_eq!(history.prev("ignored").as_deref(), Some("b"));
+        assert_eq!(history.prev("ignored"), None);
+        assert_eq!(history.next().as_deref(), Some("a"));
+        assert_eq!(history.next().as_deref(), Some("draft"));
+        assert_eq!(history.next(), None);
+    }
+
+    #[test]
+    fn persist() {
+        let path = std::env::temp_dir()
+            .join(format!("jx-history-{}", std::process::id()))
+            .join("search_history");
+
+        let mut history = History::from_path(Some(path.clone()));
+        history.push("$..price");
+        history.push(":type null");
+
+        let mut history = History::from_path(Some(path.clone()));
+        assert_eq!(history.prev("").as_deref(), Some(":type null"));
+        assert_eq!(history.prev("").as_deref(), Some("$..price"));
+
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+}
@@ -3,23 +3,26 @@ use std::{borrow::Borrow, rc::Rc};
 use serde_json::{Map, Value};
 
 use crate::{
-    json::{Pointer, PointerData, PointerMap, PointerValue, Token},
+    json::{ArrayOrders, Pointer, PointerData, PointerMap, PointerValue, Token},
     style::{StyleClass, StyledLine, StyledString, INDENT},
 };
 
-pub struct Formatter<'lines, 'pm> {
+pub struct Formatter<'lines, 'pm, 'o> {
     depth: usize,
     value: Rc<Value>,
     tokens: Vec<Token>,
     lines: &'lines mut Vec<StyledLine>,
     pointer_map: &'pm mut PointerMap,
+    /// Arrays whose elements are displayed in another order
+    orders: &'o ArrayOrders,
 }
 
-impl<'lines, 'pm> Formatter<'lines, 'pm> {
+impl<'lines, 'pm, 'o> Formatter<'lines, 'pm, 'o> {
     pub fn format(
         value: Rc<Value>,
         lines: &'lines mut Vec<StyledLine>,
         pointer_map: &'pm mut PointerMap,
+        orders: &'o ArrayOrders,
     ) {
         let value_clone = Rc::clone(&value);
         let mut formatter = Self {
@@ -28,6 +31,7 @@ impl<'lines, 'pm> Formatter<'lines, 'pm> {
             tokens: vec![],
             lines,
             pointer_map,
+            orders,
         };
 
         formatter.format_value(value_clone.borrow());
@@ -166,9 +170,14 @@ impl<'lines, 'pm> Formatter<'lines, 'pm> {
     fn format_array(&mut self, array: &[Value]) {
         self.open_bracket("[");
 
-        for (idx, value) in array.iter().enumerate() {
+        let orders = self.orders;
+        let order = orders.get(&self.tokens).map(|o| o.indices.as_slice());
+
+        for position in 0..array.len() {
+            // Elements keep their original index when displayed in another order
+            let idx = order.map_or(position, |indices| indices[position]);
             let token = Token::Index(idx);
-            if token == 0 {
+            if position == 0 {
                 self.push_token(&token);
             } else {
                 self.set_token(&token);
@@ -176,9 +185,9 @@ impl<'lines, 'pm> Formatter<'lines, 'pm> {
 
             self.new_line();
 
-            self.format_value(value);
+            self.format_value(&array[idx]);
 
-            if idx < array.len() - 1 {
+            if position < array.len() - 1 {
                 self.append_line(format_punct(","));
             }
         }
@@ -247,19 +256,63 @@ fn format_punct(punct: &str) -> StyledString {
     StyledString(punct.into(), StyleClass::Punct)
 }
 
-fn format_fold_count(n: usize) -> StyledString {
-    StyledString(format!(" ({}) ", n), StyleClass::FoldCount)
+/// A one-line summary of a value: primitives as displayed but without quotes, and containers
+/// collapsed to their size.
+pub fn summarize(value: &Value) -> StyledString {
+    match value {
+        Value::String(s) => format_string(s),
+        Value::Number(n) => format_number(&n.to_string()),
+        Value::Bool(b) => format_bool(&b.to_string()),
+        Value::Null => format_null(),
+        Value::Array(a) => StyledString(format!("[{}]", a.len()), StyleClass::FoldCount),
+        Value::Object(o) => StyledString(format!("{{{}}}", o.len()), StyleClass::FoldCount),
+    }
+}
+
+/// How a member or element is named outside of formatted JSON: its key, escaped but without
+/// quotes, or its index in brackets.
+pub fn token_label(token: &Token) -> String {
+    match token {
+        Token::Key(key) => format_string(key).0,
+        Token::Index(index) => format!("[{}]", index),
+    }
+}
+
+/// Formats the summary of a folded container: its number of children and, during a
+/// search, how many matches it holds.
+fn format_fold_summary(n: usize, matches: usize) -> Vec<StyledString> {
+    let count = StyledString(format!(" ({}) ", n), StyleClass::FoldCount);
+    match matches {
+        0 => vec![count],
+        1 => vec![
+            count,
+            StyledString("1 match ".to_string(), StyleClass::FoldMatchCount),
+        ],
+        _ => vec![
+            count,
+            StyledString(format!("{} matches ", matches), StyleClass::FoldMatchCount),
+        ],
+    }
 }
 
-pub fn curly_fold(key: Option<&str>, n: usize) -> Vec<StyledString> {
-    let fold = format_fold_count(n);
+pub fn curly_fold(key: Option<&str>, n: usize, matches: usize) -> Vec<StyledString> {
+    let fold = [
+        vec![format_punct("{")],
+        format_fold_summary(n, matches),
+        vec![format_punct("}")],
+    ]
+    .concat();
     match key {
-        Some(key) => [format_key(key), surround_punct(fold, "{", "}")].concat(),
-        None => surround_punct(fold, "{", "}"),
+        Some(key) => [format_key(key), fold].concat(),
+        None => fold,
     }
 }
 
-pub fn bracket_fold(n: usize) -> Vec<StyledString> {
-    let fold = format_fold_count(n);
-    surround_punct(fold, "[", "]")
+pub fn bracket_fold(n: usize, matches: usize) -> Vec<StyledString> {
+    [
+        vec![format_punct("[")],
+        format_fold_summary(n, matches),
+        vec![format_punct("]")],
+    ]
+    .concat()
 }
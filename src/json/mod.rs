@@ -1,19 +1,28 @@
 use std::{
     collections::{HashMap, HashSet},
     rc::Rc,
+    sync::Arc,
 };
 
 use serde_json::Value;
 
 pub use formatter::*;
+pub use path::{completions, jq_path, parse_path, resolve, PathPattern};
 pub use pointer::Pointer;
+pub use refs::{broken_refs, ref_target, REF_KEY};
+pub use sort::{ArrayOrder, SortOrder};
 pub use token::Token;
+pub use walk::{children, select_paths, walk};
 
 use crate::style::StyledLine;
 
 mod formatter;
+mod path;
 mod pointer;
+mod refs;
+mod sort;
 mod token;
+mod walk;
 
 #[derive(Default, Clone, Copy)]
 pub enum PointerValue {
@@ -42,6 +51,9 @@ pub struct PointerData {
 /// A map from tokens to first and last lines matching the formatted value.
 pub type PointerMap = HashMap<Vec<Token>, PointerData>;
 
+/// Display orders of sorted arrays, by their path.
+pub type ArrayOrders = HashMap<Vec<Token>, ArrayOrder>;
+
 /// Returns the total width of the formatted JSON
 fn measure_width(formatted: &[StyledLine]) -> usize {
     formatted.iter().fold(0, |w, s| {
@@ -57,9 +69,14 @@ pub struct Json {
     pub value: Rc<Value>,
     pub folds: HashSet<Vec<Token>>,
     all_folded: bool,
-    pub formatted: Vec<StyledLine>,
+    pub formatted: Arc<Vec<StyledLine>>,
     pub pointer_map: PointerMap,
     pub width: usize,
+    /// The value as parsed, kept while keys are shown sorted
+    original: Option<Rc<Value>>,
+    /// Whether output and copies use the sorted key order
+    pub sorted_output: bool,
+    pub array_orders: ArrayOrders,
 }
 
 impl From<Rc<Value>> for Json {
@@ -67,7 +84,12 @@ impl From<Rc<Value>> for Json {
         let mut formatted = vec![];
         let mut pointer_map = PointerMap::new();
 
-        Formatter::format(Rc::clone(&value), &mut formatted, &mut pointer_map);
+        Formatter::format(
+            Rc::clone(&value),
+            &mut formatted,
+            &mut pointer_map,
+            &ArrayOrders::new(),
+        );
 
         let width = measure_width(&formatted);
 
@@ -76,9 +98,12 @@ impl From<Rc<Value>> for Json {
             folds: HashSet::new(),
             all_folded: false,
             pointer: Pointer::new(),
-            formatted,
+            formatted: Arc::new(formatted),
             width,
             pointer_map,
+            original: None,
+            sorted_output: false,
+            array_orders: ArrayOrders::new(),
         }
     }
 }
@@ -104,11 +129,259 @@ impl Json {
         self.pointer.set_path(tokens);
     }
 
+    /// Unfolds every ancestor of the given path, then selects it.
+    pub fn reveal(&mut self, tokens: Vec<Token>) {
+        for i in 0..tokens.len() {
+            self.unfold(&tokens[..i]);
+        }
+
+        self.set_selection(tokens);
+    }
+
     /// Gets the JSON value at the current pointer location.
     pub fn value(&self) -> Option<&Value> {
         self.value.pointer(&self.pointer.to_json_pointer())
     }
 
+    /// The selected value as it should be output, see [`Json::output_value_at`].
+    pub fn output_value(&self) -> Option<&Value> {
+        self.output_value_at(&self.pointer.to_json_pointer())
+    }
+
+    /// The value at a JSON pointer in its original key order, unless output should follow
+    /// the sorted order.
+    pub fn output_value_at(&self, json_pointer: &str) -> Option<&Value> {
+        match self.original {
+            Some(ref original) if !self.sorted_output => original.pointer(json_pointer),
+            _ => self.value.pointer(json_pointer),
+        }
+    }
+
+    /// Sorts the keys of the selected object (or the one containing a selected primitive), or
+    /// of every object within it if `recursive`. Sorting again in the same order restores the
+    /// original order.
+    ///
+    /// Returns whether keys were sorted (or restored), or `None` if there's nothing to sort.
+    pub fn sort_keys(&mut self, order: SortOrder, recursive: bool) -> Option<bool> {
+        let mut json_pointer = self.pointer.to_json_pointer();
+        if self
+            .value()
+            .is_some_and(|v| !v.is_object() && !v.is_array())
+        {
+            json_pointer = Pointer::json_pointer(&self.pointer.parent_tokens());
+        }
+        let current = self.value.pointer(&json_pointer)?;
+        if !(current.is_object() || recursive && current.is_array()) {
+            return None;
+        }
+
+        let sorted = sort::sort_keys(current, order, recursive);
+        let (replacement, sorting) = if sort::same_order(&sorted, current) {
+            // Already in order, and never sorted: there's no original order to go back to
+            let Some(original) = self.original.as_ref() else {
+                return Some(true);
+            };
+            let original = original.pointer(&json_pointer)?;
+            if sort::same_order(original, current) {
+                return Some(true);
+            }
+            (original.clone(), false)
+        } else {
+            (sorted, true)
+        };
+
+        self.original.get_or_insert_with(|| Rc::clone(&self.value));
+        *Rc::make_mut(&mut self.value).pointer_mut(&json_pointer)? = replacement;
+        if self
+            .original
+            .as_ref()
+            .is_some_and(|original| sort::same_order(original, &self.value))
+        {
+            self.original = None;
+        }
+        self.reformat();
+
+        Some(sorting)
+    }
+
+    /// Displays the elements of the selected array sorted by `field`, a path within each
+    /// element (`.` for the element itself), or in their original order if `field` is empty.
+    ///
+    /// Returns whether the array is now sorted.
+    pub fn sort_array(&mut self, field: &str, order: SortOrder) -> Result<bool, String> {
+        let tokens = self.tokens();
+        let Some(Value::Array(array)) = self.value() else {
+            return Err("Select an array to sort".to_string());
+        };
+
+        let sorted = if field.trim().is_empty() {
+            self.array_orders.remove(&tokens);
+            false
+        } else {
+            let path = parse_path(field)?;
+            let array_order = ArrayOrder::new(array, field.trim(), &path, order);
+            self.array_orders.insert(tokens, array_order);
+            true
+        };
+        self.reformat();
+
+        Ok(sorted)
+    }
+
+    /// The container at `path` as a document of its own, keeping its folds, key and element
+    /// orders, and the selection if it's within. Paths in it are relative to `path`.
+    pub fn zoom(&self, path: &[Token]) -> Option<Json> {
+        let json_pointer = Pointer::json_pointer(path);
+        let value = self.value.pointer(&json_pointer)?;
+        if !(value.is_object() || value.is_array()) {
+            return None;
+        }
+
+        let mut zoomed = Json::from(Rc::new(value.clone()));
+        zoomed.original = self
+            .original
+            .as_ref()
+            .and_then(|original| original.pointer(&json_pointer))
+            .filter(|original| !sort::same_order(original, value))
+            .map(|original| Rc::new(original.clone()));
+        zoomed.sorted_output = self.sorted_output;
+        zoomed.folds = self
+            .folds
+            .iter()
+            .filter_map(|tokens| tokens.strip_prefix(path))
+            .map(<[Token]>::to_vec)
+            .collect();
+        zoomed.array_orders = self
+            .array_orders
+            .iter()
+            .filter_map(|(tokens, order)| {
+                Some((tokens.strip_prefix(path)?.to_vec(), order.clone()))
+            })
+            .collect();
+        if let Some(selection) = self.tokens().strip_prefix(path) {
+            zoomed.set_selection(selection.to_vec());
+        }
+        if !zoomed.array_orders.is_empty() {
+            zoomed.reformat();
+        }
+
+        Some(zoomed)
+    }
+
+    /// Takes back a document made by [`Json::zoom`] at `path`, with the changes made to its
+    /// key order, folds and element orders, and its selection.
+    pub fn unzoom(&mut self, path: &[Token], zoomed: Json) {
+        let json_pointer = Pointer::json_pointer(path);
+        if self
+            .value
+            .pointer(&json_pointer)
+            .is_some_and(|current| !sort::same_order(current, &zoomed.value))
+        {
+            self.original.get_or_insert_with(|| Rc::clone(&self.value));
+            if let Some(current) = Rc::make_mut(&mut self.value).pointer_mut(&json_pointer) {
+                *current = (*zoomed.value).clone();
+            }
+            if self
+                .original
+                .as_ref()
+                .is_some_and(|original| sort::same_order(original, &self.value))
+            {
+                self.original = None;
+            }
+        }
+
+        let prefixed = |tokens: &Vec<Token>| [path, tokens].concat();
+        self.folds.retain(|tokens| !tokens.starts_with(path));
+        self.folds.extend(zoomed.folds.iter().map(prefixed));
+        self.array_orders
+            .retain(|tokens, _| !tokens.starts_with(path));
+        self.array_orders.extend(
+            zoomed
+                .array_orders
+                .iter()
+                .map(|(tokens, order)| (prefixed(tokens), order.clone())),
+        );
+        self.set_selection(prefixed(&zoomed.tokens()));
+        self.reformat();
+    }
+
+    /// How the selected array is sorted, if it is.
+    pub fn array_order(&self) -> Option<&ArrayOrder> {
+        self.array_orders.get(&self.tokens())
+    }
+
+    /// The index of the element starting on `line`, if its array is displayed sorted.
+    pub fn sorted_index(&self, line: usize) -> Option<usize> {
+        let tokens = &self.formatted.get(line)?.pointer;
+        let (Token::Index(index), parent) = tokens.split_last()? else {
+            return None;
+        };
+
+        (self.array_orders.contains_key(parent) && self.pointer_map.get(tokens)?.bounds.0 == line)
+            .then_some(*index)
+    }
+
+    /// The children of the container at `tokens`, in display order.
+    pub fn children(&self, tokens: &[Token]) -> Vec<(Token, &Value)> {
+        let value = self.value.pointer(&Pointer::json_pointer(tokens));
+        match (value, self.array_orders.get(tokens)) {
+            (Some(Value::Array(a)), Some(order)) => order
+                .indices
+                .iter()
+                .map(|&i| (Token::Index(i), &a[i]))
+                .collect(),
+            (Some(value), _) => children(value),
+            (None, _) => vec![],
+        }
+    }
+
+    /// The index of the element displayed `offset` positions away from the one at `index`,
+    /// in the array at `parent`.
+    fn displayed_sibling(
+        &self,
+        parent: &[Token],
+        index: usize,
+        len: usize,
+        offset: isize,
+    ) -> Option<usize> {
+        match self.array_orders.get(parent) {
+            Some(order) => {
+                let position = order.position(index)?.checked_add_signed(offset)?;
+                order.indices.get(position).copied()
+            }
+            None => index.checked_add_signed(offset).filter(|&i| i < len),
+        }
+    }
+
+    /// Formats the value again, keeping folds and the selection.
+    fn reformat(&mut self) {
+        let mut formatted = vec![];
+        let mut pointer_map = PointerMap::new();
+        Formatter::format(
+            Rc::clone(&self.value),
+            &mut formatted,
+            &mut pointer_map,
+            &self.array_orders,
+        );
+
+        self.width = measure_width(&formatted);
+        self.formatted = Arc::new(formatted);
+        self.pointer_map = pointer_map;
+    }
+
+    /// The node a line belongs to, and whether it's the node's closing line.
+    pub fn line_location(&self, line: usize) -> Option<(Vec<Token>, bool)> {
+        let tokens = &self.formatted.get(line)?.pointer;
+        let data = self.pointer_map.get(tokens)?;
+        Some((tokens.clone(), data.bounds.0 != line))
+    }
+
+    /// The opening or closing line of a node, see [`Json::line_location`].
+    pub fn line_at(&self, tokens: &[Token], closing: bool) -> Option<usize> {
+        let (start, end) = self.pointer_map.get(tokens)?.bounds;
+        Some(if closing { end } else { start })
+    }
+
     pub fn bounds(&self) -> (usize, usize) {
         match self.pointer_map.get(&self.tokens()) {
             Some(&PointerData { bounds, .. }) => bounds,
@@ -186,6 +459,26 @@ impl Json {
         true
     }
 
+    /// Folds every container except the ancestors of `paths`, so that only the branches
+    /// leading to them are open.
+    pub fn fold_all_except(&mut self, paths: &[Vec<Token>]) {
+        let open: HashSet<&[Token]> = paths
+            .iter()
+            .flat_map(|path| (0..path.len()).map(move |i| &path[..i]))
+            .collect();
+
+        self.folds = self
+            .pointer_map
+            .iter()
+            .filter(|(tokens, data)| {
+                matches!(data.value, PointerValue::Object | PointerValue::Array)
+                    && !open.contains(tokens.as_slice())
+            })
+            .map(|(tokens, _)| tokens.clone())
+            .collect();
+        self.all_folded = false;
+    }
+
     fn fold(&mut self, tokens: Vec<Token>) -> bool {
         self.folds.insert(tokens)
     }
@@ -258,7 +551,7 @@ impl Json {
 
     /// Get selection (key and value)
     pub fn token_value_pair(&self) -> Option<(Option<String>, &Value)> {
-        let value = self.value()?;
+        let value = self.output_value()?;
 
         Some(if self.value_is_array_element() {
             (None, value)
@@ -278,10 +571,10 @@ impl Json {
                 Value::Object(o) => {
                     return o.keys().next().map(|key| Token::Key(key.to_owned()));
                 }
-                Value::Array(a) => {
-                    if !a.is_empty() {
-                        return Some(Token::Index(0));
-                    }
+                Value::Array(a) if !a.is_empty() => {
+                    let tokens = self.tokens();
+                    let first = self.array_orders.get(&tokens).map_or(0, |o| o.indices[0]);
+                    return Some(Token::Index(first));
                 }
                 _ => {}
             }
@@ -298,10 +591,13 @@ impl Json {
                 Value::Object(o) => {
                     return o.keys().next_back().map(|key| Token::Key(key.to_owned()));
                 }
-                Value::Array(a) => {
-                    if !a.is_empty() {
-                        return Some(Token::Index(a.len() - 1));
-                    }
+                Value::Array(a) if !a.is_empty() => {
+                    let tokens = self.tokens();
+                    let last = self
+                        .array_orders
+                        .get(&tokens)
+                        .map_or(a.len() - 1, |o| o.indices[a.len() - 1]);
+                    return Some(Token::Index(last));
                 }
                 _ => {}
             }
@@ -329,12 +625,13 @@ impl Json {
                         return o.keys().nth(key_idx - 1).map(|k| Token::Key(k.to_string()));
                     }
                 }
-                Value::Array(_) => {
+                Value::Array(a) => {
                     let idx = self.token()?.as_index()?;
+                    let parent = self.pointer.parent_tokens();
 
-                    if idx > 0 {
-                        return Some(Token::Index(idx - 1));
-                    }
+                    return self
+                        .displayed_sibling(&parent, idx, a.len(), -1)
+                        .map(Token::Index);
                 }
                 _ => {}
             }
@@ -363,10 +660,11 @@ impl Json {
                 }
                 Value::Array(a) => {
                     let idx = self.token()?.as_index()?;
+                    let parent = self.pointer.parent_tokens();
 
-                    if idx < a.len() - 1 {
-                        return Some(Token::Index(idx + 1));
-                    }
+                    return self
+                        .displayed_sibling(&parent, idx, a.len(), 1)
+                        .map(Token::Index);
                 }
                 _ => {}
             }
@@ -390,15 +688,23 @@ mod tests {
             let mut formatted = Vec::new();
             let width = measure_width(&formatted);
             let mut pointer_map = HashMap::new();
-            Formatter::format(Rc::clone(&rc_value), &mut formatted, &mut pointer_map);
+            Formatter::format(
+                Rc::clone(&rc_value),
+                &mut formatted,
+                &mut pointer_map,
+                &ArrayOrders::new(),
+            );
             Self {
                 value: rc_value,
                 pointer: Pointer::new(),
                 folds: HashSet::new(),
                 all_folded: false,
-                formatted,
+                formatted: Arc::new(formatted),
                 width,
                 pointer_map,
+                original: None,
+                sorted_output: false,
+                array_orders: ArrayOrders::new(),
             }
         }
     }
@@ -425,6 +731,90 @@ mod tests {
         );
     }
 
+    #[test]
+    fn sort_keys() {
+        let mut json = Json::from(json!({"b": {"y": 1, "x": 2}, "a": 3}));
+        let b = vec![Token::Key("b".to_string())];
+        json.folds.insert(b.clone());
+
+        assert_eq!(json.sort_keys(SortOrder::Ascending, false), Some(true));
+        assert_eq!(json.formatted[1].pointer, vec![Token::Key("a".to_string())]);
+        assert_eq!(json.line_at(&b, false), Some(2));
+        assert!(json.folds.contains(&b));
+        // Output keeps the original order unless asked otherwise
+        assert_eq!(
+            json.output_value().unwrap().to_string(),
+            r#"{"b":{"y":1,"x":2},"a":3}"#
+        );
+        json.sorted_output = true;
+        assert_eq!(
+            json.output_value().unwrap().to_string(),
+            r#"{"a":3,"b":{"y":1,"x":2}}"#
+        );
+
+        json.set_selection(b.clone());
+        assert_eq!(json.sort_keys(SortOrder::Descending, false), Some(true));
+        assert_eq!(json.sort_keys(SortOrder::Ascending, false), Some(true));
+        // Sorting again in the same order restores the original order
+        assert_eq!(json.sort_keys(SortOrder::Ascending, false), Some(false));
+        assert_eq!(json.value().unwrap().to_string(), r#"{"y":1,"x":2}"#);
+
+        // Primitives sort the object they're in
+        json.set_selection(vec![Token::Key("a".to_string())]);
+        assert_eq!(json.sort_keys(SortOrder::Descending, false), Some(true));
+        assert_eq!(json.formatted[1].pointer, b);
+
+        let mut json = Json::from(json!([1]));
+        assert_eq!(json.sort_keys(SortOrder::Ascending, false), None);
+
+        // Objects already in order are sorted too
+        let mut json = Json::from(json!({"a": 1, "b": 2}));
+        assert_eq!(json.sort_keys(SortOrder::Ascending, false), Some(true));
+        assert!(json.original.is_none());
+    }
+
+    #[test]
+    fn sort_array() {
+        let mut json = Json::from(json!({"a": [{"n": 3}, {"n": 1}, {"n": 2}]}));
+        let a = vec![Token::Key("a".to_string())];
+        json.set_selection(a.clone());
+
+        assert_eq!(json.sort_array(".n", SortOrder::Descending), Ok(true));
+        assert!(json.sort_array(".n[", SortOrder::Ascending).is_err());
+        // Elements keep their index, and are navigated in display order
+        let starts: Vec<usize> = (0..json.formatted.len())
+            .filter_map(|line| json.sorted_index(line))
+            .collect();
+        assert_eq!(starts, vec![0, 2, 1]);
+        json.go_in();
+        assert_eq!(json.token(), Some(&Token::Index(0)));
+        json.go_next();
+        assert_eq!(json.token(), Some(&Token::Index(2)));
+        json.go_prev();
+        assert_eq!(json.token(), Some(&Token::Index(0)));
+
+        json.set_selection(a);
+        assert_eq!(json.sort_array("", SortOrder::Descending), Ok(false));
+        assert_eq!(json.sorted_index(2), None);
+    }
+
+    #[test]
+    fn fold_all_except() {
+        let mut json = Json::from(json!({"a": {"b": [0]}, "c": {"d": 1}, "e": []}));
+        json.fold_all_except(&[vec![
+            Token::Key("a".to_string()),
+            Token::Key("b".to_string()),
+        ]]);
+        assert_eq!(
+            json.folds,
+            HashSet::from([
+                vec![Token::Key("a".to_string()), Token::Key("b".to_string())],
+                vec![Token::Key("c".to_string())],
+                vec![Token::Key("e".to_string())],
+            ])
+        );
+    }
+
     #[test]
     fn move_around() {
         let value = json!({ "a": [0, { "/": "foo", "~": [true, null] }] });
@@ -553,4 +943,45 @@ mod tests {
         assert_eq!(json.token(), Some(&Token::Index(0)));
         assert!(!json.go_prev());
     }
+
+    #[test]
+    fn zoom() {
+        let a = Token::Key("a".to_string());
+        let mut json = Json::from(json!({"a": {"y": [3, 1, 2], "x": {"z": 0}}, "b": 1}));
+        json.set_selection(vec![a.clone(), Token::Key("y".to_string())]);
+        json.sort_array(".", SortOrder::Ascending).unwrap();
+
+        let path = vec![a.clone()];
+        let mut zoomed = json.zoom(&path).unwrap();
+        assert_eq!(*zoomed.value, json!({"y": [3, 1, 2], "x": {"z": 0}}));
+        assert_eq!(zoomed.tokens(), vec!["y"]);
+        assert_eq!(zoomed.array_order().unwrap().indices, vec![1, 2, 0]);
+        assert!(json.zoom(&[Token::Key("b".to_string())]).is_none());
+
+        // Changes made while zoomed in are kept
+        zoomed.set_selection(vec![]);
+        zoomed.sort_keys(SortOrder::Ascending, false);
+        zoomed.set_selection(vec![Token::Key("x".to_string())]);
+        zoomed.toggle_fold();
+        json.unzoom(&path, zoomed);
+        assert_eq!(json.tokens(), vec!["a", "x"]);
+        assert!(json.folds.contains(&json.tokens()));
+        assert_eq!(
+            json.value["a"].as_object().unwrap().keys().next().unwrap(),
+            "x"
+        );
+        assert_eq!(
+            json.output_value_at("/a")
+                .unwrap()
+                .as_object()
+                .unwrap()
+                .keys()
+                .next()
+                .unwrap(),
+            "y"
+        );
+        assert!(json
+            .array_orders
+            .contains_key(&vec![a, Token::Key("y".to_string())]));
+    }
 }
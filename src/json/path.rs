@@ -0,0 +1,377 @@
+use serde_json::Value;
+
+use super::{Pointer, Token};
+
+/// Parses a path written as a JSON Pointer (`/a/0`), a jq path (`.a[0]`) or a
+/// JSONPath (`$.a[0]`) into tokens.
+///
+/// Segments that could be either a key or an index (`/0`, `.0`) are returned as keys,
+/// see [`resolve`].
+pub fn parse_path(input: &str) -> Result<Vec<Token>, String> {
+    let input = input.trim();
+
+    if let Some(pointer) = input.strip_prefix('/') {
+        return Ok(pointer
+            .split('/')
+            .map(|s| Token::Key(s.replace("~1", "/").replace("~0", "~")))
+            .collect());
+    }
+
+    parse_dotted(input.strip_prefix('$').unwrap_or(input))
+}
+
+/// Parses `.a.b[0]["c d"]` style paths. A leading bare key (`a.b`) is accepted too.
+fn parse_dotted(input: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = vec![];
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '.' => {
+                i += 1;
+                match chars.get(i) {
+                    Some('.') => return Err("Recursive descent (..) is not a path".to_string()),
+                    Some('"') => {
+                        let (key, end) = parse_quoted(&chars, i)?;
+                        tokens.push(Token::Key(key));
+                        i = end;
+                    }
+                    Some('[') | None => {}
+                    Some(_) => {
+                        let (key, end) = parse_bare(&chars, i);
+                        tokens.push(Token::Key(key));
+                        i = end;
+                    }
+                }
+            }
+            '[' => {
+                i += 1;
+                match chars.get(i) {
+                    Some('"' | '\'') => {
+                        let (key, end) = parse_quoted(&chars, i)?;
+                        tokens.push(Token::Key(key));
+                        i = end;
+                    }
+                    _ => {
+                        let start = i;
+                        while i < chars.len() && chars[i] != ']' {
+                            i += 1;
+                        }
+                        let index: String = chars[start..i].iter().collect();
+                        let index = index
+                            .trim()
+                            .parse()
+                            .map_err(|_| format!("Invalid array index '{}'", index))?;
+                        tokens.push(Token::Index(index));
+                    }
+                }
+                if chars.get(i) != Some(&']') {
+                    return Err("Missing closing ']'".to_string());
+                }
+                i += 1;
+            }
+            _ if i == 0 => {
+                let (key, end) = parse_bare(&chars, i);
+                tokens.push(Token::Key(key));
+                i = end;
+            }
+            c => return Err(format!("Unexpected '{}' at position {}", c, i)),
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Reads a key up to the next `.` or `[`.
+fn parse_bare(chars: &[char], start: usize) -> (String, usize) {
+    let mut end = start;
+    while end < chars.len() && !matches!(chars[end], '.' | '[') {
+        end += 1;
+    }
+
+    (chars[start..end].iter().collect(), end)
+}
+
+/// Reads a quoted key starting at the opening quote, returning it unescaped along with
+/// the position just past the closing quote.
+fn parse_quoted(chars: &[char], start: usize) -> Result<(String, usize), String> {
+    let quote = chars[start];
+    let mut key = String::new();
+    let mut i = start + 1;
+
+    while i < chars.len() {
+        match chars[i] {
+            '\\' if i + 1 < chars.len() => {
+                key.push(chars[i + 1]);
+                i += 2;
+            }
+            c if c == quote => return Ok((key, i + 1)),
+            c => {
+                key.push(c);
+                i += 1;
+            }
+        }
+    }
+
+    Err(format!("Missing closing {}", quote))
+}
+
+/// Walks `tokens` down `value`, coercing each token to fit its container (`"0"` indexes
+/// an array, `0` keys an object).
+/// Returns the longest prefix that exists, and whether it covers the whole path.
+pub fn resolve(value: &Value, tokens: &[Token]) -> (Vec<Token>, bool) {
+    let mut current = value;
+    let mut resolved = vec![];
+
+    for token in tokens {
+        let next = match current {
+            Value::Object(o) => o
+                .get_key_value(&token.to_string())
+                .map(|(k, v)| (Token::Key(k.clone()), v)),
+            Value::Array(a) => match token {
+                Token::Index(i) => Some(*i),
+                Token::Key(k) => k.parse().ok(),
+            }
+            .and_then(|i| a.get(i).map(|v| (Token::Index(i), v))),
+            _ => None,
+        };
+
+        match next {
+            Some((token, value)) => {
+                resolved.push(token);
+                current = value;
+            }
+            None => return (resolved, false),
+        }
+    }
+
+    (resolved, true)
+}
+
+/// Lists the ways the last segment of `input` can be completed with the children of
+/// the container it belongs to. Each candidate is the full, completed input.
+pub fn completions(value: &Value, input: &str) -> Vec<String> {
+    let (parent, partial, pointer_syntax) = match input.rfind('/') {
+        Some(pos) if input.starts_with('/') => (&input[..pos], &input[pos + 1..], true),
+        _ => {
+            let pos = last_separator(input);
+            let parent = pos.map_or("", |p| &input[..p]);
+            let partial = pos.map_or(input, |p| &input[p + 1..]);
+            (parent, partial.trim_start_matches(['"', '\'']), false)
+        }
+    };
+
+    let Ok(tokens) = parse_path(parent) else {
+        return vec![];
+    };
+    let (tokens, true) = resolve(value, &tokens) else {
+        return vec![];
+    };
+    let Some(container) = value.pointer(&Pointer::json_pointer(&tokens)) else {
+        return vec![];
+    };
+
+    let children: Vec<Token> = match container {
+        Value::Object(o) => o.keys().map(|k| Token::Key(k.clone())).collect(),
+        Value::Array(a) => (0..a.len()).map(Token::Index).collect(),
+        _ => vec![],
+    };
+
+    children
+        .into_iter()
+        .filter(|t| t.to_string().starts_with(partial))
+        .map(|t| match t {
+            Token::Key(k) if pointer_syntax => {
+                format!("{}/{}", parent, k.replace('~', "~0").replace('/', "~1"))
+            }
+            Token::Index(i) if pointer_syntax => format!("{}/{}", parent, i),
+            Token::Key(k) if is_identifier(&k) => format!("{}.{}", parent, k),
+            Token::Key(k) => format!("{}[{}]", parent, Value::String(k)),
+            Token::Index(i) => format!("{}[{}]", parent, i),
+        })
+        .collect()
+}
+
+/// Byte offset of the last `.` or `[` that isn't inside a quoted key.
+fn last_separator(input: &str) -> Option<usize> {
+    let mut quote = None;
+    let mut last = None;
+
+    for (i, c) in input.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '.' | '[') => last = Some(i),
+            _ => {}
+        }
+    }
+
+    last
+}
+
+/// A path with its array indices replaced by wildcards, matching the same node in every
+/// element, e.g. `.items[].metadata.name`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PathPattern(Vec<Option<Token>>);
+
+impl PathPattern {
+    /// Generalizes a path, or returns `None` if it doesn't go through any array.
+    pub fn generalize(tokens: &[Token]) -> Option<Self> {
+        if !tokens.iter().any(|t| matches!(t, Token::Index(_))) {
+            return None;
+        }
+
+        Some(Self(
+            tokens
+                .iter()
+                .map(|t| match t {
+                    Token::Key(_) => Some(t.clone()),
+                    Token::Index(_) => None,
+                })
+                .collect(),
+        ))
+    }
+
+    pub fn matches(&self, tokens: &[Token]) -> bool {
+        self.0.len() == tokens.len()
+            && self.0.iter().zip(tokens).all(|(p, t)| match (p, t) {
+                (None, Token::Index(_)) => true,
+                (Some(p), t) => p == t,
+                _ => false,
+            })
+    }
+
+    /// The pattern as a jq expression, e.g. `.items[].metadata.name`.
+    pub fn to_jq(&self) -> String {
+        self.0.iter().fold(String::new(), |mut acc, token| {
+            match token {
+                Some(token) => push_jq_segment(&mut acc, token),
+                None => acc += "[]",
+            }
+            acc
+        })
+    }
+}
+
+/// A path as a jq expression, e.g. `.items[3].name`. The root is `.`.
+pub fn jq_path(tokens: &[Token]) -> String {
+    if tokens.is_empty() {
+        return ".".to_string();
+    }
+
+    tokens.iter().fold(String::new(), |mut acc, token| {
+        push_jq_segment(&mut acc, token);
+        acc
+    })
+}
+
+fn push_jq_segment(acc: &mut String, token: &Token) {
+    match token {
+        Token::Key(k) if is_identifier(k) => *acc += &format!(".{}", k),
+        Token::Key(k) => *acc += &format!(".{}", Value::String(k.clone())),
+        Token::Index(i) => *acc += &format!("[{}]", i),
+    }
+}
+
+fn is_identifier(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn parse_all_syntaxes() {
+        let expected = vec![
+            Token::Key("spec".to_string()),
+            Token::Key("containers".to_string()),
+            Token::Index(0),
+            Token::Key("image".to_string()),
+        ];
+        assert_eq!(
+            parse_path(".spec.containers[0].image"),
+            Ok(expected.clone())
+        );
+        assert_eq!(
+            parse_path("$.spec.containers[0].image"),
+            Ok(expected.clone())
+        );
+        assert_eq!(parse_path("$['spec'].containers[0]['image']"), Ok(expected));
+        assert_eq!(
+            parse_path("/spec/containers/0/image").unwrap(),
+            vec!["spec", "containers", "0", "image"]
+        );
+        assert_eq!(parse_path("/a~1b/c~0d").unwrap(), vec!["a/b", "c~d"]);
+        assert_eq!(parse_path(".[\"a.b\"]").unwrap(), vec!["a.b"]);
+        assert_eq!(parse_path("").unwrap(), Vec::<Token>::new());
+        assert_eq!(parse_path(".").unwrap(), Vec::<Token>::new());
+        assert!(parse_path(".a[0").is_err());
+        assert!(parse_path("..a").is_err());
+    }
+
+    #[test]
+    fn resolve_coerces_tokens() {
+        let value = json!({"a": [{"0": true}]});
+        let tokens = parse_path("/a/0/0").unwrap();
+        assert_eq!(
+            resolve(&value, &tokens),
+            (
+                vec![
+                    Token::Key("a".into()),
+                    Token::Index(0),
+                    Token::Key("0".into())
+                ],
+                true
+            )
+        );
+    }
+
+    #[test]
+    fn resolve_longest_prefix() {
+        let value = json!({"a": {"b": 1}});
+        let tokens = parse_path(".a.c.d").unwrap();
+        assert_eq!(
+            resolve(&value, &tokens),
+            (vec![Token::Key("a".into())], false)
+        );
+    }
+
+    #[test]
+    fn path_patterns() {
+        let tokens = parse_path(".items[3].metadata[\"the name\"]").unwrap();
+        let pattern = PathPattern::generalize(&tokens).unwrap();
+        assert_eq!(pattern.to_jq(), ".items[].metadata.\"the name\"");
+
+        assert!(pattern.matches(&tokens));
+        assert!(pattern.matches(&parse_path(".items[0].metadata[\"the name\"]").unwrap()));
+        assert!(!pattern.matches(&parse_path(".items[0].metadata").unwrap()));
+        assert!(!pattern.matches(&parse_path(".items.x.metadata[\"the name\"]").unwrap()));
+
+        assert_eq!(PathPattern::generalize(&parse_path(".a.b").unwrap()), None);
+        assert_eq!(jq_path(&tokens), ".items[3].metadata.\"the name\"");
+        assert_eq!(jq_path(&[]), ".");
+    }
+
+    #[test]
+    fn complete_children() {
+        let value = json!({"spec": {"containers": [1], "cpu": 1, "a b": 2}});
+        assert_eq!(
+            completions(&value, ".spec.c"),
+            vec![".spec.containers", ".spec.cpu"]
+        );
+        assert_eq!(completions(&value, ".spec.a"), vec![".spec[\"a b\"]"]);
+        assert_eq!(completions(&value, "/spec/co"), vec!["/spec/containers"]);
+        assert_eq!(
+            completions(&value, "$.spec.containers["),
+            vec!["$.spec.containers[0]"]
+        );
+        assert!(completions(&value, ".nope.x").is_empty());
+    }
+}
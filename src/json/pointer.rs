@@ -131,6 +131,19 @@ impl Pointer {
         })
     }
 
+    /// Period-separated representation of a path, e.g. `.foo.0.bar`. The root is `.`.
+    pub fn period_path(tokens: &[Token]) -> String {
+        if tokens.is_empty() {
+            return ".".to_string();
+        }
+
+        tokens.iter().fold(String::new(), |mut acc, token| {
+            acc += format!(".{}", token).as_str();
+
+            acc
+        })
+    }
+
     fn escape_token(s: &Token) -> String {
         match s {
             Token::Key(k) => k.replace("~", "~0").replace("/", "~1"),
@@ -141,19 +154,6 @@ impl Pointer {
 
 impl Display for Pointer {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self.cursor {
-            None => {
-                write!(f, ".")?;
-                return Ok(());
-            }
-
-            Some(c) => self
-                .tokens
-                .iter()
-                .take(c + 1)
-                .for_each(|p| write!(f, ".{}", p).unwrap()),
-        }
-
-        Ok(())
+        write!(f, "{}", Self::period_path(&self.tokens()))
     }
 }
@@ -0,0 +1,149 @@
+use serde_json::Value;
+
+use super::{parse_path, resolve, select_paths, Token};
+
+/// Key of members that refer to another part of the document, as in JSON Schema and OpenAPI.
+pub const REF_KEY: &str = "$ref";
+
+/// Finds the node a local `$ref` points to in `value`: `#` for the root, a JSON Pointer like
+/// `#/components/schemas/Pet`, or an anchor like `#pet` declared with `$anchor`.
+pub fn ref_target(value: &Value, reference: &str) -> Result<Vec<Token>, String> {
+    let Some(fragment) = reference.strip_prefix('#') else {
+        return Err(format!("{} isn't in this document", reference));
+    };
+    let fragment = percent_decode(fragment);
+    if fragment.is_empty() {
+        return Ok(vec![]);
+    }
+    if !fragment.starts_with('/') {
+        let anchor = Some(fragment.as_str());
+        return select_paths(value, |_, v| {
+            v.get("$anchor").and_then(Value::as_str) == anchor
+        })
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("{} not found", reference));
+    }
+
+    match resolve(value, &parse_path(&fragment)?) {
+        (tokens, true) => Ok(tokens),
+        _ => Err(format!("{} not found", reference)),
+    }
+}
+
+/// Lists the paths of the local `$ref`s of `value` that don't point to any node.
+pub fn broken_refs(value: &Value) -> Vec<Vec<Token>> {
+    let mut broken = vec![];
+    find_broken_refs(value, value, &mut vec![], &mut broken);
+    broken
+}
+
+fn find_broken_refs(
+    root: &Value,
+    value: &Value,
+    path: &mut Vec<Token>,
+    broken: &mut Vec<Vec<Token>>,
+) {
+    match value {
+        Value::Object(o) => {
+            for (key, child) in o {
+                path.push(Token::Key(key.clone()));
+                match child {
+                    Value::String(r) if key == REF_KEY && r.starts_with('#') => {
+                        if ref_target(root, r).is_err() {
+                            broken.push(path.clone());
+                        }
+                    }
+                    _ => find_broken_refs(root, child, path, broken),
+                }
+                path.pop();
+            }
+        }
+        Value::Array(a) => {
+            for (i, child) in a.iter().enumerate() {
+                path.push(Token::Index(i));
+                find_broken_refs(root, child, path, broken);
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Decodes `%XX` escapes, which URI fragments use for characters like spaces.
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = s
+            .get(i + 1..i + 3)
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn refs() {
+        let value = json!({
+            "paths": {"/pets": {"$ref": "#/components/schemas/Pet List"}},
+            "components": {"schemas": {
+                "Pet List": {"items": {"$ref": "#/components/schemas/a~1b/1"}},
+                "a/b": [{}, {"$ref": "#/components/schemas/Missing"}]
+            }},
+            "external": {"$ref": "other.json#/Pet"},
+            "root": {"$ref": "#"},
+            "$defs": {"tag": {"$anchor": "tag"}},
+            "anchored": {"$ref": "#tag"}
+        });
+        let key = |k: &str| Token::Key(k.to_string());
+
+        assert_eq!(
+            ref_target(&value, "#/components/schemas/Pet%20List"),
+            Ok(vec![key("components"), key("schemas"), key("Pet List")])
+        );
+        assert_eq!(
+            ref_target(&value, "#/components/schemas/a~1b/1"),
+            Ok(vec![
+                key("components"),
+                key("schemas"),
+                key("a/b"),
+                Token::Index(1)
+            ])
+        );
+        assert_eq!(ref_target(&value, "#"), Ok(vec![]));
+        assert!(ref_target(&value, "other.json#/Pet").is_err());
+        assert_eq!(
+            ref_target(&value, "#tag"),
+            Ok(vec![key("$defs"), key("tag")])
+        );
+        assert!(ref_target(&value, "#missing").is_err());
+
+        assert_eq!(
+            broken_refs(&value),
+            vec![vec![
+                key("components"),
+                key("schemas"),
+                key("a/b"),
+                Token::Index(1),
+                key("$ref")
+            ]]
+        );
+    }
+}
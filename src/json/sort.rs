@@ -0,0 +1,273 @@
+use std::cmp::Ordering;
+
+use serde_json::{Map, Value};
+
+use super::Token;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+impl SortOrder {
+    fn apply(self, ordering: Ordering) -> Ordering {
+        match self {
+            SortOrder::Ascending => ordering,
+            SortOrder::Descending => ordering.reverse(),
+        }
+    }
+}
+
+/// Returns `value` with the keys of its objects sorted, only at the top level or
+/// throughout if `recursive`.
+pub fn sort_keys(value: &Value, order: SortOrder, recursive: bool) -> Value {
+    match value {
+        Value::Object(o) => {
+            let mut entries: Vec<(&String, &Value)> = o.iter().collect();
+            entries.sort_by(|(a, _), (b, _)| order.apply(natural_cmp(a, b)));
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| {
+                        let v = if recursive {
+                            sort_keys(v, order, true)
+                        } else {
+                            v.clone()
+                        };
+                        (k.clone(), v)
+                    })
+                    .collect::<Map<_, _>>(),
+            )
+        }
+        Value::Array(a) if recursive => {
+            Value::Array(a.iter().map(|v| sort_keys(v, order, true)).collect())
+        }
+        _ => value.clone(),
+    }
+}
+
+/// The display order of an array's elements, sorted by a field of each element.
+#[derive(Clone)]
+pub struct ArrayOrder {
+    /// The field as entered, e.g. `.size`
+    pub field: String,
+    pub order: SortOrder,
+    /// Original indices of the elements, in display order
+    pub indices: Vec<usize>,
+}
+
+impl ArrayOrder {
+    /// Sorts the elements of `array` by the value at `path` within each. Elements missing
+    /// the field come last in either order, and ties keep their original order.
+    pub fn new(array: &[Value], field: &str, path: &[Token], order: SortOrder) -> Self {
+        let mut indices: Vec<usize> = (0..array.len()).collect();
+        indices.sort_by(
+            |&a, &b| match (lookup(&array[a], path), lookup(&array[b], path)) {
+                (Some(a), Some(b)) => order.apply(compare_values(a, b)),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            },
+        );
+
+        Self {
+            field: field.to_string(),
+            order,
+            indices,
+        }
+    }
+
+    /// Display position of the element at `index`.
+    pub fn position(&self, index: usize) -> Option<usize> {
+        self.indices.iter().position(|&i| i == index)
+    }
+}
+
+/// Gets the value at a path of keys, which can be array indices too.
+fn lookup<'a>(value: &'a Value, path: &[Token]) -> Option<&'a Value> {
+    path.iter()
+        .try_fold(value, |value, token| match (value, token) {
+            (Value::Object(o), Token::Key(k)) => o.get(k),
+            (Value::Array(a), Token::Key(k)) => a.get(k.parse::<usize>().ok()?),
+            (Value::Array(a), Token::Index(i)) => a.get(*i),
+            _ => None,
+        })
+}
+
+/// Orders values by type (numbers, strings, booleans, arrays, objects, then null), then
+/// numbers by value, strings naturally, and containers by size.
+fn compare_values(a: &Value, b: &Value) -> Ordering {
+    fn rank(value: &Value) -> u8 {
+        match value {
+            Value::Number(_) => 0,
+            Value::String(_) => 1,
+            Value::Bool(_) => 2,
+            Value::Array(_) => 3,
+            Value::Object(_) => 4,
+            Value::Null => 5,
+        }
+    }
+
+    match (a, b) {
+        // Integers exactly, as large ones lose precision as floats
+        (Value::Number(a), Value::Number(b)) => match (a.as_i64(), b.as_i64()) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            _ => match (a.as_u64(), b.as_u64()) {
+                (Some(a), Some(b)) => a.cmp(&b),
+                _ => {
+                    let (a, b) = (a.as_f64().unwrap_or(0.0), b.as_f64().unwrap_or(0.0));
+                    a.total_cmp(&b)
+                }
+            },
+        },
+        (Value::String(a), Value::String(b)) => natural_cmp(a, b),
+        (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+        (Value::Array(a), Value::Array(b)) => a.len().cmp(&b.len()),
+        (Value::Object(a), Value::Object(b)) => a.len().cmp(&b.len()),
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+/// Compares values including the order of object keys, which `==` ignores.
+pub fn same_order(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Object(a), Value::Object(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .zip(b)
+                    .all(|((ka, va), (kb, vb))| ka == kb && same_order(va, vb))
+        }
+        (Value::Array(a), Value::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| same_order(a, b))
+        }
+        _ => a == b,
+    }
+}
+
+/// Natural ordering: runs of digits compare by their numeric value, so `item2` comes
+/// before `item10`. Other text compares case-insensitively first.
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a_rest, mut b_rest) = (a, b);
+
+    loop {
+        let (a_chunk, a_next) = next_chunk(a_rest);
+        let (b_chunk, b_next) = next_chunk(b_rest);
+
+        let ordering = match (a_chunk, b_chunk) {
+            (None, None) => break,
+            (None, Some(_)) => Ordering::Less,
+            (Some(_), None) => Ordering::Greater,
+            (Some(a), Some(b)) => match (is_digits(a), is_digits(b)) {
+                (true, true) => {
+                    let (a, b) = (a.trim_start_matches('0'), b.trim_start_matches('0'));
+                    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+                }
+                _ => a.to_lowercase().cmp(&b.to_lowercase()),
+            },
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+
+        (a_rest, b_rest) = (a_next, b_next);
+    }
+
+    // Tell apart keys that only differ by case or leading zeros
+    a.cmp(b)
+}
+
+/// Splits off the leading run of digits or non-digits.
+fn next_chunk(s: &str) -> (Option<&str>, &str) {
+    let Some(first) = s.chars().next() else {
+        return (None, s);
+    };
+
+    let digits = first.is_ascii_digit();
+    let end = s
+        .find(|c: char| c.is_ascii_digit() != digits)
+        .unwrap_or(s.len());
+    (Some(&s[..end]), &s[end..])
+}
+
+fn is_digits(s: &str) -> bool {
+    s.starts_with(|c: char| c.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn natural_order() {
+        let mut keys = vec!["item10", "Item2", "item1", "b", "a10b", "a9c", "007", "8"];
+        keys.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(
+            keys,
+            vec!["007", "8", "a9c", "a10b", "b", "item1", "Item2", "item10"]
+        );
+    }
+
+    #[test]
+    fn array_order() {
+        let array = json!([
+            {"size": 10, "name": "b"},
+            {"name": "a"},
+            {"size": 2, "name": "c"},
+            {"size": "big"},
+            {"size": 2.5}
+        ]);
+        let array = array.as_array().unwrap();
+        let path = vec![Token::Key("size".to_string())];
+
+        let ascending = ArrayOrder::new(array, ".size", &path, SortOrder::Ascending);
+        assert_eq!(ascending.indices, vec![2, 4, 0, 3, 1]);
+        let descending = ArrayOrder::new(array, ".size", &path, SortOrder::Descending);
+        assert_eq!(descending.indices, vec![3, 0, 4, 2, 1]);
+        assert_eq!(descending.position(0), Some(1));
+
+        let by_name = ArrayOrder::new(
+            array,
+            "name",
+            &[Token::Key("name".to_string())],
+            SortOrder::Ascending,
+        );
+        assert_eq!(by_name.indices, vec![1, 0, 2, 3, 4]);
+
+        // Large integers that are equal as floats keep their order
+        let ids = json!([
+            9007199254740993u64,
+            9007199254740992u64,
+            -1,
+            18446744073709551615u64
+        ]);
+        let ids = ids.as_array().unwrap();
+        let by_id = ArrayOrder::new(ids, ".", &[], SortOrder::Ascending);
+        assert_eq!(by_id.indices, vec![2, 1, 0, 3]);
+    }
+
+    #[test]
+    fn sorting() {
+        let value = json!({"b": {"y": 1, "x": 2}, "a": [{"d": 1, "c": 2}]});
+
+        let sorted = sort_keys(&value, SortOrder::Ascending, false);
+        assert!(same_order(
+            &sorted,
+            &json!({"a": [{"d": 1, "c": 2}], "b": {"y": 1, "x": 2}})
+        ));
+        assert!(!same_order(&sorted, &value));
+
+        let sorted = sort_keys(&value, SortOrder::Descending, true);
+        assert!(same_order(
+            &sorted,
+            &json!({"b": {"y": 1, "x": 2}, "a": [{"d": 1, "c": 2}]})
+        ));
+        let sorted = sort_keys(&value, SortOrder::Ascending, true);
+        assert!(same_order(
+            &sorted,
+            &json!({"a": [{"c": 2, "d": 1}], "b": {"x": 2, "y": 1}})
+        ));
+    }
+}
@@ -0,0 +1,87 @@
+use serde_json::Value;
+
+use super::Token;
+
+/// The members of an object or the elements of an array, with the tokens they're reached by.
+pub fn children(value: &Value) -> Vec<(Token, &Value)> {
+    match value {
+        Value::Object(o) => o.iter().map(|(k, v)| (Token::Key(k.clone()), v)).collect(),
+        Value::Array(a) => a
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (Token::Index(i), v))
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Visits `value` and all of its descendants in document order, with their paths from it.
+pub fn walk<'a>(value: &'a Value, visit: &mut dyn FnMut(&[Token], &'a Value)) {
+    fn walk_from<'a>(
+        tokens: &mut Vec<Token>,
+        value: &'a Value,
+        visit: &mut dyn FnMut(&[Token], &'a Value),
+    ) {
+        visit(tokens, value);
+        for (token, child) in children(value) {
+            tokens.push(token);
+            walk_from(tokens, child, visit);
+            tokens.pop();
+        }
+    }
+
+    walk_from(&mut vec![], value, visit);
+}
+
+/// Returns the paths of the nodes of `value` passing `test`, given the token they're reached
+/// by and their value, in document order.
+pub fn select_paths(
+    value: &Value,
+    test: impl Fn(Option<&Token>, &Value) -> bool,
+) -> Vec<Vec<Token>> {
+    let mut paths = vec![];
+    walk(value, &mut |tokens, v| {
+        if test(tokens.last(), v) {
+            paths.push(tokens.to_vec());
+        }
+    });
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn walking() {
+        let value = json!({"a": [1, {"b": 2}], "c": 3});
+        let mut visited = vec![];
+        walk(&value, &mut |tokens, v| {
+            visited.push((tokens.len(), v.clone()))
+        });
+        assert_eq!(
+            visited,
+            vec![
+                (0, value.clone()),
+                (1, json!([1, {"b": 2}])),
+                (2, json!(1)),
+                (2, json!({"b": 2})),
+                (3, json!(2)),
+                (1, json!(3)),
+            ]
+        );
+
+        let numbers = select_paths(&value, |_, v| v.is_number());
+        assert_eq!(numbers.len(), 3);
+        assert_eq!(
+            numbers[1],
+            vec![
+                Token::Key("a".into()),
+                Token::Index(1),
+                Token::Key("b".into())
+            ]
+        );
+    }
+}
@@ -0,0 +1,634 @@
+use std::cmp::Ordering;
+
+use serde_json::Value;
+
+use crate::json::{children, walk, Token};
+
+enum Selector {
+    Name(String),
+    Wildcard,
+    Index(i64),
+    Slice(Option<i64>, Option<i64>, Option<i64>),
+    Filter(Filter),
+}
+
+struct Segment {
+    descendant: bool,
+    selectors: Vec<Selector>,
+}
+
+enum Filter {
+    Or(Box<Filter>, Box<Filter>),
+    And(Box<Filter>, Box<Filter>),
+    Not(Box<Filter>),
+    Exists(Operand),
+    Compare(CompareOp, Operand, Operand),
+}
+
+#[derive(Clone, Copy)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+enum Operand {
+    Literal(Value),
+    /// A query relative to the current node (`@`) or to the root (`$`)
+    Query(bool, Vec<Segment>),
+    Length(Box<Operand>),
+}
+
+/// Evaluates a JSONPath expression (RFC 9535) and returns the paths of the matching nodes.
+///
+/// Supports names, wildcards, indices, slices, unions, descendants (`..`) and filters
+/// with comparisons, `&&`, `||`, `!`, existence tests and `length()`.
+pub fn select(path: &str, root: &Value) -> Result<Vec<Vec<Token>>, String> {
+    let mut parser = Parser {
+        chars: path.trim().chars().collect(),
+        pos: 0,
+    };
+
+    if !parser.eat('$') {
+        return Err("JSONPath must start with '$'".to_string());
+    }
+    let segments = parser.segments()?;
+    if parser.pos < parser.chars.len() {
+        return Err(format!("Unexpected '{}'", parser.chars[parser.pos]));
+    }
+
+    Ok(apply(&segments, root, vec![(vec![], root)])
+        .into_iter()
+        .map(|(tokens, _)| tokens)
+        .collect())
+}
+
+type Node<'a> = (Vec<Token>, &'a Value);
+
+fn apply<'a>(segments: &[Segment], root: &'a Value, mut nodes: Vec<Node<'a>>) -> Vec<Node<'a>> {
+    for segment in segments {
+        let mut next = vec![];
+        for node in nodes {
+            if segment.descendant {
+                let mut descendants = vec![];
+                collect_descendants(node, &mut descendants);
+                for d in descendants {
+                    select_children(&segment.selectors, root, d, &mut next);
+                }
+            } else {
+                select_children(&segment.selectors, root, node, &mut next);
+            }
+        }
+        nodes = next;
+    }
+
+    nodes
+}
+
+/// Collects a node and all of its descendants, in document order.
+fn collect_descendants<'a>((tokens, value): Node<'a>, out: &mut Vec<Node<'a>>) {
+    walk(value, &mut |path, v| {
+        out.push(([tokens.as_slice(), path].concat(), v))
+    });
+}
+
+/// The children of a node, with their paths.
+fn child_nodes<'a>(tokens: &[Token], value: &'a Value) -> Vec<Node<'a>> {
+    children(value)
+        .into_iter()
+        .map(|(t, v)| ([tokens, &[t]].concat(), v))
+        .collect()
+}
+
+fn select_children<'a>(
+    selectors: &[Selector],
+    root: &'a Value,
+    (tokens, value): Node<'a>,
+    out: &mut Vec<Node<'a>>,
+) {
+    let push = |out: &mut Vec<Node<'a>>, t: Token, v: &'a Value| {
+        let mut tokens = tokens.clone();
+        tokens.push(t);
+        out.push((tokens, v));
+    };
+
+    for selector in selectors {
+        match (selector, value) {
+            (Selector::Name(name), Value::Object(o)) => {
+                if let Some((k, v)) = o.get_key_value(name) {
+                    push(out, Token::Key(k.clone()), v);
+                }
+            }
+            (Selector::Wildcard, _) => out.extend(child_nodes(&tokens, value)),
+            (Selector::Index(i), Value::Array(a)) => {
+                let i = if *i < 0 { a.len() as i64 + i } else { *i };
+                if let Some(v) = usize::try_from(i).ok().and_then(|i| a.get(i)) {
+                    push(out, Token::Index(i as usize), v);
+                }
+            }
+            (Selector::Slice(start, end, step), Value::Array(a)) => {
+                for i in slice_indices(a.len() as i64, *start, *end, step.unwrap_or(1)) {
+                    push(out, Token::Index(i), &a[i]);
+                }
+            }
+            (Selector::Filter(filter), Value::Object(_) | Value::Array(_)) => {
+                for (child_tokens, child) in child_nodes(&tokens, value) {
+                    if test(filter, root, child) {
+                        out.push((child_tokens, child));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn slice_indices(len: i64, start: Option<i64>, end: Option<i64>, step: i64) -> Vec<usize> {
+    let normalize = |i: i64| if i < 0 { len + i } else { i };
+    let mut indices = vec![];
+
+    if step > 0 {
+        let start = normalize(start.unwrap_or(0)).clamp(0, len);
+        let end = normalize(end.unwrap_or(len)).clamp(0, len);
+        let mut i = start;
+        while i < end {
+            indices.push(i as usize);
+            match i.checked_add(step) {
+                Some(next) => i = next,
+                None => break,
+            }
+        }
+    } else if step < 0 {
+        let start = normalize(start.unwrap_or(len - 1)).clamp(-1, len - 1);
+        let end = normalize(end.unwrap_or(-len - 1)).clamp(-1, len - 1);
+        let mut i = start;
+        while i > end {
+            indices.push(i as usize);
+            match i.checked_add(step) {
+                Some(next) => i = next,
+                None => break,
+            }
+        }
+    }
+
+    indices
+}
+
+fn test(filter: &Filter, root: &Value, current: &Value) -> bool {
+    match filter {
+        Filter::Or(a, b) => test(a, root, current) || test(b, root, current),
+        Filter::And(a, b) => test(a, root, current) && test(b, root, current),
+        Filter::Not(f) => !test(f, root, current),
+        Filter::Exists(operand) => match operand {
+            Operand::Query(..) => !query_nodes(operand, root, current).is_empty(),
+            _ => operand_value(operand, root, current).is_some_and(|v| v != Value::Bool(false)),
+        },
+        Filter::Compare(op, a, b) => {
+            let a = operand_value(a, root, current);
+            let b = operand_value(b, root, current);
+            compare(*op, a.as_ref(), b.as_ref())
+        }
+    }
+}
+
+fn query_nodes<'a>(operand: &Operand, root: &'a Value, current: &'a Value) -> Vec<Node<'a>> {
+    match operand {
+        Operand::Query(relative, segments) => {
+            let start = if *relative { current } else { root };
+            apply(segments, root, vec![(vec![], start)])
+        }
+        _ => vec![],
+    }
+}
+
+/// Resolves an operand to a single value; `None` stands for "Nothing".
+fn operand_value(operand: &Operand, root: &Value, current: &Value) -> Option<Value> {
+    match operand {
+        Operand::Literal(v) => Some(v.clone()),
+        Operand::Query(..) => {
+            let nodes = query_nodes(operand, root, current);
+            match nodes.as_slice() {
+                [(_, v)] => Some((*v).clone()),
+                _ => None,
+            }
+        }
+        Operand::Length(inner) => match operand_value(inner, root, current)? {
+            Value::String(s) => Some(Value::from(s.chars().count())),
+            Value::Array(a) => Some(Value::from(a.len())),
+            Value::Object(o) => Some(Value::from(o.len())),
+            _ => None,
+        },
+    }
+}
+
+fn compare(op: CompareOp, a: Option<&Value>, b: Option<&Value>) -> bool {
+    let equal = match (a, b) {
+        (None, None) => true,
+        (Some(Value::Number(x)), Some(Value::Number(y))) => x.as_f64() == y.as_f64(),
+        (Some(x), Some(y)) => x == y,
+        _ => false,
+    };
+
+    let ordering = match (a, b) {
+        (Some(Value::Number(x)), Some(Value::Number(y))) => x
+            .as_f64()
+            .unwrap_or(0.0)
+            .partial_cmp(&y.as_f64().unwrap_or(0.0)),
+        (Some(Value::String(x)), Some(Value::String(y))) => Some(x.cmp(y)),
+        _ => None,
+    };
+
+    match op {
+        CompareOp::Eq => equal,
+        CompareOp::Ne => !equal,
+        CompareOp::Lt => ordering == Some(Ordering::Less),
+        CompareOp::Gt => ordering == Some(Ordering::Greater),
+        CompareOp::Le => equal || ordering == Some(Ordering::Less),
+        CompareOp::Ge => equal || ordering == Some(Ordering::Greater),
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_str(&mut self, s: &str) -> bool {
+        self.skip_whitespace();
+        if s.chars()
+            .enumerate()
+            .all(|(i, c)| self.chars.get(self.pos + i) == Some(&c))
+        {
+            self.pos += s.chars().count();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        self.skip_whitespace();
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(match self.peek() {
+                Some(found) => format!("Expected '{}' but found '{}'", c, found),
+                None => format!("Expected '{}'", c),
+            })
+        }
+    }
+
+    /// Parses segments until something that can't start one.
+    fn segments(&mut self) -> Result<Vec<Segment>, String> {
+        let mut segments = vec![];
+
+        loop {
+            let descendant = self.chars.get(self.pos..self.pos + 2) == Some(&['.', '.']);
+            if descendant {
+                self.pos += 2;
+                if self.peek() == Some('[') {
+                    self.pos += 1;
+                    segments.push(Segment {
+                        descendant,
+                        selectors: self.bracket()?,
+                    });
+                } else {
+                    segments.push(Segment {
+                        descendant,
+                        selectors: vec![self.dot_selector()?],
+                    });
+                }
+            } else if self.eat('.') {
+                segments.push(Segment {
+                    descendant,
+                    selectors: vec![self.dot_selector()?],
+                });
+            } else if self.eat('[') {
+                segments.push(Segment {
+                    descendant,
+                    selectors: self.bracket()?,
+                });
+            } else {
+                return Ok(segments);
+            }
+        }
+    }
+
+    fn dot_selector(&mut self) -> Result<Selector, String> {
+        if self.eat('*') {
+            return Ok(Selector::Wildcard);
+        }
+
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '-' || !c.is_ascii())
+        {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return Err("Expected a name after '.'".to_string());
+        }
+
+        Ok(Selector::Name(self.chars[start..self.pos].iter().collect()))
+    }
+
+    /// Parses the comma-separated selectors of a `[...]` segment, after the `[`.
+    fn bracket(&mut self) -> Result<Vec<Selector>, String> {
+        let mut selectors = vec![];
+
+        loop {
+            self.skip_whitespace();
+            selectors.push(match self.peek() {
+                Some('*') => {
+                    self.pos += 1;
+                    Selector::Wildcard
+                }
+                Some('\'' | '"') => Selector::Name(self.string()?),
+                Some('?') => {
+                    self.pos += 1;
+                    Selector::Filter(self.filter()?)
+                }
+                _ => self.index_or_slice()?,
+            });
+
+            self.skip_whitespace();
+            if self.eat(']') {
+                return Ok(selectors);
+            }
+            self.expect(',')?;
+        }
+    }
+
+    fn index_or_slice(&mut self) -> Result<Selector, String> {
+        let start = self.integer()?;
+        self.skip_whitespace();
+        if !self.eat(':') {
+            return start
+                .map(Selector::Index)
+                .ok_or_else(|| "Expected an index".to_string());
+        }
+
+        let end = self.integer()?;
+        self.skip_whitespace();
+        let step = if self.eat(':') { self.integer()? } else { None };
+
+        Ok(Selector::Slice(start, end, step))
+    }
+
+    fn integer(&mut self) -> Result<Option<i64>, String> {
+        self.skip_whitespace();
+        let start = self.pos;
+        self.eat('-');
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return Ok(None);
+        }
+
+        let text: String = self.chars[start..self.pos].iter().collect();
+        text.parse()
+            .map(Some)
+            .map_err(|_| format!("Invalid integer '{}'", text))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let quote = self.peek().ok_or("Expected a string")?;
+        self.pos += 1;
+        let mut s = String::new();
+
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                '\\' => {
+                    let escaped = self.peek().ok_or("Unterminated string")?;
+                    self.pos += 1;
+                    s.push(match escaped {
+                        'n' => '\n',
+                        't' => '\t',
+                        'r' => '\r',
+                        c => c,
+                    });
+                }
+                c if c == quote => return Ok(s),
+                c => s.push(c),
+            }
+        }
+
+        Err("Unterminated string".to_string())
+    }
+
+    fn filter(&mut self) -> Result<Filter, String> {
+        let mut lhs = self.and()?;
+        while self.eat_str("||") {
+            lhs = Filter::Or(Box::new(lhs), Box::new(self.and()?));
+        }
+        Ok(lhs)
+    }
+
+    fn and(&mut self) -> Result<Filter, String> {
+        let mut lhs = self.basic()?;
+        while self.eat_str("&&") {
+            lhs = Filter::And(Box::new(lhs), Box::new(self.basic()?));
+        }
+        Ok(lhs)
+    }
+
+    fn basic(&mut self) -> Result<Filter, String> {
+        self.skip_whitespace();
+
+        if self.peek() == Some('!') && self.chars.get(self.pos + 1) != Some(&'=') {
+            self.pos += 1;
+            return Ok(Filter::Not(Box::new(self.basic()?)));
+        }
+
+        if self.eat('(') {
+            let inner = self.filter()?;
+            self.expect(')')?;
+            return Ok(inner);
+        }
+
+        let lhs = self.operand()?;
+        let op = [
+            ("==", CompareOp::Eq),
+            ("!=", CompareOp::Ne),
+            ("<=", CompareOp::Le),
+            (">=", CompareOp::Ge),
+            ("<", CompareOp::Lt),
+            (">", CompareOp::Gt),
+        ]
+        .into_iter()
+        .find(|(s, _)| self.eat_str(s));
+
+        match op {
+            Some((_, op)) => Ok(Filter::Compare(op, lhs, self.operand()?)),
+            None => Ok(Filter::Exists(lhs)),
+        }
+    }
+
+    fn operand(&mut self) -> Result<Operand, String> {
+        self.skip_whitespace();
+
+        match self.peek() {
+            Some('@') => {
+                self.pos += 1;
+                Ok(Operand::Query(true, self.segments()?))
+            }
+            Some('$') => {
+                self.pos += 1;
+                Ok(Operand::Query(false, self.segments()?))
+            }
+            Some('\'' | '"') => Ok(Operand::Literal(Value::String(self.string()?))),
+            Some(c) if c == '-' || c.is_ascii_digit() => {
+                let start = self.pos;
+                self.pos += 1;
+                while self
+                    .peek()
+                    .is_some_and(|c| c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E' | '+' | '-'))
+                {
+                    self.pos += 1;
+                }
+                let text: String = self.chars[start..self.pos].iter().collect();
+                serde_json::from_str(&text)
+                    .map(Operand::Literal)
+                    .map_err(|_| format!("Invalid number '{}'", text))
+            }
+            _ if self.eat_str("true") => Ok(Operand::Literal(Value::Bool(true))),
+            _ if self.eat_str("false") => Ok(Operand::Literal(Value::Bool(false))),
+            _ if self.eat_str("null") => Ok(Operand::Literal(Value::Null)),
+            _ if self.eat_str("length(") => {
+                let inner = self.operand()?;
+                self.expect(')')?;
+                Ok(Operand::Length(Box::new(inner)))
+            }
+            Some(c) => Err(format!("Unexpected '{}' in filter", c)),
+            None => Err("Unterminated filter".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::json::Pointer;
+
+    fn pointers(path: &str, value: &Value) -> Vec<String> {
+        select(path, value)
+            .unwrap()
+            .iter()
+            .map(|t| Pointer::json_pointer(t))
+            .collect()
+    }
+
+    fn store() -> Value {
+        json!({"store": {
+            "book": [
+                {"title": "A", "price": 8.95, "isbn": "1"},
+                {"title": "B", "price": 12.99},
+                {"title": "C", "price": 8.99, "isbn": "2"}
+            ],
+            "bicycle": {"color": "red", "price": 19.95}
+        }})
+    }
+
+    #[test]
+    fn names_and_indices() {
+        let value = store();
+        assert_eq!(
+            pointers("$.store.bicycle.color", &value),
+            vec!["/store/bicycle/color"]
+        );
+        assert_eq!(
+            pointers("$['store']['book'][-1]", &value),
+            vec!["/store/book/2"]
+        );
+        assert_eq!(
+            pointers("$.store.book[0,2].title", &value),
+            vec!["/store/book/0/title", "/store/book/2/title"]
+        );
+        assert_eq!(
+            pointers("$.store.book[:2]", &value),
+            vec!["/store/book/0", "/store/book/1"]
+        );
+        assert_eq!(pointers("$", &value), vec![""]);
+    }
+
+    #[test]
+    fn slices() {
+        let value = json!([0, 1, 2, 3]);
+        assert_eq!(pointers("$[::2]", &value), vec!["/0", "/2"]);
+        assert_eq!(pointers("$[::-1]", &value), vec!["/3", "/2", "/1", "/0"]);
+        // Huge steps stop after the first index instead of overflowing
+        assert_eq!(pointers("$[1::9223372036854775807]", &value), vec!["/1"]);
+        assert_eq!(pointers("$[2::-9223372036854775808]", &value), vec!["/2"]);
+    }
+
+    #[test]
+    fn descendants_and_wildcards() {
+        let value = store();
+        assert_eq!(
+            pointers("$..price", &value),
+            vec![
+                "/store/book/0/price",
+                "/store/book/1/price",
+                "/store/book/2/price",
+                "/store/bicycle/price"
+            ]
+        );
+        assert_eq!(pointers("$.store.*", &value).len(), 2);
+    }
+
+    #[test]
+    fn filters() {
+        let value = store();
+        assert_eq!(
+            pointers("$..book[?(@.price < 10)]", &value),
+            vec!["/store/book/0", "/store/book/2"]
+        );
+        assert_eq!(
+            pointers("$..book[?@.isbn && @.price > 8.96]", &value),
+            vec!["/store/book/2"]
+        );
+        assert_eq!(
+            pointers("$..book[?(!@.isbn)].title", &value),
+            vec!["/store/book/1/title"]
+        );
+        assert_eq!(
+            pointers("$..book[?@.title == 'B' || length(@.title) > 5]", &value),
+            vec!["/store/book/1"]
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(select("store", &json!({})).is_err());
+        assert!(select("$.a[", &json!({})).is_err());
+        assert!(select("$ref", &json!({})).is_err());
+    }
+}
@@ -11,14 +11,15 @@ use clap::Parser;
 use crossterm::{
     cursor,
     event::{
-        DisableMouseCapture, EnableMouseCapture, KeyboardEnhancementFlags,
-        PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+        DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+        KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
     },
     execute, queue,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 
 use crate::json::Json;
+use crate::schema::discover_schema;
 use crate::style::set_no_color;
 
 pub enum InputSource {
@@ -27,13 +28,26 @@ pub enum InputSource {
     Clipboard,
 }
 
+mod columns;
+mod docs;
 mod events;
+mod finder;
+mod goto;
 mod help;
+mod history;
 mod json;
+mod jsonpath;
+mod outline;
+mod predicate;
+mod prompt;
+mod query;
+mod results;
 mod run;
+mod schema;
 mod screen;
 mod search;
 mod style;
+mod table;
 mod ui;
 
 #[derive(Parser, Debug)]
@@ -47,6 +61,19 @@ pub struct Args {
 
     #[arg(long, help = "Disable color")]
     no_color: bool,
+
+    #[arg(
+        long,
+        help = "Output and copy values with keys in their sorted order (s/S)"
+    )]
+    sorted_output: bool,
+
+    #[arg(
+        long,
+        value_parser = validate_path,
+        help = "Validate against a JSON Schema, by default the file $schema refers to"
+    )]
+    schema: Option<PathBuf>,
 }
 
 fn main() -> anyhow::Result<()> {
@@ -59,9 +86,16 @@ fn main() -> anyhow::Result<()> {
     setup_panic_hook();
 
     let result = (|| -> anyhow::Result<Option<String>> {
-        let (json, source) = parse_input(&args)?;
+        let (mut json, source) = parse_input(&args)?;
+        json.sorted_output = args.sorted_output;
+
+        let schema = match (&args.schema, &source) {
+            (Some(path), _) => Some(path.clone()),
+            (None, InputSource::File(path)) => discover_schema(&json.value, path),
+            (None, _) => None,
+        };
 
-        run::event_loop(&source, json, args.no_numbers)
+        run::event_loop(&source, json, args.no_numbers, schema)
     })()
     .transpose();
 
@@ -134,7 +168,12 @@ fn prepare_terminal() -> anyhow::Result<()> {
 
     execute!(stdout, cursor::Hide)?;
 
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(
+        stdout,
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableBracketedPaste
+    )?;
 
     if supports_keyboard_enhancement() {
         queue!(
@@ -161,6 +200,7 @@ fn restore_terminal() -> io::Result<()> {
         stdout,
         cursor::Show,
         DisableMouseCapture,
+        DisableBracketedPaste,
         LeaveAlternateScreen
     )?;
 
@@ -0,0 +1,227 @@
+use std::collections::HashSet;
+use std::io::Write;
+use std::sync::Arc;
+
+use crossterm::{cursor, queue, style::PrintStyledContent, terminal};
+use unicode_width::UnicodeWidthStr;
+
+use crate::json::{summarize, token_label, Json, Token};
+use crate::style::{
+    styled, take_width, StyleClass, StyledLine, StyledString, STYLE_COLUMN_BORDER,
+    STYLE_SELECTION_BAR,
+};
+
+/// A node of the outline, on a line of its own.
+struct Node {
+    tokens: Vec<Token>,
+    /// Connectors drawn before the node
+    prefix: String,
+    label: String,
+    summary: StyledString,
+}
+
+/// The outline of a document, kept between renders and only listed again when its folds or
+/// contents change.
+#[derive(Default)]
+pub struct Outline {
+    nodes: Vec<Node>,
+    /// The formatted document and folds the nodes were listed from
+    formatted: Arc<Vec<StyledLine>>,
+    folds: HashSet<Vec<Token>>,
+    /// The selection when last rendered, to scroll it into view only when it moves
+    selection: Option<Vec<Token>>,
+    /// First node shown
+    top: usize,
+}
+
+impl Outline {
+    fn update(&mut self, json: &Json) {
+        if Arc::ptr_eq(&self.formatted, &json.formatted) && self.folds == json.folds {
+            return;
+        }
+        self.nodes = outline(json);
+        self.formatted = Arc::clone(&json.formatted);
+        self.folds = json.folds.clone();
+        self.selection = None;
+    }
+
+    /// Scrolls by `dy` nodes in a body `height` lines high, returning whether it moved.
+    pub fn scroll_by(&mut self, dy: isize, height: usize) -> bool {
+        let old = self.top;
+        let max = self.nodes.len().saturating_sub(height);
+        self.top = self.top.saturating_add_signed(dy).min(max);
+        self.top != old
+    }
+}
+
+/// Lists the nodes that aren't inside a fold, in display order.
+fn outline(json: &Json) -> Vec<Node> {
+    let mut nodes = vec![Node {
+        tokens: vec![],
+        prefix: String::new(),
+        label: ".".to_string(),
+        summary: summarize(&json.value),
+    }];
+    push_children(json, &mut nodes, vec![], "");
+    nodes
+}
+
+fn push_children(json: &Json, nodes: &mut Vec<Node>, tokens: Vec<Token>, indent: &str) {
+    if json.folds.contains(&tokens) {
+        return;
+    }
+
+    let children = json.children(&tokens);
+    let last = children.len().saturating_sub(1);
+    for (i, (token, value)) in children.into_iter().enumerate() {
+        let label = token_label(&token);
+        let mut child = tokens.clone();
+        child.push(token);
+        let (connector, continuation) = if i == last {
+            ("└── ", "    ")
+        } else {
+            ("├── ", "│   ")
+        };
+        nodes.push(Node {
+            tokens: child.clone(),
+            prefix: format!("{}{}", indent, connector),
+            label,
+            summary: summarize(value),
+        });
+        push_children(json, nodes, child, &format!("{}{}", indent, continuation));
+    }
+}
+
+/// Renders the document as an outline with `tree`-like connectors, scrolling to keep the
+/// selection in view when it moves.
+pub fn render_outline<W: Write>(
+    out: &mut W,
+    outline: &mut Outline,
+    json: &Json,
+    offset: (usize, usize),
+    size: (usize, usize),
+) -> anyhow::Result<()> {
+    let (width, height) = size;
+    outline.update(json);
+    let selection = json.tokens();
+
+    // Like in the JSON view, the selection spans the lines of its descendants
+    let nodes = &outline.nodes;
+    let start = nodes
+        .iter()
+        .position(|n| n.tokens == selection)
+        .unwrap_or(0);
+    let end = start
+        + nodes[start + 1..]
+            .iter()
+            .take_while(|n| n.tokens.starts_with(&selection))
+            .count();
+    let mut top = outline.top;
+    if outline.selection.as_ref() != Some(&selection) {
+        if start < top {
+            top = start;
+        } else if start >= top + height {
+            top = (start + 1).saturating_sub(height);
+        }
+    }
+    top = top.min(nodes.len().saturating_sub(height));
+
+    for y in 0..height {
+        queue!(
+            out,
+            cursor::MoveTo(offset.0 as u16, (offset.1 + y) as u16),
+            terminal::Clear(terminal::ClearType::CurrentLine)
+        )?;
+        let Some(node) = nodes.get(top + y) else {
+            continue;
+        };
+
+        let bar = if (start..=end).contains(&(top + y)) {
+            "┃"
+        } else {
+            " "
+        };
+        queue!(out, PrintStyledContent(styled(STYLE_SELECTION_BAR, bar)))?;
+
+        let parts = [
+            (node.prefix.as_str(), None),
+            (node.label.as_str(), Some(StyleClass::Key)),
+            (" ", None),
+            (node.summary.0.as_str(), Some(node.summary.1)),
+        ];
+
+        let mut col = 1;
+        for (text, class) in parts {
+            let text = take_width(text, width.saturating_sub(col));
+            col += text.width();
+            match class {
+                Some(class) => queue!(out, PrintStyledContent(class.apply(text)))?,
+                None => queue!(out, PrintStyledContent(styled(STYLE_COLUMN_BORDER, text)))?,
+            }
+        }
+    }
+
+    outline.top = top;
+    outline.selection = Some(selection);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn outline_nodes() {
+        let mut json = Json::from(json!({"a": {"b": [1, 2]}, "c": null}));
+        let lines = |json: &Json| -> Vec<String> {
+            outline(json)
+                .iter()
+                .map(|n| format!("{}{} {}", n.prefix, n.label, n.summary.0))
+                .collect()
+        };
+
+        assert_eq!(
+            lines(&json),
+            vec![
+                ". {2}",
+                "├── a {1}",
+                "│   └── b [2]",
+                "│       ├── [0] 1",
+                "│       └── [1] 2",
+                "└── c null",
+            ]
+        );
+
+        // Folds are shared with the JSON view
+        json.set_selection(vec![Token::Key("a".into())]);
+        json.toggle_fold();
+        assert_eq!(lines(&json), vec![". {2}", "├── a {1}", "└── c null"]);
+    }
+
+    #[test]
+    fn outline_cache() {
+        let mut json = Json::from(json!({"a": [1, 2, 3], "b": null}));
+        let mut outline = Outline::default();
+        outline.update(&json);
+        assert_eq!(outline.nodes.len(), 6);
+
+        // Scrolling stays within the nodes
+        assert!(outline.scroll_by(10, 2));
+        assert_eq!(outline.top, 4);
+        assert!(!outline.scroll_by(1, 2));
+        assert!(outline.scroll_by(isize::MIN, 2));
+        assert_eq!(outline.top, 0);
+
+        // Listed again only once the folds change
+        outline.selection = Some(vec![]);
+        outline.update(&json);
+        assert!(outline.selection.is_some());
+        json.set_selection(vec![Token::Key("a".into())]);
+        json.toggle_fold();
+        outline.update(&json);
+        assert!(outline.selection.is_none());
+        assert_eq!(outline.nodes.len(), 3);
+    }
+}
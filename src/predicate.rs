@@ -0,0 +1,305 @@
+use regex::Regex;
+use serde_json::Value;
+
+use crate::json::{select_paths, Token};
+
+#[derive(Clone, Copy)]
+enum Field {
+    Type,
+    Key,
+    Str,
+    Num,
+    Len,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Match,
+    NotMatch,
+}
+
+const OPS: &[(&str, Op)] = &[
+    ("==", Op::Eq),
+    ("!=", Op::Ne),
+    ("<=", Op::Le),
+    (">=", Op::Ge),
+    ("!~", Op::NotMatch),
+    ("=", Op::Eq),
+    ("<", Op::Lt),
+    (">", Op::Gt),
+    ("~", Op::Match),
+];
+
+const TYPES: &[&str] = &["null", "boolean", "number", "string", "array", "object"];
+
+enum Test {
+    Type(&'static str),
+    Number(f64),
+    Text(String),
+    Regex(Regex),
+}
+
+struct Clause {
+    field: Field,
+    op: Op,
+    test: Test,
+}
+
+/// A search predicate evaluated against values rather than their formatted text, such as
+/// `type null`, `num > 500`, `len == 0` or `key ~ ^x-`. Clauses can be joined with `&&`.
+pub struct Predicate(Vec<Clause>);
+
+impl Predicate {
+    pub fn parse(input: &str) -> Result<Self, String> {
+        split_clauses(input)
+            .into_iter()
+            .map(|clause| parse_clause(clause.trim()))
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+
+    /// Tests a node, given the token it's reached by and its value.
+    fn matches(&self, token: Option<&Token>, value: &Value) -> bool {
+        self.0.iter().all(|clause| clause.matches(token, value))
+    }
+
+    /// Returns the paths of all nodes matching the predicate, in document order.
+    pub fn select(&self, root: &Value) -> Vec<Vec<Token>> {
+        select_paths(root, |token, value| self.matches(token, value))
+    }
+}
+
+/// Splits a predicate at the `&&`s joining clauses. Quoted values may contain `&&`, and so
+/// may regexes unless it's preceded by a space, as in `key ~ a&&b && len > 2`.
+fn split_clauses(input: &str) -> Vec<&str> {
+    let mut clauses = vec![];
+    let mut start = 0;
+    let mut quote = None;
+    let mut regex = false;
+    let mut previous = ' ';
+    let mut chars = input.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '&'
+                && chars.peek().is_some_and(|&(_, next)| next == '&')
+                && (!regex || previous.is_whitespace()) =>
+            {
+                chars.next();
+                clauses.push(&input[start..i]);
+                start = i + 2;
+                regex = false;
+            }
+            None if c == '~' => regex = true,
+            // Only at the start of a value, so that `it's` isn't a quote
+            None if matches!(c, '"' | '\'')
+                && (previous.is_whitespace() || "=<>~".contains(previous)) =>
+            {
+                quote = Some(c)
+            }
+            None => {}
+        }
+        previous = c;
+    }
+    clauses.push(&input[start..]);
+
+    clauses
+}
+
+fn parse_clause(clause: &str) -> Result<Clause, String> {
+    let name_end = clause
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(clause.len());
+    let (name, rest) = clause.split_at(name_end);
+    let field = match name {
+        "type" => Field::Type,
+        "key" => Field::Key,
+        "str" => Field::Str,
+        "num" => Field::Num,
+        "len" => Field::Len,
+        "" => return Err("Expected type, key, str, num or len".to_string()),
+        _ => return Err(format!("Unknown field '{}'", name)),
+    };
+
+    let rest = rest.trim_start();
+    let (op, operand) = match OPS.iter().find(|(s, _)| rest.starts_with(s)) {
+        Some((s, op)) => (*op, rest[s.len()..].trim()),
+        None => (Op::Eq, rest.trim()),
+    };
+    let operand = unquote(operand);
+    if operand.is_empty() {
+        return Err(format!("Missing value for '{}'", name));
+    }
+
+    let test = match (field, op) {
+        (Field::Type, Op::Eq | Op::Ne) => {
+            let operand = if operand == "bool" {
+                "boolean"
+            } else {
+                operand
+            };
+            match TYPES.iter().find(|t| **t == operand) {
+                Some(t) => Test::Type(t),
+                None => return Err(format!("Unknown type '{}'", operand)),
+            }
+        }
+        (Field::Num | Field::Len, op) if !matches!(op, Op::Match | Op::NotMatch) => {
+            match operand.parse() {
+                Ok(n) => Test::Number(n),
+                Err(_) => return Err(format!("Invalid number '{}'", operand)),
+            }
+        }
+        (Field::Key | Field::Str, Op::Eq | Op::Ne) => Test::Text(operand.to_string()),
+        (Field::Key | Field::Str, Op::Match | Op::NotMatch) => match Regex::new(operand) {
+            Ok(re) => Test::Regex(re),
+            Err(_) => return Err(format!("Invalid regex '{}'", operand)),
+        },
+        _ => return Err(format!("Unsupported operator for '{}'", name)),
+    };
+
+    Ok(Clause { field, op, test })
+}
+
+fn unquote(s: &str) -> &str {
+    ['"', '\'']
+        .iter()
+        .find_map(|&q| s.strip_prefix(q).and_then(|s| s.strip_suffix(q)))
+        .unwrap_or(s)
+}
+
+impl Clause {
+    fn matches(&self, token: Option<&Token>, value: &Value) -> bool {
+        let subject = match self.field {
+            Field::Type => {
+                let name = match value {
+                    Value::Null => "null",
+                    Value::Bool(_) => "boolean",
+                    Value::Number(_) => "number",
+                    Value::String(_) => "string",
+                    Value::Array(_) => "array",
+                    Value::Object(_) => "object",
+                };
+                return matches!(self.test, Test::Type(t) if t == name) == (self.op == Op::Eq);
+            }
+            Field::Key => match token {
+                Some(Token::Key(k)) => k.as_str(),
+                _ => return false,
+            },
+            Field::Str => match value {
+                Value::String(s) => s.as_str(),
+                _ => return false,
+            },
+            Field::Num => match value.as_f64() {
+                Some(n) => return self.compare(n),
+                None => return false,
+            },
+            Field::Len => match value {
+                Value::String(s) => return self.compare(s.chars().count() as f64),
+                Value::Array(a) => return self.compare(a.len() as f64),
+                Value::Object(o) => return self.compare(o.len() as f64),
+                _ => return false,
+            },
+        };
+
+        match (&self.test, self.op) {
+            (Test::Text(t), Op::Eq) => subject == t,
+            (Test::Text(t), Op::Ne) => subject != t,
+            (Test::Regex(re), Op::Match) => re.is_match(subject),
+            (Test::Regex(re), Op::NotMatch) => !re.is_match(subject),
+            _ => false,
+        }
+    }
+
+    fn compare(&self, n: f64) -> bool {
+        let Test::Number(target) = self.test else {
+            return false;
+        };
+
+        match self.op {
+            Op::Eq => n == target,
+            Op::Ne => n != target,
+            Op::Lt => n < target,
+            Op::Le => n <= target,
+            Op::Gt => n > target,
+            Op::Ge => n >= target,
+            Op::Match | Op::NotMatch => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::json::Pointer;
+
+    fn select(predicate: &str, value: &Value) -> Vec<String> {
+        Predicate::parse(predicate)
+            .unwrap()
+            .select(value)
+            .iter()
+            .map(|t| Pointer::json_pointer(t))
+            .collect()
+    }
+
+    #[test]
+    fn predicates() {
+        let value = json!({
+            "x-id": 900,
+            "x-tags": [],
+            "name": "abc",
+            "count": 3,
+            "extra": null,
+            "list": [null, 501]
+        });
+
+        assert_eq!(select("type null", &value), vec!["/extra", "/list/0"]);
+        assert_eq!(select("num > 500", &value), vec!["/x-id", "/list/1"]);
+        assert_eq!(select("len == 0", &value), vec!["/x-tags"]);
+        assert_eq!(select("key ~ ^x-", &value), vec!["/x-id", "/x-tags"]);
+        assert_eq!(select("str = 'abc'", &value), vec!["/name"]);
+        assert_eq!(select("type number && num < 500", &value), vec!["/count"]);
+        assert_eq!(select("type != null", &value).len(), 7);
+    }
+
+    #[test]
+    fn clauses() {
+        assert_eq!(
+            split_clauses("type string && len > 2"),
+            vec!["type string ", " len > 2"]
+        );
+        assert_eq!(
+            split_clauses("key ~ a&&b && len > 2"),
+            vec!["key ~ a&&b ", " len > 2"]
+        );
+        assert_eq!(
+            split_clauses("str = 'x && y'&&num<1"),
+            vec!["str = 'x && y'", "num<1"]
+        );
+        assert_eq!(
+            split_clauses("str = it's && len > 1"),
+            vec!["str = it's ", " len > 1"]
+        );
+
+        let value = json!({"a": "x && y", "b&&c": 1});
+        assert_eq!(select("str == \"x && y\"", &value), vec!["/a"]);
+        assert_eq!(select("key ~ b&&c && num = 1", &value), vec!["/b&&c"]);
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(Predicate::parse("type nothing").is_err());
+        assert!(Predicate::parse("num > x").is_err());
+        assert!(Predicate::parse("key ~ (").is_err());
+        assert!(Predicate::parse("foo == 1").is_err());
+        assert!(Predicate::parse("type ~ null").is_err());
+    }
+}
@@ -0,0 +1,266 @@
+use std::io::Write;
+
+use crossterm::{
+    event::{KeyCode, KeyModifiers},
+    queue,
+    style::{Print, PrintStyledContent},
+};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::style::{styled, STYLE_PROMPT_CURSOR, STYLE_SEARCH_PROMPT};
+
+/// An editing operation on a prompt's input.
+pub enum Edit {
+    Insert(char),
+    Paste(String),
+    Backspace,
+    Delete,
+    Left,
+    Right,
+    Start,
+    End,
+    WordLeft,
+    WordRight,
+    DeleteWord,
+    DeleteToStart,
+    DeleteToEnd,
+}
+
+impl Edit {
+    /// Maps the line-editing keys shared by every prompt.
+    pub fn from_key(code: KeyCode, modifiers: KeyModifiers) -> Option<Self> {
+        use KeyCode::*;
+
+        Some(match (code, modifiers) {
+            (Char(c), KeyModifiers::NONE | KeyModifiers::SHIFT) => Edit::Insert(c),
+            (Backspace, KeyModifiers::ALT) | (Char('w'), KeyModifiers::CONTROL) => Edit::DeleteWord,
+            (Backspace, _) | (Char('h'), KeyModifiers::CONTROL) => Edit::Backspace,
+            (Delete, _) | (Char('d'), KeyModifiers::CONTROL) => Edit::Delete,
+            (Left, KeyModifiers::CONTROL) | (Char('b'), KeyModifiers::ALT) => Edit::WordLeft,
+            (Right, KeyModifiers::CONTROL) | (Char('f'), KeyModifiers::ALT) => Edit::WordRight,
+            (Left, _) | (Char('b'), KeyModifiers::CONTROL) => Edit::Left,
+            (Right, _) | (Char('f'), KeyModifiers::CONTROL) => Edit::Right,
+            (Home, _) | (Char('a'), KeyModifiers::CONTROL) => Edit::Start,
+            (End, _) | (Char('e'), KeyModifiers::CONTROL) => Edit::End,
+            (Char('u'), KeyModifiers::CONTROL) => Edit::DeleteToStart,
+            (Char('k'), KeyModifiers::CONTROL) => Edit::DeleteToEnd,
+            _ => return None,
+        })
+    }
+}
+
+/// A single-line text input with a cursor.
+#[derive(Clone, Default)]
+pub struct Prompt {
+    chars: Vec<char>,
+    cursor: usize,
+}
+
+impl Prompt {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a prompt holding `text`, with the cursor at the end.
+    pub fn with_text(text: &str) -> Self {
+        let mut prompt = Self::new();
+        prompt.set_text(text);
+        prompt
+    }
+
+    pub fn text(&self) -> String {
+        self.chars.iter().collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chars.is_empty()
+    }
+
+    /// Replaces the input and moves the cursor to the end.
+    pub fn set_text(&mut self, text: &str) {
+        self.chars = text.chars().collect();
+        self.cursor = self.chars.len();
+    }
+
+    /// Applies an edit, returning whether the input text changed.
+    pub fn edit(&mut self, edit: Edit) -> bool {
+        let before = self.chars.len();
+        let cursor = self.cursor;
+
+        match edit {
+            Edit::Insert(c) => {
+                self.chars.insert(self.cursor, c);
+                self.cursor += 1;
+            }
+            Edit::Paste(s) => {
+                // Prompts are single-line, so line breaks become spaces
+                let pasted: Vec<char> = s
+                    .trim_end_matches(['\n', '\r'])
+                    .chars()
+                    .map(|c| if c == '\n' || c == '\r' { ' ' } else { c })
+                    .collect();
+                self.cursor += pasted.len();
+                self.chars.splice(cursor..cursor, pasted);
+            }
+            Edit::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                self.chars.remove(self.cursor);
+            }
+            Edit::Delete if self.cursor < self.chars.len() => {
+                self.chars.remove(self.cursor);
+            }
+            Edit::Left => self.cursor = self.cursor.saturating_sub(1),
+            Edit::Right => self.cursor = (self.cursor + 1).min(self.chars.len()),
+            Edit::Start => self.cursor = 0,
+            Edit::End => self.cursor = self.chars.len(),
+            Edit::WordLeft => self.cursor = self.word_start(),
+            Edit::WordRight => self.cursor = self.word_end(),
+            Edit::DeleteWord => {
+                self.cursor = self.word_start();
+                self.chars.drain(self.cursor..cursor);
+            }
+            Edit::DeleteToStart => {
+                self.chars.drain(..self.cursor);
+                self.cursor = 0;
+            }
+            Edit::DeleteToEnd => {
+                self.chars.truncate(self.cursor);
+            }
+            Edit::Backspace | Edit::Delete => {}
+        }
+
+        // Every edit that changes the text changes its length, except inserting nothing
+        self.chars.len() != before
+    }
+
+    /// Start of the word before the cursor, skipping any whitespace first.
+    fn word_start(&self) -> usize {
+        let mut i = self.cursor;
+        while i > 0 && !is_word_char(self.chars[i - 1]) {
+            i -= 1;
+        }
+        while i > 0 && is_word_char(self.chars[i - 1]) {
+            i -= 1;
+        }
+        i
+    }
+
+    /// End of the word after the cursor, skipping any whitespace first.
+    fn word_end(&self) -> usize {
+        let mut i = self.cursor;
+        while i < self.chars.len() && !is_word_char(self.chars[i]) {
+            i += 1;
+        }
+        while i < self.chars.len() && is_word_char(self.chars[i]) {
+            i += 1;
+        }
+        i
+    }
+
+    /// Renders `symbol` and the input, with the cursor shown as a reversed cell.
+    /// The input is scrolled horizontally to keep the cursor within `width` columns.
+    pub fn render<W: Write>(&self, out: &mut W, symbol: &str, width: usize) -> anyhow::Result<()> {
+        queue!(out, PrintStyledContent(styled(STYLE_SEARCH_PROMPT, symbol)))?;
+
+        let (start, end) = self.window(width.saturating_sub(symbol.width()).max(1));
+        let before: String = self.chars[start..self.cursor].iter().collect();
+        let under = self.chars.get(self.cursor).copied().unwrap_or(' ');
+        let after: String = self.chars[(self.cursor + 1).min(end)..end].iter().collect();
+
+        queue!(
+            out,
+            Print(before),
+            PrintStyledContent(styled(STYLE_PROMPT_CURSOR, under)),
+            Print(after)
+        )?;
+
+        Ok(())
+    }
+
+    /// The range of characters shown in `width` columns, scrolled to keep the cursor visible.
+    fn window(&self, width: usize) -> (usize, usize) {
+        let char_width = |i: usize| self.chars.get(i).map_or(1, |c| c.width().unwrap_or(0));
+
+        let mut used = char_width(self.cursor);
+        let mut start = self.cursor;
+        while start > 0 && used + char_width(start - 1) <= width {
+            start -= 1;
+            used += char_width(start);
+        }
+        let mut end = (self.cursor + 1).min(self.chars.len());
+        while end < self.chars.len() && used + char_width(end) <= width {
+            used += char_width(end);
+            end += 1;
+        }
+
+        (start, end)
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(prompt: &mut Prompt, edits: Vec<Edit>) {
+        for edit in edits {
+            prompt.edit(edit);
+        }
+    }
+
+    #[test]
+    fn cursor_editing() {
+        let mut prompt = Prompt::with_text("héllo");
+        apply(
+            &mut prompt,
+            vec![
+                Edit::Left,
+                Edit::Left,
+                Edit::Insert('X'),
+                Edit::Start,
+                Edit::Delete,
+            ],
+        );
+        assert_eq!(prompt.text(), "élXlo");
+
+        apply(&mut prompt, vec![Edit::End, Edit::Backspace, Edit::Right]);
+        assert_eq!(prompt.text(), "élXl");
+        assert_eq!(prompt.cursor, 4);
+
+        prompt.edit(Edit::Paste("a\nb\n".to_string()));
+        assert_eq!(prompt.text(), "élXla b");
+    }
+
+    #[test]
+    fn word_editing() {
+        let mut prompt = Prompt::with_text("foo.bar  baz");
+        apply(&mut prompt, vec![Edit::WordLeft, Edit::WordLeft]);
+        assert_eq!(prompt.cursor, 4);
+        apply(&mut prompt, vec![Edit::WordRight]);
+        assert_eq!(prompt.cursor, 7);
+
+        assert!(prompt.edit(Edit::DeleteWord));
+        assert_eq!(prompt.text(), "foo.  baz");
+        assert!(!prompt.edit(Edit::Left) && prompt.edit(Edit::DeleteToStart));
+        assert_eq!(prompt.text(), ".  baz");
+        assert!(prompt.edit(Edit::DeleteToEnd));
+        assert_eq!(prompt.text(), "");
+    }
+
+    #[test]
+    fn scrolling() {
+        let mut prompt = Prompt::with_text("abcdef");
+        assert_eq!(prompt.window(4), (3, 6));
+        prompt.edit(Edit::Start);
+        assert_eq!(prompt.window(4), (0, 4));
+
+        // Wide characters take two columns each
+        let mut prompt = Prompt::with_text("日本語テキスト");
+        assert_eq!(prompt.window(7), (4, 7));
+        apply(&mut prompt, vec![Edit::Start, Edit::Right]);
+        assert_eq!(prompt.window(7), (0, 3));
+    }
+}
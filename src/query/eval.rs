@@ -0,0 +1,779 @@
+use std::cmp::Ordering;
+
+use serde_json::{Map, Number, Value};
+
+use super::parser::{BinOp, Expr};
+
+type Results = Result<Vec<Value>, String>;
+
+/// Most values `range` produces, since results aren't computed lazily.
+const MAX_RANGE: f64 = 1_000_000.0;
+
+/// Evaluates an expression against an input, producing a stream of values.
+pub fn eval(expr: &Expr, input: &Value) -> Results {
+    match expr {
+        Expr::Identity => Ok(vec![input.clone()]),
+        Expr::Recurse => {
+            let mut out = vec![];
+            recurse(input, &mut out);
+            Ok(out)
+        }
+        Expr::Literal(v) => Ok(vec![v.clone()]),
+        Expr::Index(target, key) => {
+            let mut out = vec![];
+            for t in eval(target, input)? {
+                for k in eval(key, input)? {
+                    out.push(index(&t, &k)?);
+                }
+            }
+            Ok(out)
+        }
+        Expr::Slice(target, from, to) => {
+            let mut out = vec![];
+            let from = optional_bound(from.as_deref(), input)?;
+            let to = optional_bound(to.as_deref(), input)?;
+            for t in eval(target, input)? {
+                out.push(slice(&t, from, to)?);
+            }
+            Ok(out)
+        }
+        Expr::Iterate(target) => {
+            let mut out = vec![];
+            for t in eval(target, input)? {
+                out.extend(iterate(&t)?);
+            }
+            Ok(out)
+        }
+        Expr::Optional(inner) => Ok(eval(inner, input).unwrap_or_default()),
+        Expr::Pipe(lhs, rhs) => {
+            let mut out = vec![];
+            for v in eval(lhs, input)? {
+                out.extend(eval(rhs, &v)?);
+            }
+            Ok(out)
+        }
+        Expr::Comma(lhs, rhs) => {
+            let mut out = eval(lhs, input)?;
+            out.extend(eval(rhs, input)?);
+            Ok(out)
+        }
+        Expr::Alternative(lhs, rhs) => {
+            let truthy: Vec<Value> = eval(lhs, input)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(is_truthy)
+                .collect();
+            if truthy.is_empty() {
+                eval(rhs, input)
+            } else {
+                Ok(truthy)
+            }
+        }
+        Expr::Or(lhs, rhs) => {
+            let mut out = vec![];
+            for l in eval(lhs, input)? {
+                if is_truthy(&l) {
+                    out.push(Value::Bool(true));
+                } else {
+                    for r in eval(rhs, input)? {
+                        out.push(Value::Bool(is_truthy(&r)));
+                    }
+                }
+            }
+            Ok(out)
+        }
+        Expr::And(lhs, rhs) => {
+            let mut out = vec![];
+            for l in eval(lhs, input)? {
+                if !is_truthy(&l) {
+                    out.push(Value::Bool(false));
+                } else {
+                    for r in eval(rhs, input)? {
+                        out.push(Value::Bool(is_truthy(&r)));
+                    }
+                }
+            }
+            Ok(out)
+        }
+        Expr::Binary(op, lhs, rhs) => {
+            let mut out = vec![];
+            for r in eval(rhs, input)? {
+                for l in eval(lhs, input)? {
+                    out.push(binary(*op, &l, &r)?);
+                }
+            }
+            Ok(out)
+        }
+        Expr::Neg(inner) => eval(inner, input)?
+            .iter()
+            .map(|v| match v {
+                Value::Number(n) => Ok(number(-as_f64(n))),
+                _ => Err(format!("{} cannot be negated", type_name(v))),
+            })
+            .collect(),
+        Expr::Array(inner) => Ok(vec![Value::Array(match inner {
+            Some(inner) => eval(inner, input)?,
+            None => vec![],
+        })]),
+        Expr::Object(entries) => {
+            let mut objects = vec![Map::new()];
+            for (key, value) in entries {
+                let keys = eval(key, input)?;
+                let values = eval(value, input)?;
+                let mut next = vec![];
+                for object in &objects {
+                    for k in &keys {
+                        let Value::String(k) = k else {
+                            return Err(format!(
+                                "Object keys must be strings, not {}",
+                                type_name(k)
+                            ));
+                        };
+                        for v in &values {
+                            let mut object = object.clone();
+                            object.insert(k.clone(), v.clone());
+                            next.push(object);
+                        }
+                    }
+                }
+                objects = next;
+            }
+            Ok(objects.into_iter().map(Value::Object).collect())
+        }
+        Expr::If(condition, then, otherwise) => {
+            let mut out = vec![];
+            for c in eval(condition, input)? {
+                if is_truthy(&c) {
+                    out.extend(eval(then, input)?);
+                } else {
+                    out.extend(eval(otherwise, input)?);
+                }
+            }
+            Ok(out)
+        }
+        Expr::Call(name, args) => call(name, args, input),
+    }
+}
+
+fn recurse(value: &Value, out: &mut Vec<Value>) {
+    out.push(value.clone());
+    match value {
+        Value::Array(a) => a.iter().for_each(|v| recurse(v, out)),
+        Value::Object(o) => o.values().for_each(|v| recurse(v, out)),
+        _ => {}
+    }
+}
+
+fn optional_bound(expr: Option<&Expr>, input: &Value) -> Result<Option<f64>, String> {
+    let Some(expr) = expr else {
+        return Ok(None);
+    };
+    match eval(expr, input)?.first() {
+        Some(Value::Number(n)) => Ok(Some(as_f64(n))),
+        Some(Value::Null) | None => Ok(None),
+        Some(v) => Err(format!(
+            "Slice bounds must be numbers, not {}",
+            type_name(v)
+        )),
+    }
+}
+
+fn index(target: &Value, key: &Value) -> Result<Value, String> {
+    match (target, key) {
+        (Value::Object(o), Value::String(k)) => Ok(o.get(k).cloned().unwrap_or(Value::Null)),
+        (Value::Array(a), Value::Number(n)) => {
+            let i = as_f64(n).floor() as i64;
+            let i = if i < 0 { a.len() as i64 + i } else { i };
+            Ok(usize::try_from(i)
+                .ok()
+                .and_then(|i| a.get(i))
+                .cloned()
+                .unwrap_or(Value::Null))
+        }
+        (Value::Null, Value::String(_) | Value::Number(_)) => Ok(Value::Null),
+        _ => Err(format!(
+            "Cannot index {} with {}",
+            type_name(target),
+            type_name(key)
+        )),
+    }
+}
+
+fn slice(target: &Value, from: Option<f64>, to: Option<f64>) -> Result<Value, String> {
+    let bounds = |len: usize| {
+        let clamp = |b: f64| {
+            let b = if b < 0.0 { len as f64 + b } else { b };
+            b.clamp(0.0, len as f64) as usize
+        };
+        let from = from.map_or(0, clamp);
+        let to = to.map_or(len, clamp);
+        (from, to.max(from))
+    };
+
+    match target {
+        Value::Array(a) => {
+            let (from, to) = bounds(a.len());
+            Ok(Value::Array(a[from..to].to_vec()))
+        }
+        Value::String(s) => {
+            let chars: Vec<char> = s.chars().collect();
+            let (from, to) = bounds(chars.len());
+            Ok(Value::String(chars[from..to].iter().collect()))
+        }
+        Value::Null => Ok(Value::Null),
+        _ => Err(format!("Cannot slice {}", type_name(target))),
+    }
+}
+
+fn iterate(value: &Value) -> Results {
+    match value {
+        Value::Array(a) => Ok(a.clone()),
+        Value::Object(o) => Ok(o.values().cloned().collect()),
+        _ => Err(format!("Cannot iterate over {}", type_name(value))),
+    }
+}
+
+fn binary(op: BinOp, l: &Value, r: &Value) -> Result<Value, String> {
+    let incompatible = |verb: &str| {
+        Err(format!(
+            "{} and {} cannot be {}",
+            type_name(l),
+            type_name(r),
+            verb
+        ))
+    };
+
+    match op {
+        BinOp::Eq => Ok(Value::Bool(compare(l, r) == Ordering::Equal)),
+        BinOp::Ne => Ok(Value::Bool(compare(l, r) != Ordering::Equal)),
+        BinOp::Lt => Ok(Value::Bool(compare(l, r) == Ordering::Less)),
+        BinOp::Le => Ok(Value::Bool(compare(l, r) != Ordering::Greater)),
+        BinOp::Gt => Ok(Value::Bool(compare(l, r) == Ordering::Greater)),
+        BinOp::Ge => Ok(Value::Bool(compare(l, r) != Ordering::Less)),
+        BinOp::Add => match (l, r) {
+            (Value::Null, v) | (v, Value::Null) => Ok(v.clone()),
+            (Value::Number(a), Value::Number(b)) => Ok(number(as_f64(a) + as_f64(b))),
+            (Value::String(a), Value::String(b)) => Ok(Value::String(format!("{}{}", a, b))),
+            (Value::Array(a), Value::Array(b)) => Ok(Value::Array([a.clone(), b.clone()].concat())),
+            (Value::Object(a), Value::Object(b)) => {
+                let mut merged = a.clone();
+                merged.extend(b.clone());
+                Ok(Value::Object(merged))
+            }
+            _ => incompatible("added"),
+        },
+        BinOp::Sub => match (l, r) {
+            (Value::Number(a), Value::Number(b)) => Ok(number(as_f64(a) - as_f64(b))),
+            (Value::Array(a), Value::Array(b)) => Ok(Value::Array(
+                a.iter().filter(|v| !b.contains(v)).cloned().collect(),
+            )),
+            _ => incompatible("subtracted"),
+        },
+        BinOp::Mul => match (l, r) {
+            (Value::Number(a), Value::Number(b)) => Ok(number(as_f64(a) * as_f64(b))),
+            (Value::Object(a), Value::Object(b)) => {
+                let mut merged = a.clone();
+                merged.extend(b.clone());
+                Ok(Value::Object(merged))
+            }
+            _ => incompatible("multiplied"),
+        },
+        BinOp::Div => match (l, r) {
+            (Value::Number(a), Value::Number(b)) if as_f64(b) == 0.0 => {
+                Err(format!("{} cannot be divided by zero", a))
+            }
+            (Value::Number(a), Value::Number(b)) => Ok(number(as_f64(a) / as_f64(b))),
+            (Value::String(a), Value::String(b)) => Ok(Value::Array(
+                a.split(b.as_str())
+                    .map(|s| Value::String(s.to_string()))
+                    .collect(),
+            )),
+            _ => incompatible("divided"),
+        },
+        BinOp::Rem => match (l, r) {
+            (Value::Number(a), Value::Number(b)) => {
+                let b = as_f64(b) as i64;
+                if b == 0 {
+                    return Err(format!("{} cannot be divided by zero", a));
+                }
+                match (as_f64(a) as i64).checked_rem(b) {
+                    Some(n) => Ok(number(n as f64)),
+                    None => Err(format!("{} % {} overflows", a, b)),
+                }
+            }
+            _ => incompatible("divided"),
+        },
+    }
+}
+
+/// Evaluates a builtin function.
+fn call(name: &str, args: &[Expr], input: &Value) -> Results {
+    let one = |v: Value| Ok(vec![v]);
+
+    match (name, args) {
+        ("empty", []) => Ok(vec![]),
+        ("not", []) => one(Value::Bool(!is_truthy(input))),
+        ("length", []) => one(match input {
+            Value::Null => number(0.0),
+            Value::Bool(_) => return Err("boolean has no length".to_string()),
+            Value::Number(n) => number(as_f64(n).abs()),
+            Value::String(s) => number(s.chars().count() as f64),
+            Value::Array(a) => number(a.len() as f64),
+            Value::Object(o) => number(o.len() as f64),
+        }),
+        ("keys" | "keys_unsorted", []) => {
+            let mut keys: Vec<Value> = match input {
+                Value::Object(o) => o.keys().map(|k| Value::String(k.clone())).collect(),
+                Value::Array(a) => (0..a.len()).map(|i| number(i as f64)).collect(),
+                _ => return Err(format!("{} has no keys", type_name(input))),
+            };
+            if name == "keys" {
+                keys.sort_by(compare);
+            }
+            one(Value::Array(keys))
+        }
+        ("has", [key]) => eval(key, input)?
+            .iter()
+            .map(|k| match (input, k) {
+                (Value::Object(o), Value::String(k)) => Ok(Value::Bool(o.contains_key(k))),
+                (Value::Array(a), Value::Number(n)) => Ok(Value::Bool(
+                    as_f64(n) >= 0.0 && (as_f64(n) as usize) < a.len(),
+                )),
+                _ => Err(format!(
+                    "Cannot check whether {} has a {} key",
+                    type_name(input),
+                    type_name(k)
+                )),
+            })
+            .collect(),
+        ("select", [f]) => Ok(if eval(f, input)?.iter().any(is_truthy) {
+            vec![input.clone()]
+        } else {
+            vec![]
+        }),
+        ("map", [f]) => {
+            let mut out = vec![];
+            for v in iterate(input)? {
+                out.extend(eval(f, &v)?);
+            }
+            one(Value::Array(out))
+        }
+        ("map_values", [f]) => match input {
+            Value::Object(o) => {
+                let mut out = Map::new();
+                for (k, v) in o {
+                    if let Some(v) = eval(f, v)?.into_iter().next() {
+                        out.insert(k.clone(), v);
+                    }
+                }
+                one(Value::Object(out))
+            }
+            _ => {
+                let mut out = vec![];
+                for v in iterate(input)? {
+                    out.extend(eval(f, &v)?.into_iter().next());
+                }
+                one(Value::Array(out))
+            }
+        },
+        ("recurse", []) => eval(&Expr::Recurse, input),
+        ("type", []) => one(Value::String(type_name(input).to_string())),
+        ("values", []) => select_if(input, !input.is_null()),
+        ("nulls", []) => select_if(input, input.is_null()),
+        ("booleans", []) => select_if(input, input.is_boolean()),
+        ("numbers", []) => select_if(input, input.is_number()),
+        ("strings", []) => select_if(input, input.is_string()),
+        ("arrays", []) => select_if(input, input.is_array()),
+        ("objects", []) => select_if(input, input.is_object()),
+        ("iterables", []) => select_if(input, input.is_array() || input.is_object()),
+        ("scalars", []) => select_if(input, !input.is_array() && !input.is_object()),
+        ("add", []) => {
+            let mut sum = Value::Null;
+            for v in iterate(input)? {
+                sum = binary(BinOp::Add, &sum, &v)?;
+            }
+            one(sum)
+        }
+        ("any", []) => one(Value::Bool(iterate(input)?.iter().any(is_truthy))),
+        ("all", []) => one(Value::Bool(iterate(input)?.iter().all(is_truthy))),
+        ("any", [f]) => {
+            for v in iterate(input)? {
+                if eval(f, &v)?.iter().any(is_truthy) {
+                    return one(Value::Bool(true));
+                }
+            }
+            one(Value::Bool(false))
+        }
+        ("all", [f]) => {
+            for v in iterate(input)? {
+                if !eval(f, &v)?.iter().all(is_truthy) {
+                    return one(Value::Bool(false));
+                }
+            }
+            one(Value::Bool(true))
+        }
+        ("first", []) => Ok(vec![index(input, &number(0.0))?]),
+        ("last", []) => Ok(vec![index(input, &number(-1.0))?]),
+        ("first", [f]) => Ok(eval(f, input)?.into_iter().take(1).collect()),
+        ("last", [f]) => Ok(eval(f, input)?.into_iter().last().into_iter().collect()),
+        ("limit", [n, f]) => {
+            let mut out = vec![];
+            for n in eval(n, input)? {
+                let n = n.as_f64().ok_or("limit requires a number")?;
+                out.extend(eval(f, input)?.into_iter().take(n.max(0.0) as usize));
+            }
+            Ok(out)
+        }
+        ("range", [n]) => {
+            let mut out = vec![];
+            for n in eval(n, input)? {
+                let n = n.as_f64().ok_or("range requires numbers")?;
+                out.extend(range(0.0, n)?);
+            }
+            Ok(out)
+        }
+        ("range", [from, to]) => {
+            let mut out = vec![];
+            for from in eval(from, input)? {
+                for to in eval(to, input)? {
+                    let (Some(from), Some(to)) = (from.as_f64(), to.as_f64()) else {
+                        return Err("range requires numbers".to_string());
+                    };
+                    out.extend(range(from, to)?);
+                }
+            }
+            Ok(out)
+        }
+        ("reverse", []) => match input {
+            Value::Array(a) => one(Value::Array(a.iter().rev().cloned().collect())),
+            Value::String(s) => one(Value::String(s.chars().rev().collect())),
+            Value::Null => one(Value::Array(vec![])),
+            _ => Err(format!("Cannot reverse {}", type_name(input))),
+        },
+        ("sort", []) => {
+            let mut items = array(input, name)?;
+            items.sort_by(compare);
+            one(Value::Array(items))
+        }
+        ("sort_by", [f]) => {
+            let mut keyed = keyed(input, f, name)?;
+            keyed.sort_by(|(a, _), (b, _)| compare(a, b));
+            one(Value::Array(keyed.into_iter().map(|(_, v)| v).collect()))
+        }
+        ("group_by", [f]) => {
+            let mut keyed = keyed(input, f, name)?;
+            keyed.sort_by(|(a, _), (b, _)| compare(a, b));
+            let mut groups: Vec<(Value, Vec<Value>)> = vec![];
+            for (k, v) in keyed {
+                match groups.last_mut() {
+                    Some((last, group)) if compare(last, &k) == Ordering::Equal => group.push(v),
+                    _ => groups.push((k, vec![v])),
+                }
+            }
+            one(Value::Array(
+                groups.into_iter().map(|(_, g)| Value::Array(g)).collect(),
+            ))
+        }
+        ("unique", []) => {
+            let mut items = array(input, name)?;
+            items.sort_by(compare);
+            items.dedup_by(|a, b| compare(a, b) == Ordering::Equal);
+            one(Value::Array(items))
+        }
+        ("unique_by", [f]) => {
+            let mut keyed = keyed(input, f, name)?;
+            keyed.sort_by(|(a, _), (b, _)| compare(a, b));
+            keyed.dedup_by(|(a, _), (b, _)| compare(a, b) == Ordering::Equal);
+            one(Value::Array(keyed.into_iter().map(|(_, v)| v).collect()))
+        }
+        ("min" | "max", []) => {
+            let items = array(input, name)?;
+            let pick = if name == "min" {
+                items.into_iter().min_by(compare)
+            } else {
+                items.into_iter().max_by(compare)
+            };
+            one(pick.unwrap_or(Value::Null))
+        }
+        ("min_by" | "max_by", [f]) => {
+            let keyed = keyed(input, f, name)?;
+            let pick = if name == "min_by" {
+                keyed.into_iter().min_by(|(a, _), (b, _)| compare(a, b))
+            } else {
+                keyed.into_iter().max_by(|(a, _), (b, _)| compare(a, b))
+            };
+            one(pick.map(|(_, v)| v).unwrap_or(Value::Null))
+        }
+        ("flatten", []) => one(Value::Array(flatten(&array(input, name)?, usize::MAX))),
+        ("flatten", [depth]) => eval(depth, input)?
+            .iter()
+            .map(|d| {
+                let d = d.as_f64().ok_or("flatten depth must be a number")?;
+                Ok(Value::Array(flatten(
+                    &array(input, name)?,
+                    d.max(0.0) as usize,
+                )))
+            })
+            .collect(),
+        ("to_entries", []) => match input {
+            Value::Object(o) => one(Value::Array(
+                o.iter()
+                    .map(|(k, v)| {
+                        let mut entry = Map::new();
+                        entry.insert("key".to_string(), Value::String(k.clone()));
+                        entry.insert("value".to_string(), v.clone());
+                        Value::Object(entry)
+                    })
+                    .collect(),
+            )),
+            _ => Err(format!("{} has no keys", type_name(input))),
+        },
+        ("from_entries", []) => {
+            let mut out = Map::new();
+            for entry in array(input, name)? {
+                let key = ["key", "k", "name", "Name", "Key", "K"]
+                    .iter()
+                    .find_map(|k| entry.get(k).filter(|v| !v.is_null()))
+                    .ok_or("from_entries requires a key on each entry")?;
+                let key = match key {
+                    Value::String(s) => s.clone(),
+                    v => v.to_string(),
+                };
+                let value = ["value", "v", "Value", "V"]
+                    .iter()
+                    .find_map(|k| entry.get(k))
+                    .cloned()
+                    .unwrap_or(Value::Null);
+                out.insert(key, value);
+            }
+            one(Value::Object(out))
+        }
+        ("with_entries", [f]) => {
+            let entries = call("to_entries", &[], input)?;
+            let mapped = call("map", std::slice::from_ref(f), &entries[0])?;
+            call("from_entries", &[], &mapped[0])
+        }
+        ("contains", [other]) => eval(other, input)?
+            .iter()
+            .map(|o| Ok(Value::Bool(contains(input, o))))
+            .collect(),
+        ("tostring", []) => one(match input {
+            Value::String(_) => input.clone(),
+            v => Value::String(v.to_string()),
+        }),
+        ("tonumber", []) => match input {
+            Value::Number(_) => one(input.clone()),
+            Value::String(s) => s
+                .trim()
+                .parse()
+                .map(|n| vec![number(n)])
+                .map_err(|_| format!("Cannot parse '{}' as a number", s)),
+            _ => Err(format!("{} cannot be parsed as a number", type_name(input))),
+        },
+        ("tojson", []) => one(Value::String(input.to_string())),
+        ("fromjson", []) => match input {
+            Value::String(s) => serde_json::from_str(s)
+                .map(|v| vec![v])
+                .map_err(|e| e.to_string()),
+            _ => Err(format!("{} cannot be parsed as JSON", type_name(input))),
+        },
+        ("ascii_downcase" | "ascii_upcase", []) => match input {
+            Value::String(s) if name == "ascii_downcase" => {
+                one(Value::String(s.to_ascii_lowercase()))
+            }
+            Value::String(s) => one(Value::String(s.to_ascii_uppercase())),
+            _ => Err(format!("{} cannot be case-converted", type_name(input))),
+        },
+        ("startswith" | "endswith" | "ltrimstr" | "rtrimstr" | "split" | "join", [arg]) => {
+            eval(arg, input)?
+                .iter()
+                .map(|arg| string_function(name, input, arg))
+                .collect()
+        }
+        ("floor" | "ceil" | "round" | "fabs" | "sqrt", []) => match input {
+            Value::Number(n) => {
+                let n = as_f64(n);
+                one(number(match name {
+                    "floor" => n.floor(),
+                    "ceil" => n.ceil(),
+                    "round" => n.round(),
+                    "fabs" => n.abs(),
+                    _ => n.sqrt(),
+                }))
+            }
+            _ => Err(format!("{} is not a number", type_name(input))),
+        },
+        ("error", []) => Err(match input {
+            Value::String(s) => s.clone(),
+            v => v.to_string(),
+        }),
+        _ => Err(format!("{}/{} is not defined", name, args.len())),
+    }
+}
+
+fn string_function(name: &str, input: &Value, arg: &Value) -> Result<Value, String> {
+    match (name, input, arg) {
+        ("startswith", Value::String(s), Value::String(a)) => {
+            Ok(Value::Bool(s.starts_with(a.as_str())))
+        }
+        ("endswith", Value::String(s), Value::String(a)) => {
+            Ok(Value::Bool(s.ends_with(a.as_str())))
+        }
+        ("ltrimstr", Value::String(s), Value::String(a)) => Ok(Value::String(
+            s.strip_prefix(a.as_str()).unwrap_or(s).to_string(),
+        )),
+        ("rtrimstr", Value::String(s), Value::String(a)) => Ok(Value::String(
+            s.strip_suffix(a.as_str()).unwrap_or(s).to_string(),
+        )),
+        ("ltrimstr" | "rtrimstr", v, _) => Ok(v.clone()),
+        ("split", Value::String(_), Value::String(_)) => binary(BinOp::Div, input, arg),
+        ("join", Value::Array(items), Value::String(sep)) => {
+            let parts: Result<Vec<String>, String> = items
+                .iter()
+                .map(|v| match v {
+                    Value::Null => Ok(String::new()),
+                    Value::String(s) => Ok(s.clone()),
+                    Value::Number(_) | Value::Bool(_) => Ok(v.to_string()),
+                    _ => Err(format!("Cannot join {}", type_name(v))),
+                })
+                .collect();
+            Ok(Value::String(parts?.join(sep)))
+        }
+        _ => Err(format!(
+            "{} cannot be applied to {} and {}",
+            name,
+            type_name(input),
+            type_name(arg)
+        )),
+    }
+}
+
+fn select_if(input: &Value, condition: bool) -> Results {
+    Ok(if condition {
+        vec![input.clone()]
+    } else {
+        vec![]
+    })
+}
+
+fn array(input: &Value, name: &str) -> Results {
+    match input {
+        Value::Array(a) => Ok(a.clone()),
+        _ => Err(format!(
+            "{} requires an array, not {}",
+            name,
+            type_name(input)
+        )),
+    }
+}
+
+/// Pairs each element of an array with the first value of `f` applied to it.
+fn keyed(input: &Value, f: &Expr, name: &str) -> Result<Vec<(Value, Value)>, String> {
+    array(input, name)?
+        .into_iter()
+        .map(|v| {
+            let key = Value::Array(eval(f, &v)?);
+            Ok((key, v))
+        })
+        .collect()
+}
+
+fn flatten(items: &[Value], depth: usize) -> Vec<Value> {
+    items
+        .iter()
+        .flat_map(|v| match v {
+            Value::Array(inner) if depth > 0 => flatten(inner, depth - 1),
+            v => vec![v.clone()],
+        })
+        .collect()
+}
+
+fn contains(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::String(a), Value::String(b)) => a.contains(b.as_str()),
+        (Value::Array(a), Value::Array(b)) => b.iter().all(|b| a.iter().any(|a| contains(a, b))),
+        (Value::Object(a), Value::Object(b)) => b
+            .iter()
+            .all(|(k, b)| a.get(k).is_some_and(|a| contains(a, b))),
+        _ => a == b,
+    }
+}
+
+pub fn is_truthy(value: &Value) -> bool {
+    !matches!(value, Value::Null | Value::Bool(false))
+}
+
+pub fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn as_f64(n: &Number) -> f64 {
+    n.as_f64().unwrap_or(0.0)
+}
+
+/// The numbers from `from` up to but excluding `to`, by steps of 1.
+fn range(from: f64, to: f64) -> Results {
+    let count = (to - from).ceil().max(0.0);
+    if count > MAX_RANGE {
+        return Err(format!("range of {} values is too long", count));
+    }
+    Ok((0..count as usize)
+        .map(|i| number(from + i as f64))
+        .collect())
+}
+
+/// Builds a JSON number, keeping integral values as integers.
+pub fn number(n: f64) -> Value {
+    if n.fract() == 0.0 && n.abs() < i64::MAX as f64 {
+        Value::from(n as i64)
+    } else {
+        Number::from_f64(n).map_or(Value::Null, Value::Number)
+    }
+}
+
+/// Orders values the way jq does: null < false < true < numbers < strings < arrays < objects.
+pub fn compare(a: &Value, b: &Value) -> Ordering {
+    fn rank(v: &Value) -> u8 {
+        match v {
+            Value::Null => 0,
+            Value::Bool(false) => 1,
+            Value::Bool(true) => 2,
+            Value::Number(_) => 3,
+            Value::String(_) => 4,
+            Value::Array(_) => 5,
+            Value::Object(_) => 6,
+        }
+    }
+
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => as_f64(a).total_cmp(&as_f64(b)),
+        (Value::String(a), Value::String(b)) => a.cmp(b),
+        (Value::Array(a), Value::Array(b)) => a
+            .iter()
+            .zip(b.iter())
+            .map(|(a, b)| compare(a, b))
+            .find(|o| o.is_ne())
+            .unwrap_or(a.len().cmp(&b.len())),
+        (Value::Object(a), Value::Object(b)) => {
+            let mut keys_a: Vec<&String> = a.keys().collect();
+            let mut keys_b: Vec<&String> = b.keys().collect();
+            keys_a.sort();
+            keys_b.sort();
+            keys_a.cmp(&keys_b).then_with(|| {
+                keys_a
+                    .iter()
+                    .map(|k| compare(&a[*k], &b[*k]))
+                    .find(|o| o.is_ne())
+                    .unwrap_or(Ordering::Equal)
+            })
+        }
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
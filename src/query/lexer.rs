@@ -0,0 +1,142 @@
+#[derive(Debug, Clone, PartialEq)]
+pub enum Lexeme {
+    Dot,
+    DotDot,
+    Field(String),
+    Ident(String),
+    Str(String),
+    Num(f64),
+    Punct(&'static str),
+}
+
+const PUNCTS: &[&str] = &[
+    "==", "!=", "<=", ">=", "//", "|", ",", "(", ")", "[", "]", "{", "}", ":", ";", "?", "<", ">",
+    "+", "-", "*", "/", "%",
+];
+
+/// Splits a jq expression into lexemes.
+pub fn tokenize(input: &str) -> Result<Vec<Lexeme>, String> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut lexemes = vec![];
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        if c == '.' {
+            if chars.get(i + 1) == Some(&'.') {
+                lexemes.push(Lexeme::DotDot);
+                i += 2;
+            } else if chars.get(i + 1).is_some_and(|&c| is_ident_start(c)) {
+                let end = ident_end(&chars, i + 1);
+                lexemes.push(Lexeme::Field(chars[i + 1..end].iter().collect()));
+                i = end;
+            } else {
+                lexemes.push(Lexeme::Dot);
+                i += 1;
+            }
+            continue;
+        }
+
+        if c == '"' {
+            let (s, end) = read_string(&chars, i)?;
+            lexemes.push(Lexeme::Str(s));
+            i = end;
+            continue;
+        }
+
+        if c.is_ascii_digit() {
+            let mut end = i;
+            while end < chars.len()
+                && (chars[end].is_ascii_digit()
+                    || chars[end] == '.'
+                    || matches!(chars[end], 'e' | 'E')
+                    || (matches!(chars[end], '+' | '-') && matches!(chars[end - 1], 'e' | 'E')))
+            {
+                end += 1;
+            }
+            let text: String = chars[i..end].iter().collect();
+            let n = text
+                .parse()
+                .map_err(|_| format!("Invalid number '{}'", text))?;
+            lexemes.push(Lexeme::Num(n));
+            i = end;
+            continue;
+        }
+
+        if is_ident_start(c) {
+            let end = ident_end(&chars, i);
+            lexemes.push(Lexeme::Ident(chars[i..end].iter().collect()));
+            i = end;
+            continue;
+        }
+
+        match PUNCTS.iter().find(|p| {
+            p.chars()
+                .enumerate()
+                .all(|(j, pc)| chars.get(i + j) == Some(&pc))
+        }) {
+            Some(p) => {
+                lexemes.push(Lexeme::Punct(p));
+                i += p.chars().count();
+            }
+            None => return Err(format!("Unexpected '{}'", c)),
+        }
+    }
+
+    Ok(lexemes)
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_'
+}
+
+fn ident_end(chars: &[char], start: usize) -> usize {
+    let mut end = start;
+    while end < chars.len() && (chars[end].is_alphanumeric() || chars[end] == '_') {
+        end += 1;
+    }
+    end
+}
+
+/// Reads a double-quoted string with JSON escapes, returning the position past its end.
+fn read_string(chars: &[char], start: usize) -> Result<(String, usize), String> {
+    let mut s = String::new();
+    let mut i = start + 1;
+
+    while i < chars.len() {
+        match chars[i] {
+            '"' => return Ok((s, i + 1)),
+            '\\' => {
+                let escaped = chars.get(i + 1).ok_or("Unterminated string")?;
+                match escaped {
+                    'n' => s.push('\n'),
+                    't' => s.push('\t'),
+                    'r' => s.push('\r'),
+                    'b' => s.push('\u{8}'),
+                    'f' => s.push('\u{c}'),
+                    'u' => {
+                        let hex: String = chars.iter().skip(i + 2).take(4).collect();
+                        let code = u32::from_str_radix(&hex, 16)
+                            .map_err(|_| format!("Invalid escape \\u{}", hex))?;
+                        s.push(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+                        i += 4;
+                    }
+                    c => s.push(*c),
+                }
+                i += 2;
+            }
+            c => {
+                s.push(c);
+                i += 1;
+            }
+        }
+    }
+
+    Err("Unterminated string".to_string())
+}
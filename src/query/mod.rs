@@ -0,0 +1,108 @@
+use serde_json::Value;
+
+mod eval;
+mod lexer;
+mod parser;
+
+/// Runs a jq expression against a value and returns every value it produces.
+///
+/// Supports a subset of jq: paths, iteration, pipes, comparisons, arithmetic,
+/// object/array construction, conditionals and the common builtins
+/// (`select`, `map`, `keys`, `length`, `sort_by`, ...).
+pub fn run_query(query: &str, input: &Value) -> Result<Vec<Value>, String> {
+    let expr = parser::parse(query)?;
+
+    eval::eval(&expr, input)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn q(query: &str, input: Value) -> Vec<Value> {
+        run_query(query, &input).unwrap()
+    }
+
+    #[test]
+    fn paths() {
+        let input = json!({"a": {"b": [1, 2, 3]}, "c d": true});
+        assert_eq!(q(".", input.clone()), vec![input.clone()]);
+        assert_eq!(q(".a.b[1]", input.clone()), vec![json!(2)]);
+        assert_eq!(q(".a.b[-1]", input.clone()), vec![json!(3)]);
+        assert_eq!(q(".a[\"b\"][0]", input.clone()), vec![json!(1)]);
+        assert_eq!(q(".\"c d\"", input.clone()), vec![json!(true)]);
+        assert_eq!(q(".a.b[1:]", input.clone()), vec![json!([2, 3])]);
+        assert_eq!(q(".missing.x", input.clone()), vec![json!(null)]);
+        assert!(run_query(".a.b.c", &input).is_err());
+        assert_eq!(q(".a.b.c?", input), Vec::<Value>::new());
+    }
+
+    #[test]
+    fn iterate_and_pipe() {
+        let input = json!({"items": [{"n": 1}, {"n": 2}]});
+        assert_eq!(q(".items[].n", input.clone()), vec![json!(1), json!(2)]);
+        assert_eq!(
+            q(".items[] | .n * 10", input.clone()),
+            vec![json!(10), json!(20)]
+        );
+        assert_eq!(q("[.items[].n]", input), vec![json!([1, 2])]);
+    }
+
+    #[test]
+    fn select_and_map() {
+        let input = json!([{"price": 5}, {"price": 15}, {"price": 25}]);
+        assert_eq!(
+            q(".[] | select(.price > 10) | .price", input.clone()),
+            vec![json!(15), json!(25)]
+        );
+        assert_eq!(q("map(.price)", input.clone()), vec![json!([5, 15, 25])]);
+        assert_eq!(q("map(.price) | add", input.clone()), vec![json!(45)]);
+        assert_eq!(
+            q("map(select(.price < 10 or .price > 20)) | length", input),
+            vec![json!(2)]
+        );
+    }
+
+    #[test]
+    fn builtins() {
+        assert_eq!(q("keys", json!({"b": 1, "a": 2})), vec![json!(["a", "b"])]);
+        assert_eq!(q("length", json!("héllo")), vec![json!(5)]);
+        assert_eq!(q("length", json!({"a": 1})), vec![json!(1)]);
+        assert_eq!(
+            q("sort_by(.n) | map(.n)", json!([{"n": 2}, {"n": 1}])),
+            vec![json!([1, 2])]
+        );
+        assert_eq!(
+            q("to_entries | map(.key)", json!({"x": 1, "y": 2})),
+            vec![json!(["x", "y"])]
+        );
+        assert_eq!(q(".a // \"default\"", json!({})), vec![json!("default")]);
+        assert!(run_query("nope", &json!(null)).is_err());
+        assert_eq!(q("[range(3)]", json!(null)), vec![json!([0, 1, 2])]);
+        assert_eq!(q("[range(1; 2.5)]", json!(null)), vec![json!([1, 2])]);
+        assert!(run_query("range(1e12)", &json!(null)).is_err());
+        assert!(run_query("range(1e17; 1e18)", &json!(null)).is_err());
+        assert_eq!(q(". % 3", json!(7)), vec![json!(1)]);
+        assert!(run_query(". % 0", &json!(7)).is_err());
+        assert!(run_query(". % -1", &json!(i64::MIN)).is_err());
+    }
+
+    #[test]
+    fn construction() {
+        let input = json!({"name": "x", "tags": ["a", "b"]});
+        assert_eq!(
+            q("{name, first: .tags[0]}", input.clone()),
+            vec![json!({"name": "x", "first": "a"})]
+        );
+        assert_eq!(
+            q("{tag: .tags[]}", input.clone()),
+            vec![json!({"tag": "a"}), json!({"tag": "b"})]
+        );
+        assert_eq!(
+            q("if .name == \"x\" then 1 else 2 end", input),
+            vec![json!(1)]
+        );
+    }
+}
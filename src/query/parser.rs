@@ -0,0 +1,405 @@
+use serde_json::Value;
+
+use super::lexer::{tokenize, Lexeme};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone)]
+pub enum Expr {
+    Identity,
+    Recurse,
+    Literal(Value),
+    Index(Box<Expr>, Box<Expr>),
+    Slice(Box<Expr>, Option<Box<Expr>>, Option<Box<Expr>>),
+    Iterate(Box<Expr>),
+    Optional(Box<Expr>),
+    Pipe(Box<Expr>, Box<Expr>),
+    Comma(Box<Expr>, Box<Expr>),
+    Alternative(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+    Neg(Box<Expr>),
+    Array(Option<Box<Expr>>),
+    Object(Vec<(Expr, Expr)>),
+    If(Box<Expr>, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+/// Parses a jq expression.
+pub fn parse(input: &str) -> Result<Expr, String> {
+    let lexemes = tokenize(input)?;
+    if lexemes.is_empty() {
+        return Ok(Expr::Identity);
+    }
+
+    let mut parser = Parser { lexemes, pos: 0 };
+    let expr = parser.pipe()?;
+
+    match parser.peek() {
+        None => Ok(expr),
+        Some(l) => Err(format!("Unexpected {}", describe(l))),
+    }
+}
+
+struct Parser {
+    lexemes: Vec<Lexeme>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Lexeme> {
+        self.lexemes.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Lexeme> {
+        let lexeme = self.lexemes.get(self.pos).cloned();
+        self.pos += 1;
+        lexeme
+    }
+
+    fn eat(&mut self, punct: &'static str) -> bool {
+        if self.peek() == Some(&Lexeme::Punct(punct)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if matches!(self.peek(), Some(Lexeme::Ident(k)) if k == keyword) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, punct: &'static str) -> Result<(), String> {
+        if self.eat(punct) {
+            Ok(())
+        } else {
+            Err(match self.peek() {
+                Some(l) => format!("Expected '{}' but found {}", punct, describe(l)),
+                None => format!("Expected '{}'", punct),
+            })
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), String> {
+        if self.eat_keyword(keyword) {
+            Ok(())
+        } else {
+            Err(format!("Expected '{}'", keyword))
+        }
+    }
+
+    /// `a | b`, the loosest binding operator
+    fn pipe(&mut self) -> Result<Expr, String> {
+        let lhs = self.comma()?;
+        if self.eat("|") {
+            Ok(Expr::Pipe(Box::new(lhs), Box::new(self.pipe()?)))
+        } else {
+            Ok(lhs)
+        }
+    }
+
+    fn comma(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.alternative()?;
+        while self.eat(",") {
+            lhs = Expr::Comma(Box::new(lhs), Box::new(self.alternative()?));
+        }
+        Ok(lhs)
+    }
+
+    fn alternative(&mut self) -> Result<Expr, String> {
+        let lhs = self.or()?;
+        if self.eat("//") {
+            Ok(Expr::Alternative(
+                Box::new(lhs),
+                Box::new(self.alternative()?),
+            ))
+        } else {
+            Ok(lhs)
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.and()?;
+        while self.eat_keyword("or") {
+            lhs = Expr::Or(Box::new(lhs), Box::new(self.and()?));
+        }
+        Ok(lhs)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.comparison()?;
+        while self.eat_keyword("and") {
+            lhs = Expr::And(Box::new(lhs), Box::new(self.comparison()?));
+        }
+        Ok(lhs)
+    }
+
+    fn comparison(&mut self) -> Result<Expr, String> {
+        let lhs = self.additive()?;
+        let op = match self.peek() {
+            Some(Lexeme::Punct("==")) => BinOp::Eq,
+            Some(Lexeme::Punct("!=")) => BinOp::Ne,
+            Some(Lexeme::Punct("<")) => BinOp::Lt,
+            Some(Lexeme::Punct("<=")) => BinOp::Le,
+            Some(Lexeme::Punct(">")) => BinOp::Gt,
+            Some(Lexeme::Punct(">=")) => BinOp::Ge,
+            _ => return Ok(lhs),
+        };
+        self.pos += 1;
+        Ok(Expr::Binary(op, Box::new(lhs), Box::new(self.additive()?)))
+    }
+
+    fn additive(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.multiplicative()?;
+        loop {
+            let op = match self.peek() {
+                Some(Lexeme::Punct("+")) => BinOp::Add,
+                Some(Lexeme::Punct("-")) => BinOp::Sub,
+                _ => return Ok(lhs),
+            };
+            self.pos += 1;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.multiplicative()?));
+        }
+    }
+
+    fn multiplicative(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.unary()?;
+        loop {
+            let op = match self.peek() {
+                Some(Lexeme::Punct("*")) => BinOp::Mul,
+                Some(Lexeme::Punct("/")) => BinOp::Div,
+                Some(Lexeme::Punct("%")) => BinOp::Rem,
+                _ => return Ok(lhs),
+            };
+            self.pos += 1;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat("-") {
+            Ok(Expr::Neg(Box::new(self.postfix()?)))
+        } else {
+            self.postfix()
+        }
+    }
+
+    /// A term followed by any number of `.foo`, `[...]` and `?` suffixes
+    fn postfix(&mut self) -> Result<Expr, String> {
+        let mut expr = self.primary()?;
+
+        loop {
+            match self.peek() {
+                Some(Lexeme::Field(name)) => {
+                    let name = name.clone();
+                    self.pos += 1;
+                    expr = index(expr, Expr::Literal(Value::String(name)));
+                }
+                Some(Lexeme::Dot)
+                    if matches!(
+                        self.lexemes.get(self.pos + 1),
+                        Some(Lexeme::Str(_) | Lexeme::Punct("["))
+                    ) =>
+                {
+                    self.pos += 1;
+                    if let Some(Lexeme::Str(s)) = self.peek().cloned() {
+                        self.pos += 1;
+                        expr = index(expr, Expr::Literal(Value::String(s)));
+                    }
+                }
+                Some(Lexeme::Punct("[")) => {
+                    self.pos += 1;
+                    expr = self.bracket_suffix(expr)?;
+                }
+                Some(Lexeme::Punct("?")) => {
+                    self.pos += 1;
+                    expr = Expr::Optional(Box::new(expr));
+                }
+                _ => return Ok(expr),
+            }
+        }
+    }
+
+    /// Parses what follows `[` after a term: `[]`, `[i]` or `[from:to]`
+    fn bracket_suffix(&mut self, target: Expr) -> Result<Expr, String> {
+        if self.eat("]") {
+            return Ok(Expr::Iterate(Box::new(target)));
+        }
+
+        let from = if self.peek() == Some(&Lexeme::Punct(":")) {
+            None
+        } else {
+            Some(Box::new(self.pipe()?))
+        };
+
+        if self.eat(":") {
+            let to = if self.peek() == Some(&Lexeme::Punct("]")) {
+                None
+            } else {
+                Some(Box::new(self.pipe()?))
+            };
+            self.expect("]")?;
+            return Ok(Expr::Slice(Box::new(target), from, to));
+        }
+
+        self.expect("]")?;
+        match from {
+            Some(from) => Ok(Expr::Index(Box::new(target), from)),
+            None => Err("Empty index".to_string()),
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        let lexeme = self.next().ok_or("Unexpected end of query")?;
+
+        match lexeme {
+            Lexeme::Dot => match self.peek().cloned() {
+                Some(Lexeme::Str(s)) => {
+                    self.pos += 1;
+                    Ok(index(Expr::Identity, Expr::Literal(Value::String(s))))
+                }
+                _ => Ok(Expr::Identity),
+            },
+            Lexeme::DotDot => Ok(Expr::Recurse),
+            Lexeme::Field(name) => Ok(index(Expr::Identity, Expr::Literal(Value::String(name)))),
+            Lexeme::Str(s) => Ok(Expr::Literal(Value::String(s))),
+            Lexeme::Num(n) => Ok(Expr::Literal(super::eval::number(n))),
+            Lexeme::Punct("(") => {
+                let expr = self.pipe()?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            Lexeme::Punct("[") => {
+                if self.eat("]") {
+                    return Ok(Expr::Array(None));
+                }
+                let expr = self.pipe()?;
+                self.expect("]")?;
+                Ok(Expr::Array(Some(Box::new(expr))))
+            }
+            Lexeme::Punct("{") => self.object(),
+            Lexeme::Ident(name) => match name.as_str() {
+                "true" => Ok(Expr::Literal(Value::Bool(true))),
+                "false" => Ok(Expr::Literal(Value::Bool(false))),
+                "null" => Ok(Expr::Literal(Value::Null)),
+                "if" => self.conditional(),
+                _ => {
+                    let mut args = vec![];
+                    if self.eat("(") {
+                        loop {
+                            args.push(self.pipe()?);
+                            if !self.eat(";") {
+                                break;
+                            }
+                        }
+                        self.expect(")")?;
+                    }
+                    Ok(Expr::Call(name, args))
+                }
+            },
+            l => Err(format!("Unexpected {}", describe(&l))),
+        }
+    }
+
+    /// Parses the rest of `if c then a (elif c then a)* (else b)? end`
+    fn conditional(&mut self) -> Result<Expr, String> {
+        let condition = self.pipe()?;
+        self.expect_keyword("then")?;
+        let then = self.pipe()?;
+
+        let otherwise = if self.eat_keyword("elif") {
+            return Ok(Expr::If(
+                Box::new(condition),
+                Box::new(then),
+                Box::new(self.conditional()?),
+            ));
+        } else if self.eat_keyword("else") {
+            self.pipe()?
+        } else {
+            Expr::Identity
+        };
+        self.expect_keyword("end")?;
+
+        Ok(Expr::If(
+            Box::new(condition),
+            Box::new(then),
+            Box::new(otherwise),
+        ))
+    }
+
+    /// Parses the rest of `{a, "b": .c, (.d): .e}`
+    fn object(&mut self) -> Result<Expr, String> {
+        let mut entries = vec![];
+
+        if self.eat("}") {
+            return Ok(Expr::Object(entries));
+        }
+
+        loop {
+            let (key, shorthand) = match self.next() {
+                Some(Lexeme::Ident(k) | Lexeme::Str(k)) => {
+                    let key = Expr::Literal(Value::String(k.clone()));
+                    (
+                        key,
+                        Some(index(Expr::Identity, Expr::Literal(Value::String(k)))),
+                    )
+                }
+                Some(Lexeme::Punct("(")) => {
+                    let key = self.pipe()?;
+                    self.expect(")")?;
+                    (key, None)
+                }
+                Some(l) => return Err(format!("Unexpected {} in object", describe(&l))),
+                None => return Err("Unterminated object".to_string()),
+            };
+
+            let value = if self.eat(":") {
+                self.alternative()?
+            } else {
+                shorthand.ok_or("Expected ':' after computed key")?
+            };
+            entries.push((key, value));
+
+            if self.eat("}") {
+                return Ok(Expr::Object(entries));
+            }
+            self.expect(",")?;
+        }
+    }
+}
+
+fn index(target: Expr, key: Expr) -> Expr {
+    Expr::Index(Box::new(target), Box::new(key))
+}
+
+fn describe(lexeme: &Lexeme) -> String {
+    match lexeme {
+        Lexeme::Dot => "'.'".to_string(),
+        Lexeme::DotDot => "'..'".to_string(),
+        Lexeme::Field(f) => format!("'.{}'", f),
+        Lexeme::Ident(i) => format!("'{}'", i),
+        Lexeme::Str(s) => format!("{:?}", s),
+        Lexeme::Num(n) => format!("{}", n),
+        Lexeme::Punct(p) => format!("'{}'", p),
+    }
+}
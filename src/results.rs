@@ -0,0 +1,168 @@
+use std::collections::HashSet;
+use std::io::Write;
+
+use crossterm::{
+    cursor, queue,
+    style::{Print, PrintStyledContent},
+    terminal,
+};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::json::{Json, Pointer, Token};
+use crate::search::SearchResults;
+use crate::style::{
+    styled, truncate, StyleClass, STYLE_FINDER_SELECTED, STYLE_SEARCH_MATCH,
+    STYLE_SEARCH_MATCH_CURRENT, STYLE_SEARCH_PROMPT, STYLE_SEARCH_STATUS, STYLE_SELECTION_BAR,
+};
+
+/// Maximum number of rows taken up by the results panel (title included).
+pub const RESULTS_HEIGHT: usize = 12;
+
+/// Width of the path column, as a fraction of the panel width.
+const PATH_WIDTH_RATIO: usize = 3;
+
+/// Characters shown before a match when the snippet can't start at the start of its line.
+const SNIPPET_CONTEXT: usize = 12;
+
+/// The list of search matches, opened over the footer.
+pub struct ResultsPanel {
+    /// Selection, folds and current match to restore if the panel is cancelled
+    pub origin: (Vec<Token>, HashSet<Vec<Token>>, Option<usize>),
+}
+
+/// Renders the search matches, one per row with its path and line, starting at row `top`.
+pub fn render_results<W: Write>(
+    out: &mut W,
+    json: &Json,
+    results: &SearchResults,
+    top: usize,
+    size: (usize, usize),
+) -> anyhow::Result<()> {
+    let (width, height) = size;
+    let rows = height.saturating_sub(1);
+
+    let status = results.status_text();
+    queue!(
+        out,
+        cursor::MoveTo(0, top as u16),
+        terminal::Clear(terminal::ClearType::CurrentLine),
+        PrintStyledContent(styled(STYLE_SEARCH_PROMPT, "/")),
+        Print(truncate(
+            &results.query,
+            width.saturating_sub(status.len() + 3)
+        )),
+        cursor::MoveTo(width.saturating_sub(status.len() + 1) as u16, top as u16),
+        PrintStyledContent(styled(STYLE_SEARCH_STATUS, &status))
+    )?;
+
+    // Keep the current match in view
+    let selected = results.current_index.unwrap_or(0);
+    let offset = selected.saturating_sub(rows.saturating_sub(1));
+    let path_width = width / PATH_WIDTH_RATIO;
+
+    for row in 0..rows {
+        let y = (top + 1 + row) as u16;
+        queue!(
+            out,
+            cursor::MoveTo(0, y),
+            terminal::Clear(terminal::ClearType::CurrentLine)
+        )?;
+
+        let Some(m) = results.matches.get(offset + row) else {
+            continue;
+        };
+        let Some(line) = json.formatted.get(m.line_number) else {
+            continue;
+        };
+        let is_selected = results.current_index == Some(offset + row);
+
+        if is_selected {
+            queue!(
+                out,
+                PrintStyledContent(styled(STYLE_SELECTION_BAR, "┃")),
+                Print(" ")
+            )?;
+        } else {
+            queue!(out, Print("  "))?;
+        }
+
+        // Path column, truncated from the left so the end of the path stays visible
+        let path = Pointer::period_path(&line.pointer);
+        let path_room = path_width.saturating_sub(1);
+        let path: String = if path.width() > path_room {
+            let mut used = 1;
+            let start = path
+                .char_indices()
+                .rev()
+                .take_while(|(_, c)| {
+                    used += c.width().unwrap_or(0);
+                    used <= path_room
+                })
+                .last()
+                .map_or(path.len(), |(i, _)| i);
+            format!("…{}", &path[start..])
+        } else {
+            format!("{}{}", path, " ".repeat(path_room - path.width()))
+        };
+        let path_style = if is_selected {
+            STYLE_FINDER_SELECTED
+        } else {
+            STYLE_SEARCH_STATUS
+        };
+        queue!(
+            out,
+            PrintStyledContent(styled(path_style, path)),
+            Print(" ")
+        )?;
+
+        // Snippet of the matched line, with the match highlighted
+        let match_style = if is_selected {
+            STYLE_SEARCH_MATCH_CURRENT
+        } else {
+            STYLE_SEARCH_MATCH
+        };
+        let chars: Vec<(char, StyleClass, bool)> = line
+            .elements
+            .iter()
+            .enumerate()
+            .flat_map(|(elem_idx, el)| {
+                el.0.chars().enumerate().map(move |(char_idx, ch)| {
+                    (
+                        ch,
+                        el.1,
+                        elem_idx == m.element_index && m.contains(char_idx),
+                    )
+                })
+            })
+            .collect();
+        let mut col = path_width + 2;
+        // Start a little before the match if it would be cut off otherwise
+        let match_end = chars.iter().rposition(|c| c.2).map_or(0, |i| i + 1);
+        let line_width: usize = chars[..match_end]
+            .iter()
+            .map(|c| c.0.width().unwrap_or(0))
+            .sum();
+        let start = match chars.iter().position(|c| c.2) {
+            Some(i) if col + line_width > width && i > SNIPPET_CONTEXT => {
+                queue!(out, PrintStyledContent(styled(STYLE_SEARCH_STATUS, "…")))?;
+                col += 1;
+                i - SNIPPET_CONTEXT
+            }
+            _ => 0,
+        };
+        for &(ch, class, matched) in &chars[start..] {
+            col += ch.width().unwrap_or(0);
+            if col > width {
+                break;
+            }
+            let styled = if matched {
+                styled(match_style, ch)
+            } else {
+                class.apply(ch)
+            };
+            queue!(out, PrintStyledContent(styled))?;
+        }
+    }
+
+    Ok(())
+}
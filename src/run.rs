@@ -1,17 +1,39 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::Duration;
+
 use arboard::Clipboard;
 use serde_json::{to_string_pretty, Value};
 
-use crate::events::{read_event, Action::*, Direction::*};
-use crate::json::Json;
-use crate::search::{perform_search, SearchResults};
-use crate::ui::{FlashMode, UI};
+use crate::events::{read_event, Action::*, Direction::*, Mode};
+use crate::finder::{Finder, FINDER_HEIGHT};
+use crate::goto::GotoPrompt;
+use crate::history::History;
+use crate::json::{
+    broken_refs, jq_path, parse_path, ref_target, resolve, token_label, Json, PathPattern, Pointer,
+    SortOrder, Token, REF_KEY,
+};
+use crate::prompt::Prompt;
+use crate::query::run_query;
+use crate::results::{ResultsPanel, RESULTS_HEIGHT};
+use crate::schema::{describe, generate, infer_schema, load_schema, Language, Validation};
+use crate::search::{
+    same_node_search, sibling_search, start_search, SearchJob, SearchResults, TextOptions,
+};
+use crate::table::Table;
+use crate::ui::{Body, FlashMode, Panel, View, UI};
 use crate::InputSource;
 
+/// How often partial results are collected while a search scans in the background
+const SCAN_POLL: Duration = Duration::from_millis(30);
+
 /// Starts the main loop responsible for listening to user events and triggering UI updates.
 pub fn event_loop(
     source: &InputSource,
     mut json: Json,
     no_numbers: bool,
+    schema: Option<PathBuf>,
 ) -> anyhow::Result<Option<String>> {
     let mut clipboard = Clipboard::new()?;
 
@@ -20,50 +42,200 @@ pub fn event_loop(
     let mut output: Option<String> = None;
 
     // Search state
-    let mut search_input: Option<String> = None;
+    let mut search_input: Option<Prompt> = None;
     let mut search_results: Option<SearchResults> = None;
     let mut last_search: Option<SearchResults> = None;
+    let mut search_history = History::load("search_history");
+    let mut search_job: Option<SearchJob> = None;
+    let mut search_options = TextOptions::default();
 
-    // Help state
-    let mut help_visible = false;
+    // Array sorting state
+    let mut sort_input: Option<(Prompt, SortOrder)> = None;
 
-    ui.render(
-        source,
-        &json,
-        search_input.as_deref(),
-        search_results.as_ref(),
-        help_visible,
-    )?;
+    // Finder state
+    let mut finder: Option<Finder> = None;
+    let mut results_panel: Option<ResultsPanel> = None;
 
-    loop {
-        let mut needs_redraw = false;
-        let search_mode = search_input.is_some();
+    // Go-to-path state
+    let mut goto: Option<GotoPrompt> = None;
+
+    // Query state; `other_view` holds whichever of the original and derived documents
+    // isn't currently shown. Derived documents are query results or inferred schemas.
+    let mut query_input: Option<Prompt> = None;
+    let mut query: Option<String> = None;
+    let mut query_active = false;
+    let mut other_view: Option<Json> = None;
+    let mut derived_label: Option<String> = None;
+
+    // Type generation state; whether the types are copied rather than output
+    let mut types_input: Option<(Prompt, bool)> = None;
+    let mut types_language = Language::Rust;
+
+    // Table state
+    let mut table: Option<Table> = None;
+
+    // Documents zoomed out of, with the path zoomed into in each
+    let mut zooms: Vec<(Json, Vec<Token>)> = vec![];
+
+    // Schema validation state; errors are always those of the original document
+    let mut schema_input: Option<Prompt> = None;
+    let mut validation: Option<Validation> = None;
+    let mut docs_visible = true;
+
+    // `$ref` state: where references were followed from, and those of the document shown
+    // that point to nothing
+    let ref_key = Token::Key(REF_KEY.to_string());
+    let mut jumps: Vec<Vec<Token>> = vec![];
+    let mut broken: Vec<Vec<Token>> = broken_refs(&json.value);
+    if let Some(path) = schema {
+        match load_schema(&path) {
+            Ok(schema) => {
+                let v = Validation::new(path, schema, &json);
+                ui.show_message(v.summary());
+                validation = Some(v);
+            }
+            Err(e) => ui.show_message(e),
+        }
+    }
+
+    let mut needs_redraw = true;
 
+    loop {
         if ui.clear_flash_if_expired() {
             needs_redraw = true;
         }
 
-        let timeout = ui.flash_remaining();
+        // Collect the matches found by the background scan so far
+        if let Some(job) = search_job.take() {
+            let (found, done) = job.poll();
+            let had_matches = search_results
+                .as_ref()
+                .is_some_and(|r| !r.matches.is_empty());
+            for results in [search_results.as_mut(), last_search.as_mut()]
+                .into_iter()
+                .flatten()
+            {
+                needs_redraw |= results.receive(job.id, &found, done);
+            }
 
-        let action = match read_event(search_mode, help_visible, timeout)? {
-            Some(action) => action,
-            None => {
-                if ui.clear_flash_if_expired() {
-                    ui.render(
-                        source,
-                        &json,
-                        search_input.as_deref(),
-                        search_results.as_ref(),
-                        help_visible,
-                    )?;
-                }
-                continue;
+            // While typing, show the first match as soon as it's found
+            if !had_matches && search_input.is_some() {
+                if let Some(m) = search_results.as_ref().and_then(|r| r.matches.first()) {
+                    ensure_match_visible(
+                        &mut ui,
+                        &mut json,
+                        m.line_number,
+                        m.element_index,
+                        m.char_offset,
+                        m.len,
+                    );
+                }
             }
+
+            let waiting = [search_results.as_ref(), last_search.as_ref()]
+                .into_iter()
+                .flatten()
+                .any(|r| r.scan == Some(job.id));
+            if waiting {
+                search_job = Some(job);
+            }
+        }
+
+        if needs_redraw {
+            let prompt = if let Some(ref input) = search_input {
+                Some(("/", input))
+            } else if let Some(ref g) = goto {
+                Some((":", &g.input))
+            } else if let Some((ref input, order)) = sort_input {
+                Some((
+                    match order {
+                        SortOrder::Ascending => "sort by ↑ ",
+                        SortOrder::Descending => "sort by ↓ ",
+                    },
+                    input,
+                ))
+            } else if let Some(ref input) = schema_input {
+                Some(("schema: ", input))
+            } else if let Some((ref input, _)) = types_input {
+                Some(("types (rust, ts, py, go): ", input))
+            } else {
+                query_input.as_ref().map(|input| ("|", input))
+            };
+
+            ui.marks = error_marks(validation.as_ref(), &zooms, query_active);
+            ui.broken_refs = within_zoom(&broken, &zooms);
+            ui.docs = match validation {
+                // Derived documents aren't described by the schema
+                Some(_) if docs_visible && query_active => Some(None),
+                Some(ref mut v) if docs_visible => {
+                    let root = zooms.first().map_or(&json, |(root, _)| root);
+                    let path = [zoom_path(&zooms), json.tokens()].concat();
+                    Some(v.annotation(root, &path))
+                }
+                _ => None,
+            };
+
+            ui.render(
+                source,
+                &json,
+                table.as_ref().map_or(Body::Tree, Body::Table),
+                prompt,
+                search_results.as_ref(),
+                match (&finder, &results_panel) {
+                    (Some(f), _) => Some(Panel::Finder(f)),
+                    (None, Some(_)) => Some(Panel::Results),
+                    (None, None) => None,
+                },
+            )?;
+            needs_redraw = false;
+        }
+
+        let mode = if ui.help_visible {
+            Mode::Help
+        } else if finder.is_some() {
+            Mode::Finder
+        } else if results_panel.is_some() {
+            Mode::Results
+        } else if search_input.is_some() {
+            Mode::Search
+        } else if goto.is_some() {
+            Mode::Goto
+        } else if sort_input.is_some() {
+            Mode::SortBy
+        } else if query_input.is_some() {
+            Mode::Query
+        } else if schema_input.is_some() {
+            Mode::Schema
+        } else if types_input.is_some() {
+            Mode::Types
+        } else if table.is_some() {
+            Mode::Table
+        } else {
+            Mode::Normal
         };
 
+        let timeout = match search_job {
+            Some(_) => Some(ui.flash_remaining().map_or(SCAN_POLL, |t| t.min(SCAN_POLL))),
+            None => ui.flash_remaining(),
+        };
+
+        let action = match read_event(mode, timeout)? {
+            Some(action) => action,
+            None => continue,
+        };
+
+        // Messages only last until the next key press
+        let message_cleared = !matches!(action, Resize(..) | Ignore) && ui.clear_message();
+        if message_cleared && mode == Mode::Normal {
+            ui.footer_height = if search_results.is_some() { 1 } else { 0 };
+        }
+
         match action {
             Resize(w, h) => {
                 needs_redraw = ui.resize((w, h));
+                if let Some(t) = table.as_mut() {
+                    t.scroll_to_selection(ui.body_size());
+                }
             }
 
             Quit => {
@@ -130,66 +302,162 @@ pub fn event_loop(
             ToggleFoldAll => {
                 needs_redraw = json.toggle_fold_all();
             }
-
-            Sort => {}
-            SortReverse => {}
-
-            Search => {
-                search_input = Some(String::new());
-                ui.footer_height = 1;
-                needs_redraw = true;
-            }
-
-            SearchInput(c) => {
-                if let Some(ref mut input) = search_input {
-                    input.push(c);
-                    search_results = Some(perform_search(&json.formatted, input));
-                    // Auto-scroll to first match
-                    if let Some(ref results) = search_results {
-                        let match_len = results.query.chars().count();
-                        if let Some(m) = results.matches.first() {
+            UnfoldMatches => {
+                if search_results.is_none() {
+                    search_results = last_search
+                        .clone()
+                        .map(|r| resume_scan(&json, r, &mut search_job));
+                }
+                match search_results {
+                    Some(ref mut results) if !results.matches.is_empty() => {
+                        json.fold_all_except(&results.pointers(&json.formatted));
+                        ui.footer_height = 1;
+                        if results.current_index.is_none() {
+                            results.next();
+                        }
+                        if let Some(m) = results.current() {
                             ensure_match_visible(
                                 &mut ui,
                                 &mut json,
                                 m.line_number,
                                 m.element_index,
                                 m.char_offset,
-                                match_len,
+                                m.len,
                             );
                         }
                     }
+                    _ => ui.show_message("No search matches"),
+                }
+                needs_redraw = true;
+            }
+
+            Sort | SortReverse | SortRecursive | SortReverseRecursive => {
+                let order = match action {
+                    SortReverse | SortReverseRecursive => SortOrder::Descending,
+                    _ => SortOrder::Ascending,
+                };
+                let recursive = matches!(action, SortRecursive | SortReverseRecursive);
+
+                // Arrays are sorted by a field of their elements, which is prompted for
+                if !recursive && json.value().is_some_and(|v| v.is_array()) {
+                    let field = json.array_order().map_or("", |o| o.field.as_str());
+                    sort_input = Some((Prompt::with_text(field), order));
+                    ui.footer_height = 1;
+                } else {
+                    let sorted = keep_matches(
+                        &mut json,
+                        &mut search_results,
+                        &mut last_search,
+                        &mut search_job,
+                        |json| json.sort_keys(order, recursive),
+                    );
+                    ui.show_message(match (sorted, order) {
+                        (None, _) => "Nothing to sort",
+                        (Some(false), _) => "Restored original key order",
+                        (Some(true), SortOrder::Ascending) => "Sorted keys",
+                        (Some(true), SortOrder::Descending) => "Sorted keys in reverse",
+                    });
+                    ui.ensure_visible(json.visible_bounds());
+                }
+                needs_redraw = true;
+            }
+            SortByEdit(edit) => {
+                if let Some((ref mut input, _)) = sort_input {
+                    input.edit(edit);
+                    needs_redraw = true;
+                }
+            }
+            SortByConfirm => {
+                if let Some((input, order)) = sort_input.take() {
+                    let field = input.text();
+                    ui.footer_height = if search_results.is_some() { 1 } else { 0 };
+                    let sorted = keep_matches(
+                        &mut json,
+                        &mut search_results,
+                        &mut last_search,
+                        &mut search_job,
+                        |json| json.sort_array(&field, order),
+                    );
+                    match sorted {
+                        Ok(true) => ui.show_message(format!("Sorted by {}", field.trim())),
+                        Ok(false) => ui.show_message("Restored original order"),
+                        Err(e) => ui.show_message(e),
+                    }
+                    ui.ensure_visible(json.visible_bounds());
                     needs_redraw = true;
                 }
             }
-            SearchBackspace => {
+            SortByCancel => {
+                sort_input = None;
+                ui.footer_height = if search_results.is_some() { 1 } else { 0 };
+                needs_redraw = true;
+            }
+
+            Search => {
+                search_history.reset();
+                search_input = Some(Prompt::new());
+                ui.footer_height = 1;
+                needs_redraw = true;
+            }
+
+            SearchEdit(edit) => {
                 if let Some(ref mut input) = search_input {
-                    input.pop();
-                    if input.is_empty() {
-                        search_results = None;
-                    } else {
-                        search_results = Some(perform_search(&json.formatted, input));
-                        if let Some(ref results) = search_results {
-                            let match_len = results.query.chars().count();
-                            if let Some(m) = results.matches.first() {
-                                ensure_match_visible(
-                                    &mut ui,
-                                    &mut json,
-                                    m.line_number,
-                                    m.element_index,
-                                    m.char_offset,
-                                    match_len,
-                                );
-                            }
-                        }
+                    if input.edit(edit) {
+                        search_results = preview_search(
+                            &mut ui,
+                            &mut json,
+                            &input.text(),
+                            search_options,
+                            search_results.as_ref(),
+                            &mut search_job,
+                        );
                     }
                     needs_redraw = true;
                 }
             }
+            SearchToggleDiacritics => {
+                if let Some(ref input) = search_input {
+                    search_options.ignore_diacritics = !search_options.ignore_diacritics;
+                    search_results = preview_search(
+                        &mut ui,
+                        &mut json,
+                        &input.text(),
+                        search_options,
+                        None,
+                        &mut search_job,
+                    );
+                    ui.show_message(if search_options.ignore_diacritics {
+                        "Ignoring diacritics"
+                    } else {
+                        "Matching diacritics"
+                    });
+                    needs_redraw = true;
+                }
+            }
+            SearchHistory(direction) => {
+                if let Some(ref mut input) = search_input {
+                    let entry = match direction {
+                        Up => search_history.prev(&input.text()),
+                        _ => search_history.next(),
+                    };
+                    if let Some(entry) = entry {
+                        input.set_text(&entry);
+                        search_results = preview_search(
+                            &mut ui,
+                            &mut json,
+                            &entry,
+                            search_options,
+                            None,
+                            &mut search_job,
+                        );
+                        needs_redraw = true;
+                    }
+                }
+            }
             SearchConfirm => {
                 if let Some(ref mut results) = search_results {
                     if !results.matches.is_empty() {
                         results.current_index = Some(0);
-                        let match_len = results.query.chars().count();
                         if let Some(m) = results.current() {
                             ensure_match_visible(
                                 &mut ui,
@@ -197,35 +465,58 @@ pub fn event_loop(
                                 m.line_number,
                                 m.element_index,
                                 m.char_offset,
-                                match_len,
+                                m.len,
                             );
                         }
                     }
                     last_search = Some(results.clone());
                 }
-                search_input = None;
+                if let Some(input) = search_input.take() {
+                    search_history.push(&input.text());
+                }
                 // Keep footer visible if there are results
                 ui.footer_height = if search_results.is_some() { 1 } else { 0 };
                 needs_redraw = true;
             }
             SearchCancel => {
                 // Restore previous search if any
-                search_results = last_search.clone();
+                search_results = last_search
+                    .clone()
+                    .map(|r| resume_scan(&json, r, &mut search_job));
                 search_input = None;
                 // Keep footer visible if there are results
                 ui.footer_height = if search_results.is_some() { 1 } else { 0 };
                 needs_redraw = true;
             }
+            HighlightSame | SiblingPaths => {
+                let results = match action {
+                    HighlightSame => {
+                        same_node_search(&json).ok_or("Select a key or a primitive value")
+                    }
+                    _ => sibling_search(&json).ok_or("The selection isn't inside an array"),
+                };
+                match results {
+                    Ok(results) => {
+                        search_job = None;
+                        last_search = Some(results.clone());
+                        search_results = Some(results);
+                        ui.footer_height = 1;
+                    }
+                    Err(e) => ui.show_message(e),
+                }
+                needs_redraw = true;
+            }
             RepeatSearch => {
                 // Revive search if cleared
                 if search_results.is_none() {
-                    search_results = last_search.clone();
+                    search_results = last_search
+                        .clone()
+                        .map(|r| resume_scan(&json, r, &mut search_job));
                     if search_results.is_some() {
                         ui.footer_height = 1;
                     }
                 }
                 if let Some(ref mut results) = search_results {
-                    let match_len = results.query.chars().count();
                     if let Some(m) = results.next() {
                         ensure_match_visible(
                             &mut ui,
@@ -233,7 +524,7 @@ pub fn event_loop(
                             m.line_number,
                             m.element_index,
                             m.char_offset,
-                            match_len,
+                            m.len,
                         );
                         needs_redraw = true;
                     }
@@ -241,13 +532,14 @@ pub fn event_loop(
             }
             RepeatSearchBackward => {
                 if search_results.is_none() {
-                    search_results = last_search.clone();
+                    search_results = last_search
+                        .clone()
+                        .map(|r| resume_scan(&json, r, &mut search_job));
                     if search_results.is_some() {
                         ui.footer_height = 1;
                     }
                 }
                 if let Some(ref mut results) = search_results {
-                    let match_len = results.query.chars().count();
                     if let Some(m) = results.prev() {
                         ensure_match_visible(
                             &mut ui,
@@ -255,12 +547,510 @@ pub fn event_loop(
                             m.line_number,
                             m.element_index,
                             m.char_offset,
-                            match_len,
+                            m.len,
                         );
                         needs_redraw = true;
                     }
                 }
             }
+            ToggleResults => {
+                if search_results.is_none() {
+                    search_results = last_search
+                        .clone()
+                        .map(|r| resume_scan(&json, r, &mut search_job));
+                }
+                match search_results {
+                    Some(ref mut results) if !results.matches.is_empty() => {
+                        results_panel = Some(ResultsPanel {
+                            origin: (json.tokens(), json.folds.clone(), results.current_index),
+                        });
+                        ui.footer_height = ui.panel_height(RESULTS_HEIGHT);
+                        if results.current_index.is_none() {
+                            results.next();
+                        }
+                        if let Some(m) = results.current() {
+                            ensure_match_visible(
+                                &mut ui,
+                                &mut json,
+                                m.line_number,
+                                m.element_index,
+                                m.char_offset,
+                                m.len,
+                            );
+                        }
+                    }
+                    _ => ui.show_message("No search matches"),
+                }
+                needs_redraw = true;
+            }
+            ResultsSelect(dir) => {
+                if let (Some(p), Some(ref mut results)) = (&results_panel, &mut search_results) {
+                    // Preview from the original folds so that browsing doesn't unfold everything
+                    json.folds = p.origin.1.clone();
+                    let m = match dir {
+                        Up => results.prev(),
+                        _ => results.next(),
+                    };
+                    if let Some(m) = m {
+                        ensure_match_visible(
+                            &mut ui,
+                            &mut json,
+                            m.line_number,
+                            m.element_index,
+                            m.char_offset,
+                            m.len,
+                        );
+                    }
+                    needs_redraw = true;
+                }
+            }
+            ResultsConfirm => {
+                if results_panel.take().is_some() {
+                    ui.footer_height = 1;
+                    needs_redraw = true;
+                }
+            }
+            ResultsCancel => {
+                if let Some(p) = results_panel.take() {
+                    let (tokens, folds, current_index) = p.origin;
+                    json.folds = folds;
+                    json.set_selection(tokens);
+                    if let Some(ref mut results) = search_results {
+                        results.current_index = current_index;
+                    }
+                    ui.footer_height = 1;
+                    ui.ensure_visible(json.visible_bounds());
+                    needs_redraw = true;
+                }
+            }
+
+            Find => {
+                finder = Some(Finder::new(
+                    &json.pointer_map,
+                    (json.tokens(), json.folds.clone()),
+                ));
+                ui.footer_height = ui.panel_height(FINDER_HEIGHT);
+                needs_redraw = true;
+            }
+            FinderEdit(edit) => {
+                if let Some(ref mut f) = finder {
+                    if f.edit(edit) {
+                        preview_finder_selection(&mut ui, &mut json, f);
+                    }
+                    needs_redraw = true;
+                }
+            }
+            FinderSelect(dir) => {
+                if let Some(ref mut f) = finder {
+                    let moved = match dir {
+                        Up => f.select_prev(),
+                        _ => f.select_next(),
+                    };
+                    if moved {
+                        preview_finder_selection(&mut ui, &mut json, f);
+                        needs_redraw = true;
+                    }
+                }
+            }
+            FinderConfirm => {
+                if let Some(f) = finder.take() {
+                    // Drop the folds opened while previewing; only keep the target's ancestors
+                    json.folds = f.origin.1.clone();
+                    json.reveal(f.selection().unwrap_or(f.origin.0));
+                    ui.footer_height = if search_results.is_some() { 1 } else { 0 };
+                    ui.ensure_visible(json.visible_bounds());
+                    needs_redraw = true;
+                }
+            }
+            FinderCancel => {
+                if let Some(f) = finder.take() {
+                    let (tokens, folds) = f.origin;
+                    json.folds = folds;
+                    json.set_selection(tokens);
+                    ui.footer_height = if search_results.is_some() { 1 } else { 0 };
+                    ui.ensure_visible(json.visible_bounds());
+                    needs_redraw = true;
+                }
+            }
+
+            Goto => {
+                goto = Some(GotoPrompt::new());
+                ui.footer_height = 1;
+                needs_redraw = true;
+            }
+            GotoEdit(edit) => {
+                if let Some(ref mut g) = goto {
+                    g.edit(edit);
+                    needs_redraw = true;
+                }
+            }
+            GotoComplete => {
+                if let Some(ref mut g) = goto {
+                    needs_redraw = g.complete(&json.value);
+                }
+            }
+            GotoConfirm => {
+                if let Some(g) = goto.take() {
+                    let input = g.input.text();
+                    ui.footer_height = if search_results.is_some() { 1 } else { 0 };
+                    match parse_path(&input) {
+                        Ok(tokens) => {
+                            let (tokens, found) = resolve(&json.value, &tokens);
+                            if !found {
+                                ui.show_message(format!(
+                                    "{} not found, selected {}",
+                                    input.trim(),
+                                    Pointer::period_path(&tokens)
+                                ));
+                            }
+                            json.reveal(tokens);
+                            ui.ensure_visible(json.visible_bounds());
+                        }
+                        Err(e) => ui.show_message(e),
+                    }
+                    needs_redraw = true;
+                }
+            }
+            GotoCancel => {
+                goto = None;
+                ui.footer_height = if search_results.is_some() { 1 } else { 0 };
+                needs_redraw = true;
+            }
+
+            Query => {
+                query_input = Some(Prompt::with_text(query.as_deref().unwrap_or_default()));
+                ui.footer_height = 1;
+                needs_redraw = true;
+            }
+            QueryEdit(edit) => {
+                if let Some(ref mut input) = query_input {
+                    input.edit(edit);
+                    needs_redraw = true;
+                }
+            }
+            QueryConfirm => {
+                if let Some(input) = query_input.take().map(|prompt| prompt.text()) {
+                    zoom_out_all(&mut json, &mut zooms);
+
+                    // Queries always run against the original document
+                    let original = match other_view {
+                        Some(ref other) if query_active => Rc::clone(&other.value),
+                        _ => Rc::clone(&json.value),
+                    };
+
+                    match run_query(&input, &original) {
+                        Ok(mut values) => {
+                            let count = values.len();
+                            let value = if count == 1 {
+                                values.remove(0)
+                            } else {
+                                Value::Array(values)
+                            };
+
+                            let derived = Json::from(Rc::new(value));
+                            show_derived(&mut json, &mut other_view, &mut query_active, derived);
+                            derived_label = Some(format!("| {}", input));
+                            broken = broken_refs(&json.value);
+                            jumps.clear();
+                            query = Some(input);
+                            ui.header_info = header_info(derived_label.as_ref(), &zooms);
+
+                            search_results = None;
+                            last_search = None;
+                            ui.footer_height = 0;
+                            ui.scroll_y_min();
+                            ui.scroll_x_min();
+
+                            if count != 1 {
+                                ui.show_message(format!("{} results, shown as an array", count));
+                            }
+                        }
+                        Err(e) => {
+                            ui.footer_height = if search_results.is_some() { 1 } else { 0 };
+                            ui.show_message(e);
+                        }
+                    }
+                    needs_redraw = true;
+                }
+            }
+            QueryCancel => {
+                query_input = None;
+                ui.footer_height = if search_results.is_some() { 1 } else { 0 };
+                needs_redraw = true;
+            }
+            ToggleQuery => {
+                if let Some(ref mut other) = other_view {
+                    zoom_out_all(&mut json, &mut zooms);
+                    std::mem::swap(&mut json, other);
+                    query_active = !query_active;
+                    broken = broken_refs(&json.value);
+                    jumps.clear();
+                    ui.header_info =
+                        header_info(derived_label.as_ref().filter(|_| query_active), &zooms);
+
+                    // Matches refer to lines of the other document
+                    search_results = None;
+                    last_search = None;
+                    ui.footer_height = 0;
+                    ui.ensure_visible(json.visible_bounds());
+                    needs_redraw = true;
+                }
+            }
+            ZoomIn | ZoomOut => {
+                let zoomed = match action {
+                    ZoomIn => {
+                        let path = json.tokens();
+                        match json.zoom(&path).filter(|_| !path.is_empty()) {
+                            Some(zoomed) => {
+                                zooms.push((std::mem::replace(&mut json, zoomed), path));
+                                true
+                            }
+                            None => {
+                                ui.show_message("Select a nested object or array to zoom into");
+                                false
+                            }
+                        }
+                    }
+                    _ => match zooms.pop() {
+                        Some((parent, path)) => {
+                            let zoomed = std::mem::replace(&mut json, parent);
+                            json.unzoom(&path, zoomed);
+                            true
+                        }
+                        None => {
+                            ui.show_message("Not zoomed in");
+                            false
+                        }
+                    },
+                };
+
+                if zoomed {
+                    ui.header_info =
+                        header_info(derived_label.as_ref().filter(|_| query_active), &zooms);
+
+                    // Matches refer to lines of the other document
+                    search_results = None;
+                    last_search = None;
+                    search_job = None;
+                    ui.footer_height = 0;
+                    ui.scroll_x_min();
+                    ui.scroll_y_min();
+                    ui.ensure_visible(json.visible_bounds());
+                }
+                needs_redraw = true;
+            }
+
+            InferSchema => {
+                if let Some(value) = json.output_value() {
+                    let schema = infer_schema(value);
+                    let path = [zoom_path(&zooms), json.tokens()].concat();
+
+                    zoom_out_all(&mut json, &mut zooms);
+                    let derived = Json::from(Rc::new(schema));
+                    show_derived(&mut json, &mut other_view, &mut query_active, derived);
+                    derived_label = Some(format!("schema of {}", jq_path(&path)));
+                    broken = broken_refs(&json.value);
+                    jumps.clear();
+                    query = None;
+                    ui.header_info = header_info(derived_label.as_ref(), &zooms);
+
+                    search_results = None;
+                    last_search = None;
+                    ui.footer_height = 0;
+                    ui.scroll_y_min();
+                    ui.scroll_x_min();
+                    ui.show_message("Inferred schema (Tab to go back)");
+                    needs_redraw = true;
+                }
+            }
+
+            OutputTypes | CopyTypes => {
+                if json.output_value().is_some() {
+                    let name = types_language.name().to_lowercase();
+                    types_input = Some((Prompt::with_text(&name), matches!(action, CopyTypes)));
+                    ui.footer_height = 1;
+                    needs_redraw = true;
+                }
+            }
+            TypesEdit(edit) => {
+                if let Some((ref mut input, _)) = types_input {
+                    input.edit(edit);
+                    needs_redraw = true;
+                }
+            }
+            TypesConfirm => {
+                if let Some((input, copy)) = types_input.take() {
+                    ui.footer_height = if search_results.is_some() { 1 } else { 0 };
+                    let text = input.text();
+                    match (Language::parse(&text), json.output_value()) {
+                        (None, _) => ui.show_message(format!(
+                            "Unknown language {} (rust, typescript, python or go)",
+                            text.trim()
+                        )),
+                        (Some(language), Some(value)) => {
+                            types_language = language;
+                            let path = [zoom_path(&zooms), json.tokens()].concat();
+                            let types = generate(value, &path, language);
+                            if !copy {
+                                output = Some(types);
+                                break;
+                            }
+                            clipboard.set_text(types)?;
+                            ui.show_message(format!("Copied {} types", language.name()));
+                        }
+                        (Some(_), None) => {}
+                    }
+                    needs_redraw = true;
+                }
+            }
+            TypesCancel => {
+                types_input = None;
+                ui.footer_height = if search_results.is_some() { 1 } else { 0 };
+                needs_redraw = true;
+            }
+
+            Schema => {
+                let path = validation.as_ref().map(|v| v.source.display().to_string());
+                schema_input = Some(Prompt::with_text(&path.unwrap_or_default()));
+                ui.footer_height = 1;
+                needs_redraw = true;
+            }
+            SchemaEdit(edit) => {
+                if let Some(ref mut input) = schema_input {
+                    input.edit(edit);
+                    needs_redraw = true;
+                }
+            }
+            SchemaConfirm => {
+                if let Some(input) = schema_input.take().map(|prompt| prompt.text()) {
+                    ui.footer_height = if search_results.is_some() { 1 } else { 0 };
+                    let path = input.trim();
+                    if path.is_empty() {
+                        if validation.take().is_some() {
+                            ui.show_message("Stopped validating");
+                        }
+                    } else {
+                        match load_schema(Path::new(path)) {
+                            Ok(schema) => {
+                                let original = match other_view {
+                                    Some(ref other) if query_active => other,
+                                    _ => zooms.first().map_or(&json, |(root, _)| root),
+                                };
+                                let v = Validation::new(path.into(), schema, original);
+                                ui.show_message(v.summary());
+                                validation = Some(v);
+                            }
+                            Err(e) => ui.show_message(e),
+                        }
+                    }
+                    needs_redraw = true;
+                }
+            }
+            SchemaCancel => {
+                schema_input = None;
+                ui.footer_height = if search_results.is_some() { 1 } else { 0 };
+                needs_redraw = true;
+            }
+            NextError | PrevError => {
+                match validation.as_mut() {
+                    None => ui.show_message("No schema to validate against (V to set one)"),
+                    Some(_) if query_active => {
+                        ui.show_message("Errors are in the original document (Tab to go back)")
+                    }
+                    Some(v) => match match action {
+                        NextError => v.next(),
+                        _ => v.prev(),
+                    } {
+                        None => ui.show_message(v.summary()),
+                        Some(error) => {
+                            let error = error.clone();
+                            let message = format!("Error {} at {}", v.status(), describe(&error));
+
+                            if reveal_path(&mut json, &mut zooms, &error.path) {
+                                // Matches refer to lines of the zoomed in document
+                                ui.header_info = header_info(None, &zooms);
+                                search_results = None;
+                                last_search = None;
+                                search_job = None;
+                                ui.footer_height = 0;
+                            }
+                            ui.ensure_visible(json.visible_bounds());
+                            ui.show_message(message);
+                        }
+                    },
+                }
+                needs_redraw = true;
+            }
+
+            FollowRef | JumpBack => {
+                let target = match action {
+                    FollowRef => {
+                        let root = zooms.first().map_or(&json, |(root, _)| root);
+                        let reference = match json.value() {
+                            Some(Value::String(r)) if json.token() == Some(&ref_key) => {
+                                Some(r.as_str())
+                            }
+                            Some(Value::Object(o)) => o.get(REF_KEY).and_then(Value::as_str),
+                            _ => None,
+                        };
+                        match reference.map(|r| ref_target(&root.value, r)) {
+                            Some(Ok(target)) => {
+                                jumps.push([zoom_path(&zooms), json.tokens()].concat());
+                                ui.show_message(format!(
+                                    "Followed {} (R to go back)",
+                                    reference.unwrap_or_default()
+                                ));
+                                Some(target)
+                            }
+                            Some(Err(e)) => {
+                                ui.show_message(e);
+                                None
+                            }
+                            None => {
+                                ui.show_message("Select a $ref to follow");
+                                None
+                            }
+                        }
+                    }
+                    _ => {
+                        let path = jumps.pop();
+                        if path.is_none() {
+                            ui.show_message("No $ref followed to go back from");
+                        }
+                        path
+                    }
+                };
+
+                if let Some(target) = target {
+                    if reveal_path(&mut json, &mut zooms, &target) {
+                        ui.header_info =
+                            header_info(derived_label.as_ref().filter(|_| query_active), &zooms);
+                        search_results = None;
+                        last_search = None;
+                        search_job = None;
+                        ui.footer_height = 0;
+                    }
+                    ui.ensure_visible(json.visible_bounds());
+                }
+                needs_redraw = true;
+            }
+
+            ToggleDocs => {
+                if validation.is_some() {
+                    docs_visible = !docs_visible;
+                } else {
+                    ui.show_message("No schema to describe the document (V to set one)");
+                }
+                needs_redraw = true;
+            }
+
+            QuitWithQuery => {
+                if let Some(ref q) = query {
+                    output = Some(q.clone());
+                    break;
+                }
+            }
+
             Filter => {}
             ClearSearch => {
                 if search_results.is_some() {
@@ -277,7 +1067,7 @@ pub fn event_loop(
                 }
             }
             OutputValuePretty => {
-                if let Some(value) = json.value() {
+                if let Some(value) = json.output_value() {
                     output = Some(value.to_string());
                     break;
                 }
@@ -289,12 +1079,38 @@ pub fn event_loop(
                 }
             }
             OutputValueRaw => {
-                if let Some(value) = json.value() {
+                if let Some(value) = json.output_value() {
                     output = Some(value.to_string());
                     break;
                 }
             }
 
+            OutputMatchPointers => {
+                match match_pointers(&json, search_results.as_ref(), &zoom_path(&zooms)) {
+                    Some(pointers) => {
+                        output = Some(pointers);
+                        break;
+                    }
+                    None => {
+                        ui.show_message("No search matches");
+                        needs_redraw = true;
+                    }
+                }
+            }
+
+            OutputMatchRecords => {
+                match match_records(&json, search_results.as_ref(), &zoom_path(&zooms)) {
+                    Some(records) => {
+                        output = Some(records);
+                        break;
+                    }
+                    None => {
+                        ui.show_message("No search matches");
+                        needs_redraw = true;
+                    }
+                }
+            }
+
             CopySelectionPretty => {
                 if let Some((key, value)) = json.token_value_pair() {
                     clipboard.set_text(selection_pretty(key, value)?)?;
@@ -303,7 +1119,7 @@ pub fn event_loop(
                 }
             }
             CopyValuePretty => {
-                if let Some(s) = json.value().map(to_string_pretty) {
+                if let Some(s) = json.output_value().map(to_string_pretty) {
                     clipboard.set_text(s?)?;
                     ui.start_flash(FlashMode::Value);
                     needs_redraw = true;
@@ -317,13 +1133,98 @@ pub fn event_loop(
                 }
             }
             CopyValueRaw => {
-                if let Some(s) = json.value().map(|v| v.to_string()) {
+                if let Some(s) = json.output_value().map(|v| v.to_string()) {
                     clipboard.set_text(s)?;
                     ui.start_flash(FlashMode::Value);
                     needs_redraw = true;
                 }
             }
 
+            CopySiblingPath => {
+                match PathPattern::generalize(&[zoom_path(&zooms), json.tokens()].concat()) {
+                    Some(pattern) => {
+                        let path = pattern.to_jq();
+                        ui.show_message(format!("Copied {}", path));
+                        clipboard.set_text(path)?;
+                    }
+                    None => ui.show_message("The selection isn't inside an array"),
+                }
+                needs_redraw = true;
+            }
+
+            CopyMatchPointers => {
+                match match_pointers(&json, search_results.as_ref(), &zoom_path(&zooms)) {
+                    Some(pointers) => {
+                        let count = pointers.lines().count();
+                        clipboard.set_text(pointers)?;
+                        ui.show_message(format!("Copied {} pointers", count));
+                    }
+                    None => ui.show_message("No search matches"),
+                }
+                needs_redraw = true;
+            }
+
+            CopyMatchRecords => {
+                match match_records(&json, search_results.as_ref(), &zoom_path(&zooms)) {
+                    Some(records) => {
+                        let count = records.lines().count();
+                        clipboard.set_text(records)?;
+                        ui.show_message(format!("Copied {} records", count));
+                    }
+                    None => ui.show_message("No search matches"),
+                }
+                needs_redraw = true;
+            }
+
+            Table => match Table::new(&json) {
+                Some(mut t) => {
+                    json.set_selection(t.selection(&json.value));
+                    t.scroll_to_selection(ui.body_size());
+                    table = Some(t);
+                    needs_redraw = true;
+                }
+                None => {
+                    ui.show_message("Nothing to show as a table");
+                    needs_redraw = true;
+                }
+            },
+            TableMove(_) | TablePage(_) | TableTop | TableBottom => {
+                if let Some(t) = table.as_mut() {
+                    let rows = ui.body_size().1.saturating_sub(1).max(1);
+                    needs_redraw = match action {
+                        TableMove(dir) => t.select(dir, 1),
+                        TablePage(dir) => t.select(dir, rows / 2),
+                        TableTop => t.select(Up, usize::MAX),
+                        _ => t.select(Down, usize::MAX),
+                    };
+                    json.set_selection(t.selection(&json.value));
+                    t.scroll_to_selection(ui.body_size());
+                }
+            }
+            TableResize(dir) => {
+                if let Some(t) = table.as_mut() {
+                    needs_redraw = t.resize(dir);
+                    t.scroll_to_selection(ui.body_size());
+                }
+            }
+            TableHideColumn | TableShowColumns => {
+                if let Some(t) = table.as_mut() {
+                    needs_redraw = match action {
+                        TableHideColumn => t.hide_column(),
+                        _ => t.show_columns(),
+                    };
+                    json.set_selection(t.selection(&json.value));
+                    t.scroll_to_selection(ui.body_size());
+                }
+            }
+            TableClose => {
+                table = None;
+                let tokens = json.tokens();
+                json.reveal(tokens);
+                ui.ensure_visible(json.visible_bounds());
+                needs_redraw = true;
+            }
+
             ToggleLineNumbers => {
                 ui.toggle_line_numbers();
                 needs_redraw = true;
@@ -333,32 +1234,208 @@ pub fn event_loop(
                 needs_redraw = true;
             }
 
+            CycleView => {
+                ui.view = ui.view.next();
+                ui.show_message(match ui.view {
+                    View::Json => "JSON view",
+                    View::Columns => "Columns view",
+                    View::Outline => "Outline view",
+                });
+                ui.ensure_visible(json.visible_bounds());
+                needs_redraw = true;
+            }
+
             ShowHelp => {
-                help_visible = true;
+                ui.help_visible = true;
                 needs_redraw = true;
             }
             DismissHelp => {
-                help_visible = false;
+                ui.help_visible = false;
                 needs_redraw = true;
             }
 
             Ignore => {}
         }
 
-        if needs_redraw {
-            ui.render(
-                source,
-                &json,
-                search_input.as_deref(),
-                search_results.as_ref(),
-                help_visible,
-            )?;
-        }
+        needs_redraw |= message_cleared;
     }
 
     Ok(output)
 }
 
+/// Header text after the source: what the derived document shown is, and the path zoomed
+/// into.
+fn header_info(derived: Option<&String>, zooms: &[(Json, Vec<Token>)]) -> Option<String> {
+    let mut info = vec![];
+    if let Some(derived) = derived {
+        info.push(derived.clone());
+    }
+    if !zooms.is_empty() {
+        let path: Vec<String> = zooms
+            .iter()
+            .flat_map(|(_, path)| path)
+            .map(token_label)
+            .collect();
+        info.push(format!("› {}", path.join(" › ")));
+    }
+
+    (!info.is_empty()).then(|| info.join(" "))
+}
+
+/// Shows a document derived from the original one, which `Tab` toggles back to.
+fn show_derived(
+    json: &mut Json,
+    other_view: &mut Option<Json>,
+    query_active: &mut bool,
+    derived: Json,
+) {
+    if *query_active {
+        *json = derived;
+    } else {
+        *other_view = Some(std::mem::replace(json, derived));
+        *query_active = true;
+    }
+}
+
+/// Zooms back out to the whole document.
+fn zoom_out_all(json: &mut Json, zooms: &mut Vec<(Json, Vec<Token>)>) {
+    while let Some((parent, path)) = zooms.pop() {
+        let zoomed = std::mem::replace(json, parent);
+        json.unzoom(&path, zoomed);
+    }
+}
+
+/// The path of the subtree zoomed into, from the root of the document.
+fn zoom_path(zooms: &[(Json, Vec<Token>)]) -> Vec<Token> {
+    zooms
+        .iter()
+        .flat_map(|(_, path)| path.iter().cloned())
+        .collect()
+}
+
+/// Selects the node at `path` from the root of the document, zooming out if it's outside the
+/// subtree zoomed into. Returns whether it zoomed out.
+fn reveal_path(json: &mut Json, zooms: &mut Vec<(Json, Vec<Token>)>, path: &[Token]) -> bool {
+    let zoomed_out = !path.starts_with(&zoom_path(zooms));
+    if zoomed_out {
+        zoom_out_all(json, zooms);
+    }
+    json.reveal(path[zoom_path(zooms).len()..].to_vec());
+    zoomed_out
+}
+
+/// The paths that are inside the subtree zoomed into, relative to it.
+fn within_zoom(paths: &[Vec<Token>], zooms: &[(Json, Vec<Token>)]) -> HashSet<Vec<Token>> {
+    let prefix = zoom_path(zooms);
+    paths
+        .iter()
+        .filter_map(|p| Some(p.strip_prefix(prefix.as_slice())?.to_vec()))
+        .collect()
+}
+
+/// Nodes with schema errors in the document shown, which is zoomed into the path of `zooms`.
+fn error_marks(
+    validation: Option<&Validation>,
+    zooms: &[(Json, Vec<Token>)],
+    query_active: bool,
+) -> HashSet<Vec<Token>> {
+    match validation {
+        Some(v) if !query_active => v.marks(&zoom_path(zooms)),
+        _ => HashSet::new(),
+    }
+}
+
+/// Moves the selection to the finder's current result, unfolding as needed
+fn preview_finder_selection(ui: &mut UI, json: &mut Json, finder: &Finder) {
+    json.folds = finder.origin.1.clone();
+    if let Some(tokens) = finder.selection() {
+        json.reveal(tokens);
+    } else {
+        json.set_selection(finder.origin.0.clone());
+    }
+    ui.ensure_visible(json.visible_bounds());
+}
+
+/// Searches for the prompt input as it's typed and scrolls to the first match
+fn preview_search(
+    ui: &mut UI,
+    json: &mut Json,
+    input: &str,
+    options: TextOptions,
+    previous: Option<&SearchResults>,
+    search_job: &mut Option<SearchJob>,
+) -> Option<SearchResults> {
+    if input.is_empty() {
+        *search_job = None;
+        return None;
+    }
+
+    let (results, job) = start_search(json, input, options, previous);
+    *search_job = job;
+    if let Some(m) = results.matches.first() {
+        ensure_match_visible(
+            ui,
+            json,
+            m.line_number,
+            m.element_index,
+            m.char_offset,
+            m.len,
+        );
+    }
+
+    Some(results)
+}
+
+/// Restarts the scan of results whose background job was cancelled
+fn resume_scan(
+    json: &Json,
+    results: SearchResults,
+    search_job: &mut Option<SearchJob>,
+) -> SearchResults {
+    if results.scan.is_none() || search_job.as_ref().map(|job| job.id) == results.scan {
+        return results;
+    }
+
+    let (results, job) = start_search(json, &results.query, results.options, None);
+    *search_job = job;
+    results
+}
+
+/// Applies a change that formats the document again, moving search matches along with the
+/// nodes they're on.
+fn keep_matches<T>(
+    json: &mut Json,
+    search_results: &mut Option<SearchResults>,
+    last_search: &mut Option<SearchResults>,
+    search_job: &mut Option<SearchJob>,
+    change: impl FnOnce(&mut Json) -> T,
+) -> T {
+    let lines: HashMap<usize, (Vec<Token>, bool)> = search_results
+        .iter()
+        .chain(last_search.iter())
+        .flat_map(|r| &r.matches)
+        .filter_map(|m| Some((m.line_number, json.line_location(m.line_number)?)))
+        .collect();
+
+    let result = change(json);
+
+    let locate = |line| {
+        let (tokens, closing) = lines.get(&line)?;
+        json.line_at(tokens, *closing)
+    };
+    for results in search_results.iter_mut().chain(last_search.iter_mut()) {
+        results.relocate(locate);
+    }
+    // Partial matches of a scan can't be moved, so scan again
+    if let Some(results) = search_results.as_mut().filter(|r| r.scan.is_some()) {
+        let (restarted, job) = start_search(json, &results.query, results.options, None);
+        *results = restarted;
+        *search_job = job;
+    }
+
+    result
+}
+
 /// Unfolds ancestors, sets selection, and scrolls to make a match visible
 fn ensure_match_visible(
     ui: &mut UI,
@@ -403,6 +1480,60 @@ fn ensure_match_visible(
     }
 }
 
+/// Lists the JSON Pointers of the nodes holding search matches, one per line, from the root of
+/// the document when zoomed into `prefix`.
+fn match_pointers(
+    json: &Json,
+    search_results: Option<&SearchResults>,
+    prefix: &[Token],
+) -> Option<String> {
+    let pointers = search_results?.pointers(&json.formatted);
+    if pointers.is_empty() {
+        return None;
+    }
+
+    Some(
+        pointers
+            .iter()
+            .map(|tokens| Pointer::json_pointer(&[prefix, tokens].concat()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    )
+}
+
+/// One `{"pointer", "path", "value"}` JSON record per line for each node with a match, with
+/// paths from the root of the document when zoomed into `prefix`.
+fn match_records(
+    json: &Json,
+    search_results: Option<&SearchResults>,
+    prefix: &[Token],
+) -> Option<String> {
+    let pointers = search_results?.pointers(&json.formatted);
+    if pointers.is_empty() {
+        return None;
+    }
+
+    Some(
+        pointers
+            .iter()
+            .map(|tokens| {
+                let value = json
+                    .output_value_at(&Pointer::json_pointer(tokens))
+                    .cloned()
+                    .unwrap_or(Value::Null);
+                let path = [prefix, tokens].concat();
+                serde_json::json!({
+                    "pointer": Pointer::json_pointer(&path),
+                    "path": jq_path(&path),
+                    "value": value,
+                })
+                .to_string()
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+    )
+}
+
 fn selection(key: Option<String>, value: &Value) -> anyhow::Result<String> {
     Ok(if let Some(key) = key {
         format!("\"{}\": {}", key, value)
@@ -418,3 +1549,32 @@ fn selection_pretty(key: Option<String>, value: &Value) -> anyhow::Result<String
         to_string_pretty(value)?
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::search::pointer_search;
+
+    #[test]
+    fn zoomed_match_export() {
+        let root = Json::from(json!({"a": {"b": [1, 2]}, "c": 2}));
+        let path = vec![Token::Key("a".to_string())];
+        let zoomed = root.zoom(&path).unwrap();
+        let zooms = vec![(root, path)];
+        let matches = vec![vec![Token::Key("b".to_string()), Token::Index(1)]];
+        let results = pointer_search(&zoomed, ".b[1]", &matches);
+
+        // Paths are from the root of the document, values from the subtree shown
+        let prefix = zoom_path(&zooms);
+        assert_eq!(
+            match_pointers(&zoomed, Some(&results), &prefix).as_deref(),
+            Some("/a/b/1")
+        );
+        assert_eq!(
+            match_records(&zoomed, Some(&results), &prefix).as_deref(),
+            Some(r#"{"pointer":"/a/b/1","path":".a.b[1]","value":2}"#)
+        );
+    }
+}
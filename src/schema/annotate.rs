@@ -0,0 +1,240 @@
+use serde_json::Value;
+
+use super::validate::{resolve_ref, Validator};
+use crate::json::Token;
+
+/// Nested `$ref`s and combinators followed at one level, before giving up on a loop.
+const MAX_DEPTH: usize = 32;
+
+/// What a schema says about a node of the document.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct Annotation {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub types: Vec<String>,
+    /// The values allowed by `enum` or `const`
+    pub values: Option<Vec<Value>>,
+}
+
+/// Finds what the schema of `validator` says about the node of `document` at `path`,
+/// following `$ref`s and the branches of combinators that apply. Returns `None` if no part of
+/// the schema describes the node.
+pub(super) fn annotate(
+    validator: &Validator,
+    document: &Value,
+    path: &[Token],
+) -> Option<Annotation> {
+    let schema = validator.root;
+    let mut value = document;
+    let mut schemas = applicable(validator, vec![schema], value);
+
+    for token in path {
+        let child = match (value, token) {
+            (Value::Object(o), Token::Key(k)) => o.get(k)?,
+            (Value::Array(a), Token::Index(i)) => a.get(*i)?,
+            _ => return None,
+        };
+        let children = schemas
+            .iter()
+            .flat_map(|s| child_schemas(validator, s, token))
+            .collect();
+        schemas = applicable(validator, children, child);
+        value = child;
+    }
+    if schemas.is_empty() {
+        return None;
+    }
+
+    let mut annotation = Annotation::default();
+    for s in schemas.iter().filter_map(|s| s.as_object()) {
+        let text = |keyword| s.get(keyword).and_then(Value::as_str).map(str::to_string);
+        annotation.title = annotation.title.or_else(|| text("title"));
+        annotation.description = annotation.description.or_else(|| text("description"));
+
+        let types = match s.get("type") {
+            Some(Value::String(t)) => vec![t.clone()],
+            Some(Value::Array(ts)) => ts
+                .iter()
+                .filter_map(Value::as_str)
+                .map(Into::into)
+                .collect(),
+            _ => vec![],
+        };
+        for t in types {
+            if !annotation.types.contains(&t) {
+                annotation.types.push(t);
+            }
+        }
+
+        if annotation.values.is_none() {
+            annotation.values = match (s.get("enum"), s.get("const")) {
+                (Some(Value::Array(values)), _) => Some(values.clone()),
+                (_, Some(value)) => Some(vec![value.clone()]),
+                _ => None,
+            };
+        }
+    }
+
+    Some(annotation)
+}
+
+/// The subschemas describing the member or element at `token`.
+fn child_schemas<'s>(validator: &Validator, schema: &'s Value, token: &Token) -> Vec<&'s Value> {
+    let Some(schema) = schema.as_object() else {
+        return vec![];
+    };
+
+    match token {
+        Token::Key(key) => {
+            let mut children: Vec<&Value> = schema
+                .get("properties")
+                .and_then(|p| p.get(key))
+                .into_iter()
+                .collect();
+            let patterns = schema.get("patternProperties").and_then(Value::as_object);
+            for (pattern, subschema) in patterns.into_iter().flatten() {
+                if validator.regex(pattern).is_some_and(|re| re.is_match(key)) {
+                    children.push(subschema);
+                }
+            }
+            if children.is_empty() {
+                children.extend(schema.get("additionalProperties"));
+            }
+            children
+        }
+        Token::Index(index) => {
+            let prefix = schema.get("prefixItems").and_then(|p| p.get(index));
+            prefix.or_else(|| schema.get("items")).into_iter().collect()
+        }
+    }
+}
+
+/// Expands `schemas` with those they refer to and the branches of their combinators that
+/// apply to `value`.
+fn applicable<'s>(
+    validator: &Validator<'s>,
+    schemas: Vec<&'s Value>,
+    value: &Value,
+) -> Vec<&'s Value> {
+    let mut expanded = vec![];
+    for schema in schemas {
+        expand(validator, schema, value, 0, &mut expanded);
+    }
+    expanded
+}
+
+fn expand<'s>(
+    validator: &Validator<'s>,
+    schema: &'s Value,
+    value: &Value,
+    depth: usize,
+    expanded: &mut Vec<&'s Value>,
+) {
+    let Some(object) = schema.as_object().filter(|_| depth < MAX_DEPTH) else {
+        return;
+    };
+    expanded.push(schema);
+
+    if let Some(target) = object
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|r| resolve_ref(validator.root, r))
+    {
+        expand(validator, target, value, depth + 1, expanded);
+    }
+    for branch in object
+        .get("allOf")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        expand(validator, branch, value, depth + 1, expanded);
+    }
+    // Only the alternatives the value matches, if any does
+    for keyword in ["anyOf", "oneOf"] {
+        let Some(branches) = object.get(keyword).and_then(Value::as_array) else {
+            continue;
+        };
+        let matching: Vec<&Value> = branches
+            .iter()
+            .filter(|b| validator.matches(b, value))
+            .collect();
+        let branches = if matching.is_empty() {
+            branches.iter().collect()
+        } else {
+            matching
+        };
+        for branch in branches {
+            expand(validator, branch, value, depth + 1, expanded);
+        }
+    }
+    if let Some(condition) = object.get("if") {
+        let branch = if validator.matches(condition, value) {
+            object.get("then")
+        } else {
+            object.get("else")
+        };
+        if let Some(branch) = branch {
+            expand(validator, branch, value, depth + 1, expanded);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::schema::validate::Patterns;
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn annotations() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "servers": {"type": "array", "items": {"$ref": "#/$defs/server"}},
+                "mode": {"description": "How to run", "enum": ["fast", "safe"]}
+            },
+            "additionalProperties": {"description": "Anything else"},
+            "$defs": {
+                "server": {
+                    "title": "Server",
+                    "properties": {
+                        "port": {
+                            "oneOf": [
+                                {"type": "integer", "description": "A port number"},
+                                {"type": "string", "description": "A named port"}
+                            ]
+                        }
+                    }
+                }
+            }
+        });
+        let document = json!({"servers": [{"port": 80}, {"port": "http"}], "mode": "fast", "x": 1});
+        let patterns = Patterns::default();
+        let validator = Validator::new(&schema, &patterns);
+        let at = |path: Vec<Token>| annotate(&validator, &document, &path);
+        let key = |k: &str| Token::Key(k.to_string());
+
+        assert_eq!(
+            at(vec![key("mode")]),
+            Some(Annotation {
+                description: Some("How to run".into()),
+                values: Some(vec![json!("fast"), json!("safe")]),
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            at(vec![key("servers"), Token::Index(1)]).and_then(|a| a.title),
+            Some("Server".into())
+        );
+        let port = at(vec![key("servers"), Token::Index(1), key("port")]).unwrap();
+        assert_eq!(port.description.as_deref(), Some("A named port"));
+        assert_eq!(port.types, vec!["string"]);
+        assert_eq!(
+            at(vec![key("x")]).and_then(|a| a.description),
+            Some("Anything else".into())
+        );
+        assert_eq!(at(vec![key("servers"), Token::Index(0), key("host")]), None);
+    }
+}
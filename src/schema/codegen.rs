@@ -0,0 +1,792 @@
+use std::collections::{BTreeSet, HashSet};
+
+use serde_json::Value;
+
+use super::infer::{ObjectShape, Shape};
+use crate::json::Token;
+
+/// A language type definitions are generated in.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Language {
+    Rust,
+    TypeScript,
+    Python,
+    Go,
+}
+
+impl Language {
+    /// Parses a language name or its usual abbreviation, e.g. `ts`.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "rust" | "rs" => Some(Language::Rust),
+            "typescript" | "ts" => Some(Language::TypeScript),
+            "python" | "py" => Some(Language::Python),
+            "go" | "golang" => Some(Language::Go),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Language::Rust => "Rust",
+            Language::TypeScript => "TypeScript",
+            Language::Python => "Python",
+            Language::Go => "Go",
+        }
+    }
+
+    /// Type names that declarations mustn't shadow, as the generated code uses them.
+    fn reserved_names(self) -> &'static [&'static str] {
+        match self {
+            Language::Rust => RUST_TYPES,
+            Language::TypeScript => TYPESCRIPT_TYPES,
+            Language::Python => PYTHON_TYPES,
+            // Go's own types aren't capitalized
+            Language::Go => &[],
+        }
+    }
+}
+
+/// The type of a value, once shapes of objects are given names.
+#[derive(Debug)]
+enum Type {
+    /// Values that are only ever `null`, or arrays that are always empty
+    Any,
+    Bool,
+    Integer,
+    Number,
+    String,
+    Array(Box<Type>),
+    /// A struct declared under that name
+    Named(String),
+    Nullable(Box<Type>),
+    /// Values of several types
+    Union(Vec<Type>),
+}
+
+struct Field {
+    key: String,
+    ty: Type,
+    /// Whether some objects lack the field
+    optional: bool,
+}
+
+enum Declaration {
+    Struct(String, Vec<Field>),
+    Alias(String, Type),
+}
+
+/// The declarations needed for a value, in the order they're generated.
+#[derive(Default)]
+struct Declarations {
+    declarations: Vec<Declaration>,
+    names: HashSet<String>,
+}
+
+impl Declarations {
+    /// Reserves a name, prefixing it with the parent's name or numbering it when it's taken.
+    fn unique_name(&mut self, name: &str, parent: &str) -> String {
+        let mut unique = name.to_string();
+        if self.names.contains(&unique) {
+            unique = format!("{}{}", parent, name);
+        }
+        let mut n = 2;
+        while self.names.contains(&unique) {
+            unique = format!("{}{}", name, n);
+            n += 1;
+        }
+        self.names.insert(unique.clone());
+        unique
+    }
+
+    fn type_of(&mut self, shape: &Shape, name: &str, parent: &str) -> Type {
+        let mut variants = vec![];
+        if let Some(ref object) = shape.object {
+            variants.push(self.declare(object, name, parent));
+        }
+        if let Some(ref items) = shape.array {
+            let item = if items.is_empty() {
+                Type::Any
+            } else {
+                self.type_of(items, &singular(name), parent)
+            };
+            variants.push(Type::Array(Box::new(item)));
+        }
+        if shape.string.is_some() {
+            variants.push(Type::String);
+        }
+        if shape.number {
+            variants.push(Type::Number);
+        } else if shape.integer {
+            variants.push(Type::Integer);
+        }
+        if shape.boolean {
+            variants.push(Type::Bool);
+        }
+
+        let ty = match variants.len() {
+            0 => return Type::Any,
+            1 => variants.remove(0),
+            _ => Type::Union(variants),
+        };
+        if shape.null {
+            Type::Nullable(Box::new(ty))
+        } else {
+            ty
+        }
+    }
+
+    /// Declares a struct for the merged objects, before the structs of its fields.
+    fn declare(&mut self, object: &ObjectShape, name: &str, parent: &str) -> Type {
+        let name = self.unique_name(name, parent);
+        let index = self.declarations.len();
+        self.declarations
+            .push(Declaration::Struct(name.clone(), vec![]));
+
+        let mut fields = vec![];
+        for property in &object.properties {
+            fields.push(Field {
+                key: property.name.clone(),
+                ty: self.type_of(&property.shape, &pascal_case(&property.name), &name),
+                optional: property.count < object.count,
+            });
+        }
+        self.declarations[index] = Declaration::Struct(name.clone(), fields);
+
+        Type::Named(name)
+    }
+}
+
+/// Generates type definitions for `value` in `language`, naming the root type after the last
+/// key of `path`. Elements of arrays get one type with the shapes of all of them merged, and
+/// fields missing from some objects are optional.
+pub fn generate(value: &Value, path: &[Token], language: Language) -> String {
+    let name = root_name(path);
+    let mut declarations = Declarations::default();
+    declarations
+        .names
+        .extend(language.reserved_names().iter().map(|n| n.to_string()));
+    let root = declarations.type_of(&Shape::of(value), &name, "");
+    if !matches!(root, Type::Named(_)) {
+        let name = declarations.unique_name(&name, "");
+        declarations
+            .declarations
+            .push(Declaration::Alias(name, root));
+    }
+
+    let declarations = &declarations.declarations;
+    match language {
+        Language::Rust => rust(declarations),
+        Language::TypeScript => typescript(declarations),
+        Language::Python => python(declarations),
+        Language::Go => go(declarations),
+    }
+}
+
+fn rust(declarations: &[Declaration]) -> String {
+    fn rust_type(ty: &Type) -> String {
+        match ty {
+            Type::Any | Type::Union(_) => "serde_json::Value".to_string(),
+            Type::Bool => "bool".to_string(),
+            Type::Integer => "i64".to_string(),
+            Type::Number => "f64".to_string(),
+            Type::String => "String".to_string(),
+            Type::Array(item) => format!("Vec<{}>", rust_type(item)),
+            Type::Named(name) => name.clone(),
+            Type::Nullable(ty) => format!("Option<{}>", rust_type(ty)),
+        }
+    }
+
+    let mut blocks = vec![];
+    if declarations
+        .iter()
+        .any(|d| matches!(d, Declaration::Struct(..)))
+    {
+        blocks.push("use serde::{Deserialize, Serialize};".to_string());
+    }
+    for declaration in declarations {
+        blocks.push(match declaration {
+            Declaration::Struct(name, fields) => {
+                let mut lines = vec![
+                    "#[derive(Debug, Clone, Serialize, Deserialize)]".to_string(),
+                    format!("pub struct {} {{", name),
+                ];
+                for (field, name) in fields.iter().zip(field_names(fields, rust_field, "_")) {
+                    if name.trim_start_matches("r#") != field.key {
+                        lines.push(format!("    #[serde(rename = {:?})]", field.key));
+                    }
+                    let ty = match field.ty {
+                        Type::Nullable(_) => rust_type(&field.ty),
+                        _ if field.optional => format!("Option<{}>", rust_type(&field.ty)),
+                        _ => rust_type(&field.ty),
+                    };
+                    if field.optional {
+                        lines.push(
+                            "    #[serde(skip_serializing_if = \"Option::is_none\")]".to_string(),
+                        );
+                    }
+                    lines.push(format!("    pub {}: {},", name, ty));
+                }
+                lines.push("}".to_string());
+                lines.join("\n")
+            }
+            Declaration::Alias(name, ty) => format!("pub type {} = {};", name, rust_type(ty)),
+        });
+    }
+
+    blocks.join("\n\n")
+}
+
+fn typescript(declarations: &[Declaration]) -> String {
+    fn ts_type(ty: &Type) -> String {
+        match ty {
+            Type::Any => "unknown".to_string(),
+            Type::Bool => "boolean".to_string(),
+            Type::Integer | Type::Number => "number".to_string(),
+            Type::String => "string".to_string(),
+            Type::Array(item) => match **item {
+                Type::Nullable(_) | Type::Union(_) => format!("({})[]", ts_type(item)),
+                _ => format!("{}[]", ts_type(item)),
+            },
+            Type::Named(name) => name.clone(),
+            Type::Nullable(ty) => format!("{} | null", ts_type(ty)),
+            Type::Union(types) => types.iter().map(ts_type).collect::<Vec<_>>().join(" | "),
+        }
+    }
+
+    let blocks: Vec<String> = declarations
+        .iter()
+        .map(|declaration| match declaration {
+            Declaration::Struct(name, fields) => {
+                let mut lines = vec![format!("export interface {} {{", name)];
+                for field in fields {
+                    let key = if is_identifier(&field.key, true) {
+                        field.key.clone()
+                    } else {
+                        format!("{:?}", field.key)
+                    };
+                    let optional = if field.optional { "?" } else { "" };
+                    lines.push(format!("  {}{}: {};", key, optional, ts_type(&field.ty)));
+                }
+                lines.push("}".to_string());
+                lines.join("\n")
+            }
+            Declaration::Alias(name, ty) => format!("export type {} = {};", name, ts_type(ty)),
+        })
+        .collect();
+
+    blocks.join("\n\n")
+}
+
+fn python(declarations: &[Declaration]) -> String {
+    fn py_type(ty: &Type, imports: &mut BTreeSet<&'static str>) -> String {
+        match ty {
+            Type::Any => {
+                imports.insert("Any");
+                "Any".to_string()
+            }
+            Type::Bool => "bool".to_string(),
+            Type::Integer => "int".to_string(),
+            Type::Number => "float".to_string(),
+            Type::String => "str".to_string(),
+            Type::Array(item) => format!("list[{}]", py_type(item, imports)),
+            Type::Named(name) => name.clone(),
+            Type::Nullable(ty) => {
+                imports.insert("Optional");
+                format!("Optional[{}]", py_type(ty, imports))
+            }
+            Type::Union(types) => {
+                imports.insert("Union");
+                let types: Vec<String> = types.iter().map(|t| py_type(t, imports)).collect();
+                format!("Union[{}]", types.join(", "))
+            }
+        }
+    }
+
+    let mut imports = BTreeSet::new();
+    let mut blocks = vec![];
+    for declaration in declarations {
+        blocks.push(match declaration {
+            Declaration::Struct(name, fields) => {
+                let mut lines = vec!["@dataclass".to_string(), format!("class {}:", name)];
+                // Fields with a default must come last
+                let (optional, required): (Vec<_>, Vec<_>) = fields
+                    .iter()
+                    .zip(field_names(fields, python_field, "_"))
+                    .partition(|(f, _)| f.optional);
+                for (field, name) in required.into_iter().chain(optional) {
+                    let ty = match field.ty {
+                        Type::Nullable(_) | Type::Any => py_type(&field.ty, &mut imports),
+                        _ if field.optional => {
+                            imports.insert("Optional");
+                            format!("Optional[{}]", py_type(&field.ty, &mut imports))
+                        }
+                        _ => py_type(&field.ty, &mut imports),
+                    };
+                    let default = if field.optional { " = None" } else { "" };
+                    let comment = if name != field.key {
+                        format!("  # {:?} in JSON", field.key)
+                    } else {
+                        String::new()
+                    };
+                    lines.push(format!("    {}: {}{}{}", name, ty, default, comment));
+                }
+                if fields.is_empty() {
+                    lines.push("    pass".to_string());
+                }
+                lines.join("\n")
+            }
+            Declaration::Alias(name, ty) => format!("{} = {}", name, py_type(ty, &mut imports)),
+        });
+    }
+
+    let mut header = vec!["from __future__ import annotations".to_string()];
+    let mut modules = vec![];
+    if declarations
+        .iter()
+        .any(|d| matches!(d, Declaration::Struct(..)))
+    {
+        modules.push("from dataclasses import dataclass".to_string());
+    }
+    if !imports.is_empty() {
+        let names: Vec<&str> = imports.into_iter().collect();
+        modules.push(format!("from typing import {}", names.join(", ")));
+    }
+    if !modules.is_empty() {
+        header.push(modules.join("\n"));
+    }
+
+    format!("{}\n\n\n{}", header.join("\n\n"), blocks.join("\n\n\n"))
+}
+
+fn go(declarations: &[Declaration]) -> String {
+    fn go_type(ty: &Type) -> String {
+        match ty {
+            Type::Any | Type::Union(_) => "any".to_string(),
+            Type::Bool => "bool".to_string(),
+            Type::Integer => "int64".to_string(),
+            Type::Number => "float64".to_string(),
+            Type::String => "string".to_string(),
+            Type::Array(item) => format!("[]{}", go_type(item)),
+            Type::Named(name) => name.clone(),
+            Type::Nullable(ty) => pointer(ty),
+        }
+    }
+    // Slices and `any` can already be nil
+    fn pointer(ty: &Type) -> String {
+        match ty {
+            Type::Any | Type::Union(_) | Type::Array(_) | Type::Nullable(_) => go_type(ty),
+            _ => format!("*{}", go_type(ty)),
+        }
+    }
+
+    let blocks: Vec<String> = declarations
+        .iter()
+        .map(|declaration| match declaration {
+            Declaration::Struct(name, fields) => {
+                let rows: Vec<(String, String, String)> = fields
+                    .iter()
+                    .zip(field_names(fields, go_field, ""))
+                    .map(|(field, name)| {
+                        let (ty, tag) = if field.optional {
+                            (pointer(&field.ty), format!("{},omitempty", field.key))
+                        } else {
+                            (go_type(&field.ty), field.key.clone())
+                        };
+                        let tag = format!("`json:{:?}`", tag);
+                        (name, ty, tag)
+                    })
+                    .collect();
+
+                // Aligned like gofmt does
+                let name_width = rows.iter().map(|r| r.0.len()).max().unwrap_or(0);
+                let type_width = rows.iter().map(|r| r.1.len()).max().unwrap_or(0);
+                let mut lines = vec![format!("type {} struct {{", name)];
+                for (field, ty, tag) in rows {
+                    lines.push(format!(
+                        "\t{:name_width$} {:type_width$} {}",
+                        field, ty, tag
+                    ));
+                }
+                lines.push("}".to_string());
+                lines.join("\n")
+            }
+            Declaration::Alias(name, ty) => format!("type {} {}", name, go_type(ty)),
+        })
+        .collect();
+
+    blocks.join("\n\n")
+}
+
+/// Names the fields of a struct with `name`, numbering those that would clash with an earlier
+/// one, as `user-id` would with `user_id`.
+fn field_names(fields: &[Field], name: fn(&str) -> String, separator: &str) -> Vec<String> {
+    let mut taken = HashSet::new();
+    fields
+        .iter()
+        .map(|field| {
+            let base = name(&field.key);
+            let mut unique = base.clone();
+            let mut n = 2;
+            while !taken.insert(unique.clone()) {
+                unique = format!("{}{}{}", base, separator, n);
+                n += 1;
+            }
+            unique
+        })
+        .collect()
+}
+
+/// Names the root type after the last key of its path, in the singular for an element.
+fn root_name(path: &[Token]) -> String {
+    match path.iter().rposition(|t| matches!(t, Token::Key(_))) {
+        Some(i) => {
+            let name = pascal_case(&path[i].to_string());
+            if i + 1 < path.len() {
+                singular(&name)
+            } else {
+                name
+            }
+        }
+        None => "Root".to_string(),
+    }
+}
+
+/// The singular of an English plural, e.g. `Categories` for `Category`, or the name followed
+/// by `Item` if it doesn't look like one.
+fn singular(name: &str) -> String {
+    let singular = if let Some(stem) = name.strip_suffix("ies") {
+        format!("{}y", stem)
+    } else if ["sses", "uses", "xes", "ches", "shes"]
+        .iter()
+        .any(|s| name.ends_with(s))
+    {
+        name[..name.len() - 2].to_string()
+    } else if name.ends_with('s') && !["ss", "us", "is"].iter().any(|s| name.ends_with(s)) {
+        name[..name.len() - 1].to_string()
+    } else {
+        name.to_string()
+    };
+
+    if singular == name || singular.len() < 2 {
+        format!("{}Item", name)
+    } else {
+        singular
+    }
+}
+
+/// Splits a key into words at non-alphanumeric characters and case changes, e.g. `userID`
+/// and `user_id` into `user` and `ID`/`id`.
+fn words(key: &str) -> Vec<String> {
+    let chars: Vec<char> = key.chars().collect();
+    let mut words = vec![];
+    let mut word = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_ascii_alphanumeric() {
+            words.push(std::mem::take(&mut word));
+            continue;
+        }
+        let previous = i.checked_sub(1).map(|i| chars[i]);
+        let next = chars.get(i + 1);
+        let boundary = c.is_ascii_uppercase()
+            && previous.is_some_and(|p| {
+                p.is_ascii_lowercase()
+                    || p.is_ascii_digit()
+                    || p.is_ascii_uppercase() && next.is_some_and(|n| n.is_ascii_lowercase())
+            });
+        if boundary {
+            words.push(std::mem::take(&mut word));
+        }
+        word.push(c);
+    }
+    words.push(word);
+    words.retain(|w| !w.is_empty());
+    words
+}
+
+fn pascal_case(key: &str) -> String {
+    let name: String = words(key)
+        .iter()
+        .map(|w| {
+            let lower = w.to_lowercase();
+            let mut chars = lower.chars();
+            chars.next().map_or(String::new(), |first| {
+                first.to_ascii_uppercase().to_string() + chars.as_str()
+            })
+        })
+        .collect();
+
+    match name.chars().next() {
+        None => "Value".to_string(),
+        Some(c) if c.is_ascii_digit() => format!("T{}", name),
+        Some(_) => name,
+    }
+}
+
+fn snake_case(key: &str) -> String {
+    let name = words(key)
+        .iter()
+        .map(|w| w.to_lowercase())
+        .collect::<Vec<_>>()
+        .join("_");
+
+    match name.chars().next() {
+        None => "field".to_string(),
+        Some(c) if c.is_ascii_digit() => format!("_{}", name),
+        Some(_) => name,
+    }
+}
+
+fn is_identifier(s: &str, dollar: bool) -> bool {
+    let valid = |c: char| c.is_ascii_alphanumeric() || c == '_' || dollar && c == '$';
+    s.chars().next().is_some_and(|c| !c.is_ascii_digit()) && s.chars().all(valid)
+}
+
+/// Global types that an interface of the same name would shadow or merge with.
+const TYPESCRIPT_TYPES: &[&str] = &[
+    "Array", "Boolean", "Date", "Error", "Function", "Map", "Number", "Object", "Promise",
+    "Record", "RegExp", "Set", "String", "Symbol",
+];
+
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern", "false",
+    "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
+    "return", "static", "struct", "trait", "true", "type", "unsafe", "use", "where", "while",
+    "abstract", "become", "box", "do", "final", "gen", "macro", "override", "priv", "try",
+    "typeof", "unsized", "virtual", "yield",
+];
+
+/// Types in the prelude or imported by the generated code.
+const RUST_TYPES: &[&str] = &[
+    "Box",
+    "Deserialize",
+    "Option",
+    "Result",
+    "Self",
+    "Serialize",
+    "String",
+    "Vec",
+];
+
+fn rust_field(key: &str) -> String {
+    let name = snake_case(key);
+    match name.as_str() {
+        "self" | "super" | "crate" => format!("{}_", name),
+        name if RUST_KEYWORDS.contains(&name) => format!("r#{}", name),
+        _ => name,
+    }
+}
+
+const PYTHON_KEYWORDS: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import",
+    "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+    "with", "yield",
+];
+
+/// Names imported from `typing` by the generated code.
+const PYTHON_TYPES: &[&str] = &["Any", "Optional", "Union"];
+
+fn python_field(key: &str) -> String {
+    let name = if is_identifier(key, false) {
+        key.to_string()
+    } else {
+        snake_case(key)
+    };
+    if PYTHON_KEYWORDS.contains(&name.as_str()) {
+        format!("{}_", name)
+    } else {
+        name
+    }
+}
+
+/// Words written in capitals in Go names.
+const GO_INITIALISMS: &[&str] = &[
+    "api", "html", "http", "https", "id", "ip", "json", "sql", "uri", "url", "uuid", "xml",
+];
+
+fn go_field(key: &str) -> String {
+    let name: String = words(key)
+        .iter()
+        .map(|w| {
+            let lower = w.to_lowercase();
+            if GO_INITIALISMS.contains(&lower.as_str()) {
+                lower.to_uppercase()
+            } else {
+                pascal_case(&lower)
+            }
+        })
+        .collect();
+
+    match name.chars().next() {
+        None => "Field".to_string(),
+        Some(c) if c.is_ascii_digit() => format!("F{}", name),
+        Some(_) => name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn names() {
+        assert_eq!(words("userID"), vec!["user", "ID"]);
+        assert_eq!(words("HTTPServer2go"), vec!["HTTP", "Server2go"]);
+        assert_eq!(pascal_case("first-name"), "FirstName");
+        assert_eq!(snake_case("firstName"), "first_name");
+        assert_eq!(go_field("user_id"), "UserID");
+        assert_eq!(rust_field("type"), "r#type");
+        assert_eq!(python_field("class"), "class_");
+        for (plural, singular_name) in [
+            ("Users", "User"),
+            ("Categories", "Category"),
+            ("Addresses", "Address"),
+            ("Statuses", "Status"),
+            ("Status", "StatusItem"),
+            ("Data", "DataItem"),
+        ] {
+            assert_eq!(singular(plural), singular_name);
+        }
+        assert_eq!(
+            root_name(&[Token::Key("users".into()), Token::Index(0)]),
+            "User"
+        );
+    }
+
+    #[test]
+    fn generation() {
+        let value = json!([
+            {"id": 1, "firstName": "Ada", "address": {"city": "London"}, "tags": ["a"]},
+            {"id": 2, "firstName": null, "email": "grace@example.com", "tags": []}
+        ]);
+        let path = [Token::Key("users".into())];
+
+        assert_eq!(
+            generate(&value, &path, Language::Rust),
+            r#"use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct User {
+    pub id: i64,
+    #[serde(rename = "firstName")]
+    pub first_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<Address>,
+    pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Address {
+    pub city: String,
+}
+
+pub type Users = Vec<User>;"#
+        );
+
+        assert_eq!(
+            generate(&value, &path, Language::TypeScript),
+            r#"export interface User {
+  id: number;
+  firstName: string | null;
+  address?: Address;
+  tags: string[];
+  email?: string;
+}
+
+export interface Address {
+  city: string;
+}
+
+export type Users = User[];"#
+        );
+
+        assert_eq!(
+            generate(&value, &path, Language::Python),
+            r#"from __future__ import annotations
+
+from dataclasses import dataclass
+from typing import Optional
+
+
+@dataclass
+class User:
+    id: int
+    firstName: Optional[str]
+    tags: list[str]
+    address: Optional[Address] = None
+    email: Optional[str] = None
+
+
+@dataclass
+class Address:
+    city: str
+
+
+Users = list[User]"#
+        );
+
+        assert_eq!(
+            generate(&value, &path, Language::Go),
+            "type User struct {
+\tID        int64    `json:\"id\"`
+\tFirstName *string  `json:\"firstName\"`
+\tAddress   *Address `json:\"address,omitempty\"`
+\tTags      []string `json:\"tags\"`
+\tEmail     *string  `json:\"email,omitempty\"`
+}
+
+type Address struct {
+\tCity string `json:\"city\"`
+}
+
+type Users []User"
+        );
+
+        assert_eq!(
+            generate(&json!(1.5), &[], Language::TypeScript),
+            "export type Root = number;"
+        );
+    }
+
+    #[test]
+    fn clashing_fields() {
+        let value = json!({"user_id": 1, "user-id": 2, "userId": 3});
+        let rust = generate(&value, &[], Language::Rust);
+        assert!(rust.contains("    pub user_id: i64,\n"));
+        assert!(rust.contains("    #[serde(rename = \"user-id\")]\n    pub user_id_2: i64,"));
+        assert!(rust.contains("    #[serde(rename = \"userId\")]\n    pub user_id_3: i64,"));
+
+        let python = generate(&value, &[], Language::Python);
+        assert!(python.contains("    user_id_2: int  # \"user-id\" in JSON\n    userId: int"));
+
+        let go = generate(&value, &[], Language::Go);
+        assert!(go.contains("\tUserID2 int64 `json:\"user-id\"`"));
+        assert!(go.contains("\tUserID3 int64 `json:\"userId\"`"));
+    }
+
+    #[test]
+    fn reserved_names() {
+        let value = json!({"string": {"a": 1}, "any": {"b": 2}});
+        let rust = generate(&value, &[], Language::Rust);
+        assert!(rust.contains("pub struct RootString {"));
+        assert!(rust.contains("    pub string: RootString,"));
+        assert!(rust.contains("pub struct Any {"));
+
+        let python = generate(&value, &[], Language::Python);
+        assert!(python.contains("class RootAny:"));
+        let typescript = generate(&value, &[], Language::TypeScript);
+        assert!(typescript.contains("export interface RootString {"));
+        let go = generate(&value, &[], Language::Go);
+        assert!(go.contains("type String struct {"));
+
+        assert_eq!(
+            generate(&json!("x"), &[Token::Key("option".into())], Language::Rust),
+            "pub type Option2 = String;"
+        );
+    }
+}
@@ -0,0 +1,347 @@
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use serde_json::{json, Map, Value};
+
+pub const DRAFT_2020_12: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// A format recognized in strings.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Format {
+    DateTime,
+    Date,
+    Time,
+    Uuid,
+    Uri,
+    Email,
+    Ipv4,
+    Ipv6,
+}
+
+impl Format {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Format::DateTime => "date-time",
+            Format::Date => "date",
+            Format::Time => "time",
+            Format::Uuid => "uuid",
+            Format::Uri => "uri",
+            Format::Email => "email",
+            Format::Ipv4 => "ipv4",
+            Format::Ipv6 => "ipv6",
+        }
+    }
+
+    /// The format of a string, if it has one of those recognized.
+    pub fn detect(s: &str) -> Option<Self> {
+        if is_date(s) {
+            Some(Format::Date)
+        } else if s.get(..10).is_some_and(is_date) && matches!(s.get(10..11), Some("T" | "t" | " "))
+        {
+            is_time(&s[11..]).then_some(Format::DateTime)
+        } else if is_time(s) {
+            Some(Format::Time)
+        } else if is_uuid(s) {
+            Some(Format::Uuid)
+        } else if s.parse::<Ipv4Addr>().is_ok() {
+            Some(Format::Ipv4)
+        } else if s.contains(':') && s.parse::<Ipv6Addr>().is_ok() {
+            Some(Format::Ipv6)
+        } else if is_uri(s) {
+            Some(Format::Uri)
+        } else if is_email(s) {
+            Some(Format::Email)
+        } else {
+            None
+        }
+    }
+}
+
+/// Whether `s` has the given number of ASCII digits at each of its `-`, `:` or `.` separated
+/// positions, e.g. `[4, 2, 2]` for `2024-01-31`.
+fn is_digits(s: &str, lengths: &[usize], separator: char) -> bool {
+    let parts: Vec<&str> = s.split(separator).collect();
+    parts.len() == lengths.len()
+        && parts
+            .iter()
+            .zip(lengths)
+            .all(|(p, &n)| p.len() == n && p.bytes().all(|b| b.is_ascii_digit()))
+}
+
+fn is_date(s: &str) -> bool {
+    is_digits(s, &[4, 2, 2], '-')
+}
+
+/// A time with seconds and an optional fraction, and an optional `Z` or numeric offset.
+fn is_time(s: &str) -> bool {
+    let (time, offset) = match s.find(['Z', 'z', '+', '-']) {
+        Some(i) => s.split_at(i),
+        None => (s, ""),
+    };
+    let time = time.split_once('.').map_or(time, |(time, fraction)| {
+        if !fraction.is_empty() && fraction.bytes().all(|b| b.is_ascii_digit()) {
+            time
+        } else {
+            ""
+        }
+    });
+
+    is_digits(time, &[2, 2, 2], ':')
+        && (matches!(offset, "" | "Z" | "z") || is_digits(&offset[1..], &[2, 2], ':'))
+}
+
+fn is_uuid(s: &str) -> bool {
+    let parts: Vec<&str> = s.split('-').collect();
+    parts.len() == 5
+        && parts
+            .iter()
+            .zip([8, 4, 4, 4, 12])
+            .all(|(p, n)| p.len() == n && p.bytes().all(|b| b.is_ascii_hexdigit()))
+}
+
+/// An absolute URI with an authority, like `https://example.com/path`.
+fn is_uri(s: &str) -> bool {
+    let Some((scheme, rest)) = s.split_once("://") else {
+        return false;
+    };
+    scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        && !rest.is_empty()
+        && !rest.contains(char::is_whitespace)
+}
+
+fn is_email(s: &str) -> bool {
+    let Some((local, domain)) = s.split_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && domain.contains('.')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+        && !s.contains(char::is_whitespace)
+        && !domain.contains('@')
+}
+
+/// The shape of values seen at one place in a document: the types they had, and for
+/// containers, the merged shapes of their members or elements.
+#[derive(Default, Debug)]
+pub struct Shape {
+    pub null: bool,
+    pub boolean: bool,
+    pub integer: bool,
+    /// Numbers with a fractional part
+    pub number: bool,
+    /// The format shared by every string, once strings were seen
+    pub string: Option<Option<Format>>,
+    /// The merged shape of array elements, once arrays were seen
+    pub array: Option<Box<Shape>>,
+    pub object: Option<ObjectShape>,
+}
+
+/// The merged properties of objects.
+#[derive(Default, Debug)]
+pub struct ObjectShape {
+    /// Number of objects merged
+    pub count: usize,
+    pub properties: Vec<Property>,
+    index: HashMap<String, usize>,
+}
+
+#[derive(Debug)]
+pub struct Property {
+    pub name: String,
+    pub shape: Shape,
+    /// Number of objects it appeared in
+    pub count: usize,
+}
+
+impl Shape {
+    pub fn of(value: &Value) -> Self {
+        let mut shape = Shape::default();
+        shape.merge(value);
+        shape
+    }
+
+    /// Whether no value was merged, as for the elements of empty arrays.
+    pub fn is_empty(&self) -> bool {
+        !(self.null || self.boolean || self.integer || self.number)
+            && self.string.is_none()
+            && self.array.is_none()
+            && self.object.is_none()
+    }
+
+    pub fn merge(&mut self, value: &Value) {
+        match value {
+            Value::Null => self.null = true,
+            Value::Bool(_) => self.boolean = true,
+            Value::Number(n) if n.is_f64() => self.number = true,
+            Value::Number(_) => self.integer = true,
+            Value::String(s) => {
+                let format = Format::detect(s);
+                self.string = Some(match self.string {
+                    Some(previous) if previous != format => None,
+                    _ => format,
+                });
+            }
+            Value::Array(a) => {
+                let items = self.array.get_or_insert_with(Default::default);
+                for element in a {
+                    items.merge(element);
+                }
+            }
+            Value::Object(o) => {
+                let object = self.object.get_or_insert_with(Default::default);
+                object.count += 1;
+                for (key, value) in o {
+                    match object.index.get(key) {
+                        Some(&i) => {
+                            let property = &mut object.properties[i];
+                            property.count += 1;
+                            property.shape.merge(value);
+                        }
+                        None => {
+                            object.index.insert(key.clone(), object.properties.len());
+                            object.properties.push(Property {
+                                name: key.clone(),
+                                shape: Shape::of(value),
+                                count: 1,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// The JSON Schema describing every value merged.
+    pub fn to_schema(&self) -> Map<String, Value> {
+        let mut types = vec![];
+        let mut schema = Map::new();
+
+        if let Some(ref object) = self.object {
+            types.push("object");
+            let properties: Map<String, Value> = object
+                .properties
+                .iter()
+                .map(|p| (p.name.clone(), Value::Object(p.shape.to_schema())))
+                .collect();
+            let required: Vec<&str> = object
+                .properties
+                .iter()
+                .filter(|p| p.count == object.count)
+                .map(|p| p.name.as_str())
+                .collect();
+            schema.insert("properties".to_string(), Value::Object(properties));
+            if !required.is_empty() {
+                schema.insert("required".to_string(), json!(required));
+            }
+        }
+        if let Some(ref items) = self.array {
+            types.push("array");
+            if !items.is_empty() {
+                schema.insert("items".to_string(), Value::Object(items.to_schema()));
+            }
+        }
+        if let Some(format) = self.string {
+            types.push("string");
+            if let Some(format) = format {
+                schema.insert("format".to_string(), json!(format.as_str()));
+            }
+        }
+        if self.number {
+            types.push("number");
+        } else if self.integer {
+            types.push("integer");
+        }
+        if self.boolean {
+            types.push("boolean");
+        }
+        if self.null {
+            types.push("null");
+        }
+
+        let kind = match types[..] {
+            [] => return schema,
+            [kind] => json!(kind),
+            _ => json!(types),
+        };
+        // Put the type first
+        let mut typed = Map::from_iter([("type".to_string(), kind)]);
+        typed.extend(schema);
+        typed
+    }
+}
+
+/// Infers a JSON Schema (draft 2020-12) that `value` validates against, merging the shapes
+/// of array elements. Properties missing from some objects aren't required, and values that
+/// are sometimes `null` are nullable.
+pub fn infer_schema(value: &Value) -> Value {
+    let mut schema = Map::from_iter([("$schema".to_string(), json!(DRAFT_2020_12))]);
+    schema.extend(Shape::of(value).to_schema());
+    Value::Object(schema)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats() {
+        let cases = [
+            ("2024-01-31", Some(Format::Date)),
+            ("2024-01-31T12:00:00Z", Some(Format::DateTime)),
+            ("2024-01-31T12:00:00.123+02:00", Some(Format::DateTime)),
+            ("12:30:00", Some(Format::Time)),
+            ("0b0e3b8a-7f0c-4b8e-9d3c-3a0e3f6e1c2a", Some(Format::Uuid)),
+            ("https://example.com/a?b=c", Some(Format::Uri)),
+            ("ada@example.com", Some(Format::Email)),
+            ("192.168.0.1", Some(Format::Ipv4)),
+            ("::1", Some(Format::Ipv6)),
+            ("2024-1-31", None),
+            ("key:value", None),
+            ("hello world", None),
+        ];
+        for (s, format) in cases {
+            assert_eq!(Format::detect(s), format, "{}", s);
+        }
+    }
+
+    #[test]
+    fn inference() {
+        let value = json!([
+            {"id": 1, "name": "a", "created": "2024-01-31", "tags": [], "score": 1},
+            {"id": 2, "name": null, "created": "2024-02-01", "tags": ["x"], "score": 1.5},
+            {"id": 3, "name": "c", "created": "soon", "extra": {"a": true}}
+        ]);
+        assert_eq!(
+            infer_schema(&value),
+            json!({
+                "$schema": DRAFT_2020_12,
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "id": {"type": "integer"},
+                        "name": {"type": ["string", "null"]},
+                        "created": {"type": "string"},
+                        "tags": {"type": "array", "items": {"type": "string"}},
+                        "score": {"type": "number"},
+                        "extra": {
+                            "type": "object",
+                            "properties": {"a": {"type": "boolean"}},
+                            "required": ["a"]
+                        }
+                    },
+                    "required": ["id", "name", "created"]
+                }
+            })
+        );
+
+        assert_eq!(
+            infer_schema(&json!([])),
+            json!({"$schema": DRAFT_2020_12, "type": "array"})
+        );
+    }
+}
@@ -0,0 +1,9 @@
+mod annotate;
+mod codegen;
+mod infer;
+mod validate;
+
+pub use annotate::Annotation;
+pub use codegen::{generate, Language};
+pub use infer::infer_schema;
+pub use validate::{describe, discover_schema, load_schema, Validation};
@@ -0,0 +1,853 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use regex::Regex;
+use serde_json::{Map, Value};
+
+use super::annotate::{annotate, Annotation};
+use crate::json::{ref_target, Json, Pointer, Token};
+use crate::style::StyledLine;
+
+/// Nested `$ref`s followed without moving into the instance, before giving up on a loop.
+const MAX_REF_DEPTH: usize = 64;
+
+/// A part of a document that doesn't satisfy its schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    /// Path of the offending value in the document
+    pub path: Vec<Token>,
+    pub message: String,
+}
+
+/// Validates `instance` against `schema`, a JSON Schema whose `$ref`s may point within itself.
+/// Returns every violation found.
+fn validate(schema: &Value, instance: &Value, patterns: &Patterns) -> Vec<Violation> {
+    let validator = Validator::new(schema, patterns);
+    let mut errors = vec![];
+    validator.check(schema, instance, &mut vec![], 0, &mut errors);
+    errors
+}
+
+/// The `pattern` and `patternProperties` regexes of a schema, each compiled the first time
+/// it's used; `None` if invalid.
+#[derive(Default)]
+pub(super) struct Patterns(RefCell<HashMap<String, Option<Regex>>>);
+
+impl Patterns {
+    fn get(&self, pattern: &str) -> Option<Regex> {
+        self.0
+            .borrow_mut()
+            .entry(pattern.to_string())
+            .or_insert_with(|| Regex::new(pattern).ok())
+            .clone()
+    }
+}
+
+pub(super) struct Validator<'s> {
+    pub root: &'s Value,
+    patterns: &'s Patterns,
+}
+
+impl<'s> Validator<'s> {
+    pub fn new(root: &'s Value, patterns: &'s Patterns) -> Self {
+        Self { root, patterns }
+    }
+
+    /// Whether `instance` is valid against `schema`, a subschema of the root.
+    pub fn matches(&self, schema: &Value, instance: &Value) -> bool {
+        self.is_valid(schema, instance, &mut vec![], 0)
+    }
+
+    fn is_valid(
+        &self,
+        schema: &Value,
+        instance: &Value,
+        path: &mut Vec<Token>,
+        depth: usize,
+    ) -> bool {
+        let mut errors = vec![];
+        self.check(schema, instance, path, depth, &mut errors);
+        errors.is_empty()
+    }
+
+    fn check(
+        &self,
+        schema: &Value,
+        instance: &Value,
+        path: &mut Vec<Token>,
+        depth: usize,
+        errors: &mut Vec<Violation>,
+    ) {
+        let mut fail = |message: String| {
+            errors.push(Violation {
+                path: path.clone(),
+                message,
+            })
+        };
+
+        let schema = match schema {
+            Value::Bool(true) => return,
+            Value::Bool(false) => return fail("no value is allowed here".to_string()),
+            Value::Object(schema) => schema,
+            _ => return,
+        };
+
+        if let Some(Value::String(reference)) = schema.get("$ref") {
+            match self.resolve(reference) {
+                _ if depth >= MAX_REF_DEPTH => fail(format!("$ref {} loops", reference)),
+                Some(target) => self.check(target, instance, path, depth + 1, errors),
+                None => fail(format!("unresolvable $ref {}", reference)),
+            }
+        }
+
+        self.check_generic(schema, instance, path, depth, errors);
+        match instance {
+            Value::Object(object) => self.check_object(schema, object, path, errors),
+            Value::Array(array) => self.check_array(schema, array, path, errors),
+            Value::String(s) => self.check_string(schema, s, path, errors),
+            Value::Number(_) => check_number(schema, instance, path, errors),
+            _ => {}
+        }
+    }
+
+    /// Keywords applying to any type: types, values and combinators.
+    fn check_generic(
+        &self,
+        schema: &Map<String, Value>,
+        instance: &Value,
+        path: &mut Vec<Token>,
+        depth: usize,
+        errors: &mut Vec<Violation>,
+    ) {
+        let mut fail = |message: String| {
+            errors.push(Violation {
+                path: path.clone(),
+                message,
+            })
+        };
+
+        if let Some(types) = schema.get("type") {
+            let types: Vec<&str> = match types {
+                Value::String(t) => vec![t],
+                Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+                _ => vec![],
+            };
+            if !types.is_empty() && !types.iter().any(|t| has_type(instance, t)) {
+                fail(format!(
+                    "expected {}, got {}",
+                    types.join(" or "),
+                    type_name(instance)
+                ));
+            }
+        }
+        if let Some(Value::Array(values)) = schema.get("enum") {
+            if !values.iter().any(|v| equal(v, instance)) {
+                fail(format!(
+                    "{} isn't one of the allowed values",
+                    brief(instance)
+                ));
+            }
+        }
+        if let Some(value) = schema.get("const") {
+            if !equal(value, instance) {
+                fail(format!("expected {}", brief(value)));
+            }
+        }
+
+        if let Some(Value::Array(schemas)) = schema.get("allOf") {
+            for subschema in schemas {
+                self.check(subschema, instance, path, depth, errors);
+            }
+        }
+        let matching = |schemas: &Vec<Value>, path: &mut Vec<Token>| {
+            schemas
+                .iter()
+                .filter(|s| self.is_valid(s, instance, path, depth))
+                .count()
+        };
+        if let Some(Value::Array(schemas)) = schema.get("anyOf") {
+            if matching(schemas, path) == 0 {
+                errors.push(Violation {
+                    path: path.clone(),
+                    message: "matches none of the anyOf schemas".to_string(),
+                });
+            }
+        }
+        if let Some(Value::Array(schemas)) = schema.get("oneOf") {
+            let count = matching(schemas, path);
+            if count != 1 {
+                errors.push(Violation {
+                    path: path.clone(),
+                    message: format!("matches {} of the oneOf schemas instead of 1", count),
+                });
+            }
+        }
+        if let Some(subschema) = schema.get("not") {
+            if self.is_valid(subschema, instance, path, depth) {
+                errors.push(Violation {
+                    path: path.clone(),
+                    message: "matches the schema it must not".to_string(),
+                });
+            }
+        }
+        if let Some(condition) = schema.get("if") {
+            let branch = if self.is_valid(condition, instance, path, depth) {
+                schema.get("then")
+            } else {
+                schema.get("else")
+            };
+            if let Some(branch) = branch {
+                self.check(branch, instance, path, depth, errors);
+            }
+        }
+    }
+
+    fn check_object(
+        &self,
+        schema: &Map<String, Value>,
+        object: &Map<String, Value>,
+        path: &mut Vec<Token>,
+        errors: &mut Vec<Violation>,
+    ) {
+        if let Some(Value::Array(required)) = schema.get("required") {
+            for name in required.iter().filter_map(Value::as_str) {
+                if !object.contains_key(name) {
+                    errors.push(Violation {
+                        path: path.clone(),
+                        message: format!("missing required property \"{}\"", name),
+                    });
+                }
+            }
+        }
+        if let Some(Value::Object(dependencies)) = schema.get("dependentRequired") {
+            for (name, required) in dependencies {
+                let Value::Array(required) = required else {
+                    continue;
+                };
+                for other in required.iter().filter_map(Value::as_str) {
+                    if object.contains_key(name) && !object.contains_key(other) {
+                        errors.push(Violation {
+                            path: path.clone(),
+                            message: format!("\"{}\" requires property \"{}\"", name, other),
+                        });
+                    }
+                }
+            }
+        }
+        check_count(
+            schema,
+            ("minProperties", "maxProperties"),
+            object.len(),
+            "properties",
+            path,
+            errors,
+        );
+
+        let properties = schema.get("properties").and_then(Value::as_object);
+        let mut patterns: Vec<(Regex, &Value)> = vec![];
+        for (pattern, subschema) in schema
+            .get("patternProperties")
+            .and_then(Value::as_object)
+            .into_iter()
+            .flatten()
+        {
+            match self.regex(pattern) {
+                Some(re) => patterns.push((re, subschema)),
+                None => errors.push(Violation {
+                    path: path.clone(),
+                    message: format!("the schema's property pattern {} is invalid", pattern),
+                }),
+            }
+        }
+        let additional = schema.get("additionalProperties");
+
+        for (name, value) in object {
+            path.push(Token::Key(name.clone()));
+
+            if let Some(names) = schema.get("propertyNames") {
+                let key = Value::String(name.clone());
+                if !self.is_valid(names, &key, path, 0) {
+                    errors.push(Violation {
+                        path: path.clone(),
+                        message: format!("property name \"{}\" isn't allowed", name),
+                    });
+                }
+            }
+
+            let mut evaluated = false;
+            if let Some(subschema) = properties.and_then(|p| p.get(name)) {
+                self.check(subschema, value, path, 0, errors);
+                evaluated = true;
+            }
+            for (pattern, subschema) in &patterns {
+                if pattern.is_match(name) {
+                    self.check(subschema, value, path, 0, errors);
+                    evaluated = true;
+                }
+            }
+            match additional {
+                Some(Value::Bool(false)) if !evaluated => errors.push(Violation {
+                    path: path.clone(),
+                    message: format!("property \"{}\" isn't allowed", name),
+                }),
+                Some(subschema) if !evaluated => self.check(subschema, value, path, 0, errors),
+                _ => {}
+            }
+
+            path.pop();
+        }
+    }
+
+    fn check_array(
+        &self,
+        schema: &Map<String, Value>,
+        array: &[Value],
+        path: &mut Vec<Token>,
+        errors: &mut Vec<Violation>,
+    ) {
+        check_count(
+            schema,
+            ("minItems", "maxItems"),
+            array.len(),
+            "items",
+            path,
+            errors,
+        );
+        if schema.get("uniqueItems") == Some(&Value::Bool(true)) {
+            let duplicate = array
+                .iter()
+                .enumerate()
+                .any(|(i, v)| array[..i].iter().any(|other| equal(v, other)));
+            if duplicate {
+                errors.push(Violation {
+                    path: path.clone(),
+                    message: "items aren't unique".to_string(),
+                });
+            }
+        }
+
+        let prefix = schema
+            .get("prefixItems")
+            .and_then(Value::as_array)
+            .map_or(&[][..], |p| &p[..]);
+        for (index, value) in array.iter().enumerate() {
+            path.push(Token::Index(index));
+            match prefix.get(index) {
+                Some(subschema) => self.check(subschema, value, path, 0, errors),
+                None => {
+                    if let Some(subschema) = schema.get("items") {
+                        self.check(subschema, value, path, 0, errors);
+                    }
+                }
+            }
+            path.pop();
+        }
+
+        if let Some(subschema) = schema.get("contains") {
+            let count = array
+                .iter()
+                .enumerate()
+                .filter(|(i, v)| {
+                    path.push(Token::Index(*i));
+                    let valid = self.is_valid(subschema, v, path, 0);
+                    path.pop();
+                    valid
+                })
+                .count();
+            let min = schema
+                .get("minContains")
+                .and_then(Value::as_u64)
+                .unwrap_or(1);
+            let max = schema.get("maxContains").and_then(Value::as_u64);
+            if (count as u64) < min || max.is_some_and(|max| count as u64 > max) {
+                errors.push(Violation {
+                    path: path.clone(),
+                    message: format!("contains {} matching items", count),
+                });
+            }
+        }
+    }
+
+    fn check_string(
+        &self,
+        schema: &Map<String, Value>,
+        s: &str,
+        path: &mut [Token],
+        errors: &mut Vec<Violation>,
+    ) {
+        check_count(
+            schema,
+            ("minLength", "maxLength"),
+            s.chars().count(),
+            "characters",
+            path,
+            errors,
+        );
+        if let Some(Value::String(pattern)) = schema.get("pattern") {
+            let message = match self.regex(pattern) {
+                Some(re) if re.is_match(s) => return,
+                Some(_) => format!("doesn't match the pattern {}", pattern),
+                None => format!("the schema's pattern {} is invalid", pattern),
+            };
+            errors.push(Violation {
+                path: path.to_vec(),
+                message,
+            });
+        }
+    }
+
+    /// A regex of the schema, compiled only the first time it's used.
+    pub fn regex(&self, pattern: &str) -> Option<Regex> {
+        self.patterns.get(pattern)
+    }
+
+    fn resolve(&self, reference: &str) -> Option<&'s Value> {
+        resolve_ref(self.root, reference)
+    }
+}
+
+/// Resolves a `$ref` within the schema `root`: `#`, a JSON Pointer like `#/$defs/name`, or an
+/// `$anchor` like `#name`.
+pub(super) fn resolve_ref<'s>(root: &'s Value, reference: &str) -> Option<&'s Value> {
+    let path = ref_target(root, reference).ok()?;
+    root.pointer(&Pointer::json_pointer(&path))
+}
+
+fn check_number(
+    schema: &Map<String, Value>,
+    instance: &Value,
+    path: &mut [Token],
+    errors: &mut Vec<Violation>,
+) {
+    let Some(n) = instance.as_f64() else {
+        return;
+    };
+    let bound = |keyword: &str| schema.get(keyword).and_then(Value::as_f64);
+    let mut fail = |message: String| {
+        errors.push(Violation {
+            path: path.to_vec(),
+            message,
+        })
+    };
+
+    if let Some(min) = bound("minimum").filter(|&min| n < min) {
+        fail(format!("{} is less than the minimum of {}", instance, min));
+    }
+    if let Some(max) = bound("maximum").filter(|&max| n > max) {
+        fail(format!(
+            "{} is greater than the maximum of {}",
+            instance, max
+        ));
+    }
+    if let Some(min) = bound("exclusiveMinimum").filter(|&min| n <= min) {
+        fail(format!("{} isn't greater than {}", instance, min));
+    }
+    if let Some(max) = bound("exclusiveMaximum").filter(|&max| n >= max) {
+        fail(format!("{} isn't less than {}", instance, max));
+    }
+    if let Some(divisor) = bound("multipleOf").filter(|&d| d > 0.0) {
+        let quotient = n / divisor;
+        if (quotient - quotient.round()).abs() > 1e-9 {
+            fail(format!("{} isn't a multiple of {}", instance, divisor));
+        }
+    }
+}
+
+/// Checks a size against a pair of minimum and maximum keywords.
+fn check_count(
+    schema: &Map<String, Value>,
+    (min, max): (&str, &str),
+    count: usize,
+    what: &str,
+    path: &[Token],
+    errors: &mut Vec<Violation>,
+) {
+    let count = count as u64;
+    if let Some(min) = schema
+        .get(min)
+        .and_then(Value::as_u64)
+        .filter(|&m| count < m)
+    {
+        errors.push(Violation {
+            path: path.to_vec(),
+            message: format!("has {} {}, fewer than {}", count, what, min),
+        });
+    }
+    if let Some(max) = schema
+        .get(max)
+        .and_then(Value::as_u64)
+        .filter(|&m| count > m)
+    {
+        errors.push(Violation {
+            path: path.to_vec(),
+            message: format!("has {} {}, more than {}", count, what, max),
+        });
+    }
+}
+
+/// Whether two values are the same JSON value: numbers are compared by value, so `1` equals
+/// `1.0`, and the order of object keys doesn't matter.
+fn equal(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => match (x.as_i64(), y.as_i64()) {
+            (Some(x), Some(y)) => x == y,
+            _ => match (x.as_u64(), y.as_u64()) {
+                (Some(x), Some(y)) => x == y,
+                _ => x.as_f64() == y.as_f64(),
+            },
+        },
+        (Value::Array(x), Value::Array(y)) => {
+            x.len() == y.len() && x.iter().zip(y).all(|(x, y)| equal(x, y))
+        }
+        (Value::Object(x), Value::Object(y)) => {
+            x.len() == y.len()
+                && x.iter()
+                    .all(|(k, v)| y.get(k).is_some_and(|other| equal(v, other)))
+        }
+        _ => a == b,
+    }
+}
+
+fn has_type(instance: &Value, name: &str) -> bool {
+    match name {
+        "integer" => instance.as_f64().is_some_and(|n| n.fract() == 0.0),
+        name => type_name(instance) == name || name == "number" && instance.is_number(),
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// A value shortened to fit in a message.
+fn brief(value: &Value) -> String {
+    let text = value.to_string();
+    match text.char_indices().nth(40) {
+        Some((i, _)) => format!("{}…", &text[..i]),
+        None => text,
+    }
+}
+
+/// The result of validating the document against a schema file, with the error selected.
+pub struct Validation {
+    pub source: PathBuf,
+    pub schema: Value,
+    /// Violations in the order of the lines they're on
+    pub errors: Vec<Violation>,
+    current: Option<usize>,
+    /// The last annotation looked up
+    annotation: Option<CachedAnnotation>,
+    /// Regexes of the schema, shared by validation and annotations
+    patterns: Patterns,
+}
+
+/// An annotation, with the path and the formatted document it was made for.
+struct CachedAnnotation {
+    path: Vec<Token>,
+    formatted: Arc<Vec<StyledLine>>,
+    annotation: Option<Annotation>,
+}
+
+impl Validation {
+    pub fn new(source: PathBuf, schema: Value, json: &Json) -> Self {
+        let patterns = Patterns::default();
+        let mut errors = validate(&schema, &json.value, &patterns);
+        errors.sort_by_key(|e| json.line_at(&e.path, false));
+
+        Self {
+            source,
+            schema,
+            errors,
+            current: None,
+            annotation: None,
+            patterns,
+        }
+    }
+
+    /// What the schema says about the node of `root` at `path`, only looked up again when the
+    /// path or the document changes.
+    pub fn annotation(&mut self, root: &Json, path: &[Token]) -> Option<Annotation> {
+        match self.annotation {
+            Some(ref cached)
+                if cached.path == path && Arc::ptr_eq(&cached.formatted, &root.formatted) =>
+            {
+                cached.annotation.clone()
+            }
+            _ => {
+                let validator = Validator::new(&self.schema, &self.patterns);
+                let annotation = annotate(&validator, &root.value, path);
+                self.annotation = Some(CachedAnnotation {
+                    path: path.to_vec(),
+                    formatted: Arc::clone(&root.formatted),
+                    annotation: annotation.clone(),
+                });
+                annotation
+            }
+        }
+    }
+
+    /// Paths of the nodes with errors, relative to `prefix`, the path zoomed into.
+    pub fn marks(&self, prefix: &[Token]) -> HashSet<Vec<Token>> {
+        self.errors
+            .iter()
+            .filter_map(|e| Some(e.path.strip_prefix(prefix)?.to_vec()))
+            .collect()
+    }
+
+    pub fn next(&mut self) -> Option<&Violation> {
+        let count = self.errors.len();
+        if count == 0 {
+            return None;
+        }
+        let i = self.current.map_or(0, |i| (i + 1) % count);
+        self.current = Some(i);
+        self.errors.get(i)
+    }
+
+    pub fn prev(&mut self) -> Option<&Violation> {
+        let count = self.errors.len();
+        if count == 0 {
+            return None;
+        }
+        let i = self.current.map_or(count - 1, |i| (i + count - 1) % count);
+        self.current = Some(i);
+        self.errors.get(i)
+    }
+
+    /// Position of the selected error, like `2/5`.
+    pub fn status(&self) -> String {
+        format!(
+            "{}/{}",
+            self.current.map_or(0, |i| i + 1),
+            self.errors.len()
+        )
+    }
+
+    /// Summary of the result, e.g. for a message after validating.
+    pub fn summary(&self) -> String {
+        let name = self.source.display();
+        match self.errors.len() {
+            0 => format!("Valid against {}", name),
+            1 => format!("1 error against {} (e to show)", name),
+            n => format!("{} errors against {} (e to show)", n, name),
+        }
+    }
+}
+
+/// Reads a schema from a JSON file.
+pub fn load_schema(path: &Path) -> Result<Value, String> {
+    let file =
+        File::open(path).map_err(|e| format!("Can't open schema {}: {}", path.display(), e))?;
+    serde_json::from_reader(BufReader::new(file))
+        .map_err(|e| format!("Error parsing schema {}: {}", path.display(), e))
+}
+
+/// Finds the schema file a document refers to with a root `$schema` property, relative to the
+/// document's directory. Remote schemas aren't fetched.
+pub fn discover_schema(document: &Value, document_path: &Path) -> Option<PathBuf> {
+    let reference = document.get("$schema")?.as_str()?;
+    if reference.starts_with("http://") || reference.starts_with("https://") {
+        return None;
+    }
+
+    let reference = reference.strip_prefix("file://").unwrap_or(reference);
+    let reference = reference.split('#').next()?;
+    let path = document_path.parent()?.join(reference);
+    path.is_file().then_some(path)
+}
+
+/// Formats a violation for the footer, e.g. `/a/0: expected string, got null`.
+pub fn describe(violation: &Violation) -> String {
+    let pointer = match Pointer::json_pointer(&violation.path) {
+        p if p.is_empty() => "/".to_string(),
+        p => p,
+    };
+    format!("{}: {}", pointer, violation.message)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn messages(schema: Value, instance: Value) -> Vec<String> {
+        validate(&schema, &instance, &Patterns::default())
+            .iter()
+            .map(describe)
+            .collect()
+    }
+
+    #[test]
+    fn validation() {
+        let schema = json!({
+            "type": "object",
+            "required": ["id", "tags"],
+            "properties": {
+                "id": {"type": "integer", "minimum": 1},
+                "name": {"$ref": "#/$defs/name"},
+                "tags": {"type": "array", "items": {"enum": ["a", "b"]}, "uniqueItems": true},
+                "kind": {"oneOf": [{"const": "x"}, {"type": "string", "maxLength": 1}]}
+            },
+            "additionalProperties": false,
+            "$defs": {"name": {"type": ["string", "null"], "pattern": "^[A-Z]"}}
+        });
+
+        assert!(messages(schema.clone(), json!({"id": 1, "tags": [], "name": null})).is_empty());
+        assert_eq!(
+            messages(
+                schema.clone(),
+                json!({"id": 0, "name": "ada", "tags": ["a", "c", "a"], "kind": "x", "x": 1})
+            ),
+            vec![
+                "/id: 0 is less than the minimum of 1",
+                "/name: doesn't match the pattern ^[A-Z]",
+                "/tags: items aren't unique",
+                "/tags/1: \"c\" isn't one of the allowed values",
+                "/kind: matches 2 of the oneOf schemas instead of 1",
+                "/x: property \"x\" isn't allowed",
+            ]
+        );
+        assert_eq!(
+            messages(schema, json!([1.5])),
+            vec!["/: expected object, got array"]
+        );
+
+        assert_eq!(
+            messages(json!({"$ref": "#/nowhere"}), json!(1)),
+            vec!["/: unresolvable $ref #/nowhere"]
+        );
+        assert_eq!(
+            messages(json!({"$ref": "#"}), json!(1)),
+            vec!["/: $ref # loops"]
+        );
+        assert_eq!(
+            messages(
+                json!({"if": {"type": "string"}, "then": {"minLength": 2}}),
+                json!("a")
+            ),
+            vec!["/: has 1 characters, fewer than 2"]
+        );
+        assert!(messages(json!({"type": "integer"}), json!(2.0)).is_empty());
+        assert_eq!(
+            messages(
+                json!({"$ref": "#num", "$defs": {"n": {"$anchor": "num", "type": "number"}}}),
+                json!("1")
+            ),
+            vec!["/: expected number, got string"]
+        );
+    }
+
+    #[test]
+    fn equality() {
+        let schema = json!({"const": {"a": 1, "b": [2.0]}});
+        assert!(messages(schema, json!({"b": [2], "a": 1.0})).is_empty());
+        assert!(messages(json!({"enum": ["1", 1]}), json!(1.0)).is_empty());
+        assert_eq!(
+            messages(
+                json!({"uniqueItems": true}),
+                json!([{"a": 1, "b": 2}, {"b": 2, "a": 1.0}])
+            ),
+            vec!["/: items aren't unique"]
+        );
+        assert!(messages(json!({"uniqueItems": true}), json!([1, "1", [1]])).is_empty());
+    }
+
+    #[test]
+    fn invalid_patterns() {
+        assert_eq!(
+            messages(json!({"pattern": "(?=a)"}), json!("a")),
+            vec!["/: the schema's pattern (?=a) is invalid"]
+        );
+        assert_eq!(
+            messages(
+                json!({"patternProperties": {"[": {"type": "string"}}}),
+                json!({"a": 1})
+            ),
+            vec!["/: the schema's property pattern [ is invalid"]
+        );
+    }
+
+    #[test]
+    fn navigation() {
+        let json = Json::from(json!({"a": "x", "b": {"c": 1}, "d": "y"}));
+        // Found in the order of the schema, listed in the order of the document
+        let schema = json!({"allOf": [
+            {"properties": {"d": {"type": "number"}}},
+            {"properties": {"b": {"properties": {"c": {"type": "string"}}}}},
+            {"properties": {"a": {"type": "number"}}}
+        ]});
+        let mut validation = Validation::new(PathBuf::from("schema.json"), schema, &json);
+        let pointers: Vec<String> = validation
+            .errors
+            .iter()
+            .map(|e| Pointer::json_pointer(&e.path))
+            .collect();
+        assert_eq!(pointers, vec!["/a", "/b/c", "/d"]);
+
+        let mut next = || validation.next().map(|e| Pointer::json_pointer(&e.path));
+        assert_eq!(next().as_deref(), Some("/a"));
+        assert_eq!(next().as_deref(), Some("/b/c"));
+        assert_eq!(next().as_deref(), Some("/d"));
+        assert_eq!(next().as_deref(), Some("/a"));
+        assert_eq!(
+            validation.prev().unwrap().path,
+            vec![Token::Key("d".into())]
+        );
+        assert_eq!(validation.status(), "3/3");
+
+        // Marks are relative to the subtree zoomed into
+        let b = [Token::Key("b".into())];
+        assert_eq!(
+            validation.marks(&b),
+            HashSet::from([vec![Token::Key("c".into())]])
+        );
+        assert_eq!(validation.marks(&[]).len(), 3);
+
+        // Annotations are kept for the selection they were looked up for
+        let d = vec![Token::Key("d".into())];
+        let patterns = Patterns::default();
+        let validator = Validator::new(&validation.schema, &patterns);
+        let expected = annotate(&validator, &json.value, &d);
+        assert!(expected.is_some());
+        assert_eq!(validation.annotation(&json, &d), expected);
+        assert_eq!(validation.annotation.as_ref().map(|a| &a.path), Some(&d));
+        assert_eq!(validation.annotation(&json, &d), expected);
+
+        let empty = Json::from(json!({}));
+        let mut validation = Validation::new(PathBuf::from("schema.json"), json!({}), &empty);
+        assert!(validation.next().is_none());
+        assert!(validation.prev().is_none());
+        assert_eq!(validation.status(), "0/0");
+    }
+
+    #[test]
+    fn schema_discovery() {
+        let dir = std::env::temp_dir().join(format!("jx-schema-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let schema = dir.join("config.schema.json");
+        std::fs::write(&schema, "{}").unwrap();
+        let document = dir.join("config.json");
+        let discover = |reference: &str| discover_schema(&json!({"$schema": reference}), &document);
+
+        assert_eq!(discover("./config.schema.json"), Some(schema.clone()));
+        assert_eq!(discover("file://config.schema.json#/$defs/a"), Some(schema));
+        assert_eq!(discover("missing.schema.json"), None);
+        assert_eq!(discover("https://example.com/config.schema.json"), None);
+        assert_eq!(discover_schema(&json!({}), &document), None);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,627 @@
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+
+use serde_json::Value;
+
+use crate::json::{select_paths, Json, PathPattern, Token};
+use crate::jsonpath;
+use crate::predicate::Predicate;
+use crate::style::{StyleClass, StyledLine};
+
+mod text;
+
+use text::Matcher;
+pub use text::TextOptions;
+
+/// Number of lines scanned between checks for cancellation and reports of partial results.
+const SCAN_CHUNK: usize = 4096;
+
+static NEXT_SCAN_ID: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Clone)]
+pub struct SearchMatch {
+    pub line_number: usize,
+    pub element_index: usize,
+    pub char_offset: usize, // Offset within element where match starts
+    pub len: usize,         // Number of matched characters
+}
+
+impl SearchMatch {
+    pub fn contains(&self, char_index: usize) -> bool {
+        (self.char_offset..self.char_offset + self.len).contains(&char_index)
+    }
+}
+
+/// Matches of a search, sorted by line, element and offset.
+#[derive(Clone)]
+pub struct SearchResults {
+    pub query: String,
+    pub matches: Vec<SearchMatch>,
+    pub current_index: Option<usize>,
+    pub error: Option<String>,
+    /// Id of the background scan still adding matches, if any
+    pub scan: Option<usize>,
+    /// Whether these are plain text matches, which can be narrowed down
+    text: bool,
+    pub options: TextOptions,
+    /// The generalized path matched, for sibling paths
+    pattern: Option<PathPattern>,
+}
+
+impl SearchResults {
+    pub fn new(query: String, matches: Vec<SearchMatch>) -> Self {
+        Self {
+            query,
+            matches,
+            current_index: None,
+            error: None,
+            scan: None,
+            text: false,
+            options: TextOptions::default(),
+            pattern: None,
+        }
+    }
+
+    pub fn error(query: String, error: String) -> Self {
+        Self {
+            error: Some(error),
+            ..Self::new(query, vec![])
+        }
+    }
+
+    pub fn next(&mut self) -> Option<&SearchMatch> {
+        if self.matches.is_empty() {
+            return None;
+        }
+        self.current_index = Some(match self.current_index {
+            Some(i) => (i + 1) % self.matches.len(),
+            None => 0,
+        });
+        self.current()
+    }
+
+    pub fn prev(&mut self) -> Option<&SearchMatch> {
+        if self.matches.is_empty() {
+            return None;
+        }
+        self.current_index = Some(match self.current_index {
+            Some(0) => self.matches.len() - 1,
+            Some(i) => i - 1,
+            None => self.matches.len() - 1,
+        });
+        self.current()
+    }
+
+    pub fn current(&self) -> Option<&SearchMatch> {
+        self.current_index.and_then(|i| self.matches.get(i))
+    }
+
+    pub fn status_text(&self) -> String {
+        // Counts are lower bounds while scanning
+        let more = match (
+            self.scan.is_some(),
+            self.text && self.options.ignore_diacritics,
+        ) {
+            (true, true) => "+ ~accents",
+            (true, false) => "+",
+            (false, true) => " ~accents",
+            (false, false) => "",
+        };
+
+        if let Some(ref error) = self.error {
+            error.clone()
+        } else if self.matches.is_empty() {
+            format!("0/0{}", more)
+        } else {
+            match self.current_index {
+                Some(i) => format!("{}/{}{}", i + 1, self.matches.len(), more),
+                None => format!("-/{}{}", self.matches.len(), more),
+            }
+        }
+    }
+
+    /// The status shown in the footer. For sibling paths, it starts with the indices of the
+    /// element the selection is in, e.g. `[3] 4/10`.
+    pub fn footer_status(&self, selection: &[Token]) -> String {
+        match self.pattern {
+            Some(ref pattern) if pattern.matches(selection) => {
+                let indices: String = selection
+                    .iter()
+                    .filter_map(|t| match t {
+                        Token::Index(i) => Some(format!("[{}]", i)),
+                        Token::Key(_) => None,
+                    })
+                    .collect();
+                format!("{} {}", indices, self.status_text())
+            }
+            _ => self.status_text(),
+        }
+    }
+
+    /// Adds the matches reported by a background scan, if these results are waiting on it.
+    pub fn receive(&mut self, scan: usize, found: &[SearchMatch], done: bool) -> bool {
+        if self.scan != Some(scan) {
+            return false;
+        }
+
+        self.matches.extend_from_slice(found);
+        if done {
+            self.scan = None;
+        }
+
+        !found.is_empty() || done
+    }
+
+    /// Moves the matches to new lines after the document was formatted again, e.g. sorted.
+    pub fn relocate(&mut self, locate: impl Fn(usize) -> Option<usize>) {
+        let current = self.current_index;
+        let mut matches: Vec<(usize, SearchMatch)> = self
+            .matches
+            .drain(..)
+            .enumerate()
+            .filter_map(|(i, m)| {
+                let line_number = locate(m.line_number)?;
+                Some((i, SearchMatch { line_number, ..m }))
+            })
+            .collect();
+        matches.sort_by_key(|(_, m)| (m.line_number, m.element_index, m.char_offset));
+
+        self.current_index = current.and_then(|c| matches.iter().position(|(i, _)| *i == c));
+        self.matches = matches.into_iter().map(|(_, m)| m).collect();
+    }
+
+    pub fn get_current(&self, line_number: usize, element_index: usize) -> Option<&SearchMatch> {
+        self.current()
+            .filter(|m| m.line_number == line_number && m.element_index == element_index)
+    }
+
+    /// Returns the matches within an element.
+    pub fn matches_in(&self, line_number: usize, element_index: usize) -> &[SearchMatch] {
+        let key = (line_number, element_index);
+        let start = self
+            .matches
+            .partition_point(|m| (m.line_number, m.element_index) < key);
+        let end = start
+            + self.matches[start..].partition_point(|m| (m.line_number, m.element_index) <= key);
+
+        &self.matches[start..end]
+    }
+
+    /// Counts the matches on lines `start..=end`.
+    pub fn count_within(&self, start: usize, end: usize) -> usize {
+        let first = self.matches.partition_point(|m| m.line_number < start);
+        let last = self.matches.partition_point(|m| m.line_number <= end);
+        last.saturating_sub(first)
+    }
+
+    /// Returns the paths of the nodes holding a match, in order and without duplicates.
+    pub fn pointers(&self, formatted: &[StyledLine]) -> Vec<Vec<Token>> {
+        let mut seen = HashSet::new();
+        self.matches
+            .iter()
+            .filter_map(|m| formatted.get(m.line_number))
+            .filter(|line| seen.insert(&line.pointer))
+            .map(|line| line.pointer.clone())
+            .collect()
+    }
+}
+
+/// A text scan running on a background thread, cancelled when dropped.
+pub struct SearchJob {
+    pub id: usize,
+    cancelled: Arc<AtomicBool>,
+    receiver: Receiver<Vec<SearchMatch>>,
+}
+
+impl SearchJob {
+    fn spawn(lines: Arc<Vec<StyledLine>>, query: &str, options: TextOptions) -> Self {
+        let id = NEXT_SCAN_ID.fetch_add(1, Ordering::Relaxed);
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let (sender, receiver) = mpsc::channel();
+        let mut matcher = Matcher::new(query, options);
+
+        let flag = Arc::clone(&cancelled);
+        thread::spawn(move || {
+            for chunk in lines.chunks(SCAN_CHUNK) {
+                if flag.load(Ordering::Relaxed) {
+                    return;
+                }
+                let matches = scan(chunk, &mut matcher);
+                if !matches.is_empty() && sender.send(matches).is_err() {
+                    return;
+                }
+            }
+        });
+
+        Self {
+            id,
+            cancelled,
+            receiver,
+        }
+    }
+
+    /// Collects the matches found since the last call, and whether the scan is over.
+    pub fn poll(&self) -> (Vec<SearchMatch>, bool) {
+        let mut found = vec![];
+        loop {
+            match self.receiver.try_recv() {
+                Ok(matches) => found.extend(matches),
+                Err(TryRecvError::Empty) => return (found, false),
+                Err(TryRecvError::Disconnected) => return (found, true),
+            }
+        }
+    }
+}
+
+impl Drop for SearchJob {
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Starts a search, treating queries that parse as JSONPath (`$...`) as such, and queries
+/// starting with `:` as value predicates.
+///
+/// Text searches match decoded keys and string values, compared according to `options`.
+/// Those that extend the query of complete `previous` results only narrow them down.
+/// Otherwise they scan the document on a background job, which fills in the results.
+pub fn start_search(
+    json: &Json,
+    query: &str,
+    options: TextOptions,
+    previous: Option<&SearchResults>,
+) -> (SearchResults, Option<SearchJob>) {
+    if let Some(predicate) = query.strip_prefix(':') {
+        let results = match Predicate::parse(predicate) {
+            Ok(predicate) => pointer_search(json, query, &predicate.select(&json.value)),
+            Err(e) => SearchResults::error(query.to_string(), e),
+        };
+        return (results, None);
+    }
+
+    if query.starts_with('$') {
+        if let Ok(paths) = jsonpath::select(query, &json.value) {
+            return (pointer_search(json, query, &paths), None);
+        }
+    }
+
+    if let Some(previous) = previous.filter(|p| {
+        p.text && p.options == options && p.scan.is_none() && Matcher::narrows(&p.query, query)
+    }) {
+        return (narrow(&json.formatted, previous, query, options), None);
+    }
+
+    let job = SearchJob::spawn(Arc::clone(&json.formatted), query, options);
+    let results = SearchResults {
+        scan: Some(job.id),
+        text: true,
+        options,
+        ..SearchResults::new(query.to_string(), vec![])
+    };
+
+    (results, Some(job))
+}
+
+/// Builds search results that highlight whole nodes: the key of object members, or the
+/// value (or opening bracket) otherwise.
+pub fn pointer_search(json: &Json, query: &str, paths: &[Vec<Token>]) -> SearchResults {
+    let mut lines: Vec<usize> = paths
+        .iter()
+        .filter_map(|tokens| json.pointer_map.get(tokens))
+        .map(|data| data.bounds.0)
+        .collect();
+    lines.sort_unstable();
+    lines.dedup();
+
+    let matches = lines
+        .into_iter()
+        .filter_map(|line_number| {
+            let line = json.formatted.get(line_number)?;
+            let element_index = node_element(line)?;
+            let len = line.elements[element_index].0.chars().count();
+
+            Some(SearchMatch {
+                line_number,
+                element_index,
+                char_offset: 0,
+                len,
+            })
+        })
+        .collect();
+
+    SearchResults::new(query.to_string(), matches)
+}
+
+/// Builds search results for the nodes sharing the selected key, or the selected value if
+/// it's a primitive that isn't an object member. The selected node is the current match.
+pub fn same_node_search(json: &Json) -> Option<SearchResults> {
+    let value = json.value()?;
+    let (query, paths) = match json.token() {
+        Some(Token::Key(key)) => {
+            let paths = select_paths(
+                &json.value,
+                |token, _| matches!(token, Some(Token::Key(k)) if k == key),
+            );
+            (format!("*{}", Value::String(key.clone())), paths)
+        }
+        _ if !value.is_array() && !value.is_object() => {
+            let paths = select_paths(&json.value, |_, v| v == value);
+            (format!("*{}", value), paths)
+        }
+        _ => return None,
+    };
+
+    Some(select_current(json, pointer_search(json, &query, &paths)))
+}
+
+/// Builds search results for the node at the selected path in every element of the arrays it
+/// goes through, e.g. `.items[].name`. The selected node is the current match.
+pub fn sibling_search(json: &Json) -> Option<SearchResults> {
+    let pattern = PathPattern::generalize(&json.tokens())?;
+    let paths: Vec<Vec<Token>> = json
+        .pointer_map
+        .keys()
+        .filter(|tokens| pattern.matches(tokens))
+        .cloned()
+        .collect();
+
+    let results = pointer_search(json, &pattern.to_jq(), &paths);
+    Some(select_current(
+        json,
+        SearchResults {
+            pattern: Some(pattern),
+            ..results
+        },
+    ))
+}
+
+/// Makes the match of the selected node current.
+fn select_current(json: &Json, mut results: SearchResults) -> SearchResults {
+    let line = json.bounds().0;
+    results.current_index = results.matches.iter().position(|m| m.line_number == line);
+    results
+}
+
+/// Picks the element that best represents the node on a line.
+fn node_element(line: &StyledLine) -> Option<usize> {
+    let elements = &line.elements;
+    let index = elements
+        .iter()
+        .position(|e| matches!(e.1, StyleClass::Key))
+        .or_else(|| {
+            elements
+                .iter()
+                .position(|e| !matches!(e.1, StyleClass::Punct | StyleClass::Whitespace))
+        })
+        .or_else(|| {
+            elements
+                .iter()
+                .position(|e| matches!(e.1, StyleClass::Punct))
+        })?;
+
+    // Empty strings have nothing to highlight, fall back to their opening quote
+    if elements[index].0.is_empty() && index > 0 {
+        Some(index - 1)
+    } else {
+        Some(index)
+    }
+}
+
+/// Finds every (possibly overlapping) occurrence of the query in the keys and values of `lines`.
+fn scan(lines: &[StyledLine], matcher: &mut Matcher) -> Vec<SearchMatch> {
+    let mut matches = Vec::new();
+    for line in lines {
+        for (elem_idx, elem) in line.elements.iter().enumerate() {
+            // Skip punctuation and whitespace
+            if matches!(elem.1, StyleClass::Punct | StyleClass::Whitespace) {
+                continue;
+            }
+
+            for (char_offset, len) in matcher.find(&elem.0, &elem.1) {
+                matches.push(SearchMatch {
+                    line_number: line.line_number,
+                    element_index: elem_idx,
+                    char_offset,
+                    len,
+                });
+            }
+        }
+    }
+
+    matches
+}
+
+/// Searches again the elements matched by `previous`, as only they can match `query`,
+/// which extends its query.
+fn narrow(
+    formatted: &[StyledLine],
+    previous: &SearchResults,
+    query: &str,
+    options: TextOptions,
+) -> SearchResults {
+    let mut matcher = Matcher::new(query, options);
+    let mut matches = Vec::new();
+    let mut last = None;
+    for m in &previous.matches {
+        if last == Some((m.line_number, m.element_index)) {
+            continue;
+        }
+        last = Some((m.line_number, m.element_index));
+
+        let elem = &formatted[m.line_number].elements[m.element_index];
+        for (char_offset, len) in matcher.find(&elem.0, &elem.1) {
+            matches.push(SearchMatch {
+                char_offset,
+                len,
+                ..m.clone()
+            });
+        }
+    }
+
+    SearchResults {
+        text: true,
+        options,
+        ..SearchResults::new(query.to_string(), matches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::json::Pointer;
+
+    fn run_search(json: &Json, query: &str) -> SearchResults {
+        run_search_with(json, query, TextOptions::default())
+    }
+
+    /// Runs a search to completion
+    fn run_search_with(json: &Json, query: &str, options: TextOptions) -> SearchResults {
+        let (mut results, job) = start_search(json, query, options, None);
+        if let Some(job) = job {
+            loop {
+                let (found, done) = job.poll();
+                results.receive(job.id, &found, done);
+                if done {
+                    break;
+                }
+                thread::yield_now();
+            }
+        }
+        results
+    }
+
+    #[test]
+    fn text_search() {
+        let json = Json::from(json!({"Ünïcode": "aaa", "b": "xÜnï"}));
+
+        let results = run_search(&json, "aa");
+        assert_eq!(results.matches.len(), 2);
+        assert_eq!(results.matches[1].char_offset, 1);
+        assert!(results.scan.is_none());
+
+        let results = run_search(&json, "ünï");
+        let offsets: Vec<usize> = results.matches.iter().map(|m| m.char_offset).collect();
+        assert_eq!(offsets, vec![0, 1]);
+        let m = &results.matches[1];
+        assert_eq!(results.matches_in(m.line_number, m.element_index).len(), 1);
+
+        // Extending the query narrows the previous matches without a new scan
+        let (narrowed, job) = start_search(&json, "ünïc", TextOptions::default(), Some(&results));
+        assert!(job.is_none());
+        assert_eq!(narrowed.matches.len(), 1);
+        assert_eq!(narrowed.matches[0].len, 4);
+
+        // Unless a combining mark makes `e` match the start of `é`
+        let json = Json::from(json!({"a": "cafe", "b": "cafe\u{301}"}));
+        let results = run_search(&json, "cafe");
+        assert_eq!(results.matches.len(), 1);
+        let (_, job) = start_search(&json, "cafe\u{301}", TextOptions::default(), Some(&results));
+        assert!(job.is_some());
+        assert_eq!(run_search(&json, "cafe\u{301}").matches.len(), 1);
+    }
+
+    #[test]
+    fn decoded_search() {
+        let json = Json::from(json!({"k": "line\ncafé"}));
+        let options = TextOptions {
+            ignore_diacritics: true,
+        };
+
+        // Matches the escaped newline, and é as e
+        let results = run_search_with(&json, "\ncafe", options);
+        assert!(results.status_text().ends_with("~accents"));
+        assert_eq!(results.matches.len(), 1);
+        let m = &results.matches[0];
+        assert_eq!((m.char_offset, m.len), (4, 6));
+        assert!(run_search(&json, "\ncafe").matches.is_empty());
+    }
+
+    #[test]
+    fn same_node() {
+        let mut json = Json::from(json!({"a": [{"id": 1}, {"id": 2}, 1], "id": true}));
+
+        json.set_selection(vec![Token::Key("id".into())]);
+        let results = same_node_search(&json).unwrap();
+        assert_eq!(results.query, "*\"id\"");
+        assert_eq!(results.matches.len(), 3);
+        assert_eq!(results.current_index, Some(2));
+
+        json.set_selection(vec![Token::Key("a".into()), Token::Index(2)]);
+        let results = same_node_search(&json).unwrap();
+        let pointers: Vec<String> = results
+            .pointers(&json.formatted)
+            .iter()
+            .map(|t| Pointer::json_pointer(t))
+            .collect();
+        assert_eq!(pointers, vec!["/a/0/id", "/a/2"]);
+        assert_eq!(results.current_index, Some(1));
+
+        json.set_selection(vec![Token::Key("a".into()), Token::Index(0)]);
+        assert!(same_node_search(&json).is_none());
+    }
+
+    #[test]
+    fn sibling_paths() {
+        let mut json = Json::from(json!({"items": [{"m": {"n": 1}}, {"x": 0}, {"m": {"n": 2}}]}));
+
+        json.set_selection(vec![
+            Token::Key("items".into()),
+            Token::Index(2),
+            Token::Key("m".into()),
+            Token::Key("n".into()),
+        ]);
+        let results = sibling_search(&json).unwrap();
+        assert_eq!(results.query, ".items[].m.n");
+        assert_eq!(results.matches.len(), 2);
+        assert_eq!(results.status_text(), "2/2");
+        assert_eq!(results.footer_status(&json.tokens()), "[2] 2/2");
+        assert_eq!(results.footer_status(&[]), "2/2");
+
+        json.set_selection(vec![Token::Key("items".into())]);
+        assert!(sibling_search(&json).is_none());
+    }
+
+    #[test]
+    fn jsonpath_search() {
+        let json = Json::from(json!({"a": [{"n": 1}, {"n": 600}], "n": 700}));
+
+        let results = run_search(&json, "$..n");
+        assert_eq!(results.matches.len(), 3);
+        // Highlights the whole key of each member
+        let m = &results.matches[0];
+        assert_eq!(
+            json.formatted[m.line_number].elements[m.element_index].0,
+            "n"
+        );
+        assert_eq!(m.len, 1);
+
+        let results = run_search(&json, "$.a[?(@.n > 500)]");
+        let pointers: Vec<String> = results
+            .pointers(&json.formatted)
+            .iter()
+            .map(|t| Pointer::json_pointer(t))
+            .collect();
+        assert_eq!(pointers, vec!["/a/1"]);
+
+        // Not JSONPath, so searched as text
+        assert_eq!(run_search(&json, "$ref").matches.len(), 0);
+    }
+
+    #[test]
+    fn predicate_search() {
+        let json = Json::from(json!({"a": [null, 600], "b": 700}));
+
+        let results = run_search(&json, ":num > 500");
+        assert_eq!(results.matches.len(), 2);
+        assert!(results.error.is_none());
+
+        let results = run_search(&json, ":num >");
+        assert!(results.matches.is_empty());
+        assert!(results.error.is_some());
+    }
+}
@@ -0,0 +1,205 @@
+use std::iter;
+
+use caseless::Caseless;
+use unicode_normalization::char::{decompose_canonical, is_combining_mark};
+
+use crate::style::StyleClass;
+
+/// How query and document characters are compared.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub struct TextOptions {
+    /// Match `e` against `é`, `è`, `ê`...
+    pub ignore_diacritics: bool,
+}
+
+/// Finds a query in the decoded text of elements.
+///
+/// Both sides are canonically decomposed and case folded, so matching is done on folded
+/// characters that are mapped back to the span of display characters they came from.
+pub struct Matcher {
+    needle: Vec<char>,
+    options: TextOptions,
+    folded: Vec<char>,
+    /// Display offset and length of the character each folded character comes from
+    origins: Vec<(usize, usize)>,
+}
+
+impl Matcher {
+    pub fn new(query: &str, options: TextOptions) -> Self {
+        let mut needle = Vec::new();
+        for c in query.chars() {
+            fold(c, options, &mut needle);
+        }
+
+        Self {
+            needle,
+            options,
+            folded: Vec::new(),
+            origins: Vec::new(),
+        }
+    }
+
+    /// Whether every match of `query` is within a match of `previous`, which it extends.
+    /// Appending a combining mark breaks this, as `cafe` doesn't match the `e` of `café`.
+    pub fn narrows(previous: &str, query: &str) -> bool {
+        query
+            .strip_prefix(previous)
+            .is_some_and(|rest| !rest.chars().next().is_some_and(is_combining_mark))
+    }
+
+    /// Returns the display offset and length of every (possibly overlapping) match in `text`.
+    pub fn find(&mut self, text: &str, class: &StyleClass) -> Vec<(usize, usize)> {
+        let mut spans = Vec::new();
+        if self.needle.is_empty() {
+            return spans;
+        }
+
+        self.folded.clear();
+        self.origins.clear();
+        let escaped = matches!(class, StyleClass::Key | StyleClass::String);
+        for (c, offset, len) in Decoded::new(text, escaped) {
+            fold(c, self.options, &mut self.folded);
+            self.origins.resize(self.folded.len(), (offset, len));
+        }
+
+        let n = self.needle.len();
+        for (i, window) in self.folded.windows(n).enumerate() {
+            // Don't match `e` against the start of a decomposed `é`
+            if window != self.needle
+                || self
+                    .folded
+                    .get(i + n)
+                    .is_some_and(|c| is_combining_mark(*c))
+            {
+                continue;
+            }
+            let (start, _) = self.origins[i];
+            let (last, last_len) = self.origins[i + n - 1];
+            let span = (start, last + last_len - start);
+            // A character folding to several (`ß` to `ss`) may match more than once
+            if spans.last() != Some(&span) {
+                spans.push(span);
+            }
+        }
+
+        spans
+    }
+}
+
+/// Pushes the folded form of `c`: canonically decomposed, case folded and, if ignoring
+/// diacritics, stripped of combining marks.
+fn fold(c: char, options: TextOptions, out: &mut Vec<char>) {
+    if c.is_ascii() {
+        out.push(c.to_ascii_lowercase());
+        return;
+    }
+
+    decompose_canonical(c, |d| {
+        if !(options.ignore_diacritics && is_combining_mark(d)) {
+            out.extend(iter::once(d).default_case_fold());
+        }
+    });
+}
+
+/// Iterates over the characters of a string as displayed with JSON escapes, yielding each
+/// decoded character with the offset and length of its display form, in characters.
+struct Decoded<'a> {
+    text: &'a str,
+    /// Byte position in `text`
+    pos: usize,
+    /// Character position in `text`
+    offset: usize,
+    escaped: bool,
+}
+
+impl<'a> Decoded<'a> {
+    fn new(text: &'a str, escaped: bool) -> Self {
+        Self {
+            text,
+            pos: 0,
+            offset: 0,
+            escaped,
+        }
+    }
+
+    fn hex(&self, at: usize) -> Option<u32> {
+        let digits = self.text.get(at..at + 4)?;
+        u32::from_str_radix(digits, 16).ok()
+    }
+
+    /// Decodes the escape sequence at the current position, returning the character and the
+    /// length of the sequence. Escape sequences are ASCII, so bytes and characters agree.
+    fn escape(&self) -> Option<(char, usize)> {
+        let c = match self.text.as_bytes().get(self.pos + 1)? {
+            b'"' => '"',
+            b'\\' => '\\',
+            b'/' => '/',
+            b'b' => '\u{8}',
+            b'f' => '\u{c}',
+            b'n' => '\n',
+            b'r' => '\r',
+            b't' => '\t',
+            b'u' => {
+                let code = self.hex(self.pos + 2)?;
+                // Characters outside the BMP are escaped as a surrogate pair
+                if (0xD800..0xDC00).contains(&code)
+                    && self.text.get(self.pos + 6..self.pos + 8) == Some("\\u")
+                {
+                    let low = self.hex(self.pos + 8)?.checked_sub(0xDC00)?;
+                    let code = 0x10000 + ((code - 0xD800) << 10) + low;
+                    return char::from_u32(code).map(|c| (c, 12));
+                }
+                return char::from_u32(code).map(|c| (c, 6));
+            }
+            _ => return None,
+        };
+
+        Some((c, 2))
+    }
+}
+
+impl Iterator for Decoded<'_> {
+    type Item = (char, usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let c = self.text[self.pos..].chars().next()?;
+        let (decoded, len, bytes) = match c {
+            '\\' if self.escaped => match self.escape() {
+                Some((decoded, len)) => (decoded, len, len),
+                None => (c, 1, 1),
+            },
+            _ => (c, 1, c.len_utf8()),
+        };
+
+        let offset = self.offset;
+        self.pos += bytes;
+        self.offset += len;
+        Some((decoded, offset, len))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(query: &str, text: &str, ignore_diacritics: bool) -> Vec<(usize, usize)> {
+        Matcher::new(query, TextOptions { ignore_diacritics }).find(text, &StyleClass::String)
+    }
+
+    #[test]
+    fn decoded_matching() {
+        // Escapes are decoded, and matches cover their whole display form
+        assert_eq!(find("a\nb", r"xa\nb", false), vec![(1, 4)]);
+        assert_eq!(find("\"é", r#"\"\u00e9"#, false), vec![(0, 8)]);
+        assert_eq!(find("\\", r"a\\b", false), vec![(1, 2)]);
+        assert_eq!(find("😀", r"\ud83d\ude00", false), vec![(0, 12)]);
+
+        // Unicode case folding, and composed and decomposed forms are equivalent
+        assert_eq!(find("STRASSE", "Straße", false), vec![(0, 6)]);
+        assert_eq!(find("É", "cafe\u{301} café", false), vec![(3, 2), (9, 1)]);
+
+        // Diacritics only match when ignored
+        assert_eq!(find("cafe", "café", false), vec![]);
+        assert_eq!(find("cafe", "café", true), vec![(0, 4)]);
+    }
+}
@@ -1,7 +1,8 @@
 use std::fmt::Display;
 use std::sync::atomic::{AtomicBool, Ordering};
 
-use crossterm::style::{Attributes, Color, ContentStyle, StyledContent};
+use crossterm::style::{Attribute, Attributes, Color, ContentStyle, StyledContent};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 use crate::json::Token;
 
@@ -66,6 +67,39 @@ impl StyledLine {
     }
 }
 
+/// A line of a side panel or view as runs of styled text.
+pub type Line = Vec<(String, ContentStyle)>;
+
+/// Truncates text to `width` columns, marking truncation with `…`.
+pub fn truncate(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        text.to_string()
+    } else if width == 0 {
+        String::new()
+    } else {
+        format!("{}…", take_width(text, width - 1))
+    }
+}
+
+/// Pads or truncates text to `width` columns, marking truncation with `…`.
+pub fn fit(text: &str, width: usize) -> String {
+    let text = truncate(text, width);
+    let padding = width.saturating_sub(text.width());
+    format!("{}{}", text, " ".repeat(padding))
+}
+
+/// The longest start of `text` that fits in `width` columns.
+pub fn take_width(text: &str, width: usize) -> &str {
+    let mut used = 0;
+    for (i, c) in text.char_indices() {
+        used += c.width().unwrap_or(0);
+        if used > width {
+            return &text[..i];
+        }
+    }
+    text
+}
+
 #[derive(Debug, Default, Clone, Copy)]
 pub enum StyleClass {
     #[default]
@@ -77,22 +111,25 @@ pub enum StyleClass {
     Bool,
     Null,
     FoldCount,
+    FoldMatchCount,
 }
 
 impl StyleClass {
     pub fn apply<D: Display>(&self, text: D) -> StyledContent<D> {
-        if is_no_color() {
-            return STYLE_PLAIN.apply(text);
-        }
+        styled(self.style(), text)
+    }
+
+    pub fn style(&self) -> ContentStyle {
         match self {
-            StyleClass::Whitespace => STYLE_WHITESPACE.apply(text),
-            StyleClass::Punct => STYLE_PUNCT.apply(text),
-            StyleClass::Key => STYLE_KEY.apply(text),
-            StyleClass::String => STYLE_STRING.apply(text),
-            StyleClass::Number => STYLE_NUMBER.apply(text),
-            StyleClass::Bool => STYLE_BOOL.apply(text),
-            StyleClass::Null => STYLE_NULL.apply(text),
-            StyleClass::FoldCount => STYLE_FOLD_COUNT.apply(text),
+            StyleClass::Whitespace => STYLE_WHITESPACE,
+            StyleClass::Punct => STYLE_PUNCT,
+            StyleClass::Key => STYLE_KEY,
+            StyleClass::String => STYLE_STRING,
+            StyleClass::Number => STYLE_NUMBER,
+            StyleClass::Bool => STYLE_BOOL,
+            StyleClass::Null => STYLE_NULL,
+            StyleClass::FoldCount => STYLE_FOLD_COUNT,
+            StyleClass::FoldMatchCount => STYLE_FOLD_MATCH_COUNT,
         }
     }
 }
@@ -161,6 +198,14 @@ const STYLE_FOLD_COUNT: ContentStyle = ContentStyle {
     attributes: Attributes::none(),
     underline_color: None,
 };
+
+const STYLE_FOLD_MATCH_COUNT: ContentStyle = ContentStyle {
+    foreground_color: Some(Color::Yellow),
+    background_color: None,
+    attributes: Attributes::none(),
+    underline_color: None,
+};
+
 pub const STYLE_SELECTION_BAR: ContentStyle = ContentStyle {
     foreground_color: Some(Color::White),
     background_color: None,
@@ -196,6 +241,13 @@ pub const STYLE_SEARCH_PROMPT: ContentStyle = ContentStyle {
     underline_color: None,
 };
 
+pub const STYLE_PROMPT_CURSOR: ContentStyle = ContentStyle {
+    foreground_color: Some(Color::Black),
+    background_color: Some(Color::White),
+    attributes: Attributes::none(),
+    underline_color: None,
+};
+
 pub const STYLE_SEARCH_STATUS: ContentStyle = ContentStyle {
     foreground_color: Some(Color::Grey),
     background_color: None,
@@ -203,6 +255,27 @@ pub const STYLE_SEARCH_STATUS: ContentStyle = ContentStyle {
     underline_color: None,
 };
 
+pub const STYLE_MESSAGE: ContentStyle = ContentStyle {
+    foreground_color: Some(Color::Grey),
+    background_color: None,
+    attributes: Attributes::none(),
+    underline_color: None,
+};
+
+pub const STYLE_FINDER_MATCH: ContentStyle = ContentStyle {
+    foreground_color: Some(Color::Yellow),
+    background_color: None,
+    attributes: Attributes::none(),
+    underline_color: None,
+};
+
+pub const STYLE_FINDER_SELECTED: ContentStyle = ContentStyle {
+    foreground_color: Some(Color::White),
+    background_color: None,
+    attributes: Attributes::none(),
+    underline_color: None,
+};
+
 pub const STYLE_HELP_BORDER: ContentStyle = ContentStyle {
     foreground_color: Some(Color::DarkYellow),
     background_color: None,
@@ -231,6 +304,55 @@ pub const STYLE_LINE_NUMBER: ContentStyle = ContentStyle {
     underline_color: None,
 };
 
+pub const STYLE_SORTED_INDEX: ContentStyle = ContentStyle {
+    foreground_color: Some(Color::DarkGrey),
+    background_color: None,
+    attributes: Attributes::none(),
+    underline_color: None,
+};
+
+pub const STYLE_TABLE_HEADER: ContentStyle = ContentStyle {
+    foreground_color: Some(Color::DarkBlue),
+    background_color: None,
+    attributes: Attributes::none(),
+    underline_color: None,
+};
+
+pub const STYLE_SELECTED_ENTRY: ContentStyle = ContentStyle {
+    foreground_color: Some(Color::Black),
+    background_color: Some(Color::White),
+    attributes: Attributes::none(),
+    underline_color: None,
+};
+
+pub const STYLE_COLUMN_PARENT: ContentStyle = ContentStyle {
+    foreground_color: Some(Color::White),
+    background_color: Some(Color::DarkGrey),
+    attributes: Attributes::none(),
+    underline_color: None,
+};
+
+pub const STYLE_COLUMN_BORDER: ContentStyle = ContentStyle {
+    foreground_color: Some(Color::DarkGrey),
+    background_color: None,
+    attributes: Attributes::none(),
+    underline_color: None,
+};
+
+pub const STYLE_ERROR_MARK: ContentStyle = ContentStyle {
+    foreground_color: Some(Color::Red),
+    background_color: None,
+    attributes: Attributes::none(),
+    underline_color: None,
+};
+
+pub const STYLE_BROKEN_REF: ContentStyle = ContentStyle {
+    foreground_color: Some(Color::Red),
+    background_color: None,
+    attributes: Attributes::none().with(Attribute::CrossedOut),
+    underline_color: None,
+};
+
 pub const STYLE_COPY_FLASH: ContentStyle = ContentStyle {
     foreground_color: Some(Color::Black),
     background_color: Some(Color::Cyan),
@@ -0,0 +1,400 @@
+use std::collections::HashSet;
+use std::io::Write;
+
+use crossterm::{
+    cursor, queue,
+    style::{Print, PrintStyledContent},
+    terminal,
+};
+use serde_json::Value;
+use unicode_width::UnicodeWidthStr;
+
+use crate::events::Direction;
+use crate::json::{summarize, Json, Pointer, Token};
+use crate::style::{
+    fit, styled, take_width, StyleClass, StyledString, STYLE_LINE_NUMBER, STYLE_SELECTED_ENTRY,
+    STYLE_SELECTION_BAR, STYLE_TABLE_HEADER,
+};
+
+/// Widest a column gets by default; it can be widened further.
+const MAX_COLUMN_WIDTH: usize = 24;
+const MIN_COLUMN_WIDTH: usize = 3;
+/// Number of rows measured to pick the default column widths.
+const SAMPLE_ROWS: usize = 200;
+const RESIZE_STEP: usize = 2;
+
+struct Column {
+    /// Key of the column, or `None` for elements that aren't objects
+    key: Option<String>,
+    width: usize,
+    hidden: bool,
+}
+
+/// An array shown as a table: the union of its elements' keys as columns and one row per
+/// element, with either a whole row or a single cell selected.
+pub struct Table {
+    /// Path of the array
+    pub path: Vec<Token>,
+    columns: Vec<Column>,
+    /// Indices of the elements, in display order
+    rows: Vec<usize>,
+    row: usize,
+    /// Selected column, or `None` when the whole row is selected
+    column: Option<usize>,
+    /// First row and column in view
+    top: usize,
+    left: usize,
+}
+
+impl Table {
+    /// Builds a table for the selected array, or the innermost array holding the selection,
+    /// with the row and cell of the selection selected.
+    pub fn new(json: &Json) -> Option<Self> {
+        let tokens = json.tokens();
+        let (path, array) = (0..=tokens.len()).rev().find_map(|i| {
+            match json.value.pointer(&Pointer::json_pointer(&tokens[..i])) {
+                Some(Value::Array(array)) => Some((tokens[..i].to_vec(), array)),
+                _ => None,
+            }
+        })?;
+        if array.is_empty() {
+            return None;
+        }
+        let selected = tokens.get(path.len()).and_then(Token::as_index);
+
+        let rows = match json.array_orders.get(&path) {
+            Some(order) => order.indices.clone(),
+            None => (0..array.len()).collect(),
+        };
+
+        let mut columns: Vec<Column> = vec![];
+        let mut seen = HashSet::new();
+        for element in array {
+            let keys: Vec<Option<&String>> = match element {
+                Value::Object(o) => o.keys().map(Some).collect(),
+                _ => vec![None],
+            };
+            for key in keys {
+                if seen.insert(key) {
+                    columns.push(Column {
+                        key: key.cloned(),
+                        width: 0,
+                        hidden: false,
+                    });
+                }
+            }
+        }
+        // Plain values go first
+        columns.sort_by_key(|c| c.key.is_some());
+
+        for column in &mut columns {
+            let header = column.key.as_deref().map_or(1, |k| k.width());
+            let content = rows
+                .iter()
+                .take(SAMPLE_ROWS)
+                .map(|&i| cell_text(cell(&array[i], &column.key)).0.width())
+                .max()
+                .unwrap_or(0);
+            column.width = header
+                .max(content)
+                .clamp(MIN_COLUMN_WIDTH, MAX_COLUMN_WIDTH);
+        }
+
+        let row = selected
+            .and_then(|index| rows.iter().position(|&i| i == index))
+            .unwrap_or(0);
+        let column = match tokens.get(path.len() + 1) {
+            Some(Token::Key(key)) => columns.iter().position(|c| c.key.as_ref() == Some(key)),
+            _ => None,
+        };
+
+        Some(Self {
+            path,
+            columns,
+            rows,
+            row,
+            column,
+            top: 0,
+            left: 0,
+        })
+    }
+
+    /// Path of the selected row or cell. Cells missing from their row select the row.
+    pub fn selection(&self, root: &Value) -> Vec<Token> {
+        let mut tokens = self.path.clone();
+        tokens.push(Token::Index(self.rows[self.row]));
+
+        if let Some(Column { key: Some(key), .. }) = self.column.map(|c| &self.columns[c]) {
+            let element = root.pointer(&Pointer::json_pointer(&tokens));
+            if element.is_some_and(|e| e.get(key).is_some()) {
+                tokens.push(Token::Key(key.clone()));
+            }
+        }
+
+        tokens
+    }
+
+    /// Moves the selection by `count` rows or columns. Moving left of the first column
+    /// selects the whole row.
+    pub fn select(&mut self, direction: Direction, count: usize) -> bool {
+        let (row, column) = (self.row, self.column);
+        match direction {
+            Direction::Up => self.row = self.row.saturating_sub(count),
+            Direction::Down => self.row = self.row.saturating_add(count).min(self.rows.len() - 1),
+            Direction::Left => {
+                for _ in 0..count {
+                    self.column = self
+                        .column
+                        .and_then(|c| (0..c).rev().find(|&c| !self.columns[c].hidden));
+                }
+            }
+            Direction::Right => {
+                for _ in 0..count {
+                    let start = self.column.map_or(0, |c| c + 1);
+                    match (start..self.columns.len()).find(|&c| !self.columns[c].hidden) {
+                        Some(c) => self.column = Some(c),
+                        None => break,
+                    }
+                }
+            }
+        }
+
+        (row, column) != (self.row, self.column)
+    }
+
+    /// Widens or narrows the selected column.
+    pub fn resize(&mut self, direction: Direction) -> bool {
+        let Some(column) = self.column.map(|c| &mut self.columns[c]) else {
+            return false;
+        };
+
+        let width = match direction {
+            Direction::Left | Direction::Down => column.width.saturating_sub(RESIZE_STEP),
+            Direction::Right | Direction::Up => column.width + RESIZE_STEP,
+        };
+        let width = width.max(MIN_COLUMN_WIDTH);
+        let changed = width != column.width;
+        column.width = width;
+        changed
+    }
+
+    /// Hides the selected column, unless it's the last one shown, and selects the next one.
+    pub fn hide_column(&mut self) -> bool {
+        let Some(c) = self.column else {
+            return false;
+        };
+        if self.columns.iter().filter(|c| !c.hidden).count() <= 1 {
+            return false;
+        }
+
+        self.columns[c].hidden = true;
+        if !self.select(Direction::Right, 1) {
+            self.select(Direction::Left, 1);
+        }
+        true
+    }
+
+    /// Shows every hidden column.
+    pub fn show_columns(&mut self) -> bool {
+        let hidden = self.columns.iter().any(|c| c.hidden);
+        self.columns.iter_mut().for_each(|c| c.hidden = false);
+        hidden
+    }
+
+    /// Scrolls so that the selection is in view in a body of `size`.
+    pub fn scroll_to_selection(&mut self, size: (usize, usize)) {
+        let rows = size.1.saturating_sub(1).max(1);
+        if self.row < self.top {
+            self.top = self.row;
+        } else if self.row >= self.top + rows {
+            self.top = self.row + 1 - rows;
+        }
+
+        let Some(column) = self.column else {
+            self.left = 0;
+            return;
+        };
+        if column < self.left {
+            self.left = column;
+            return;
+        }
+        // Drop columns on the left until the selected one fits
+        let available = size.0.saturating_sub(self.gutter_width());
+        while self.left < column {
+            let used: usize = (self.left..=column)
+                .filter(|&c| !self.columns[c].hidden)
+                .map(|c| self.columns[c].width + 1)
+                .sum();
+            if used <= available {
+                break;
+            }
+            self.left += 1;
+        }
+    }
+
+    /// Width of the selection bar and row indices left of the cells.
+    fn gutter_width(&self) -> usize {
+        self.rows.len().saturating_sub(1).to_string().len() + 4
+    }
+}
+
+/// Renders the table in the body area at `offset`, with the column headers on top.
+pub fn render_table<W: Write>(
+    out: &mut W,
+    json: &Json,
+    table: &Table,
+    offset: (usize, usize),
+    size: (usize, usize),
+) -> anyhow::Result<()> {
+    let (width, height) = size;
+    let gutter_width = table.gutter_width();
+    let array = json.value.pointer(&Pointer::json_pointer(&table.path));
+    let columns: Vec<(usize, &Column)> = table
+        .columns
+        .iter()
+        .enumerate()
+        .skip(table.left)
+        .filter(|(_, c)| !c.hidden)
+        .collect();
+
+    // Header row
+    queue!(
+        out,
+        cursor::MoveTo(offset.0 as u16, offset.1 as u16),
+        terminal::Clear(terminal::ClearType::CurrentLine),
+        Print(" ".repeat(gutter_width))
+    )?;
+    let mut col = gutter_width;
+    for (_, column) in &columns {
+        if col >= width {
+            break;
+        }
+        let header = fit(column.key.as_deref().unwrap_or("-"), column.width);
+        let header = take_width(&header, width - col).to_string();
+        col += column.width + 1;
+        queue!(
+            out,
+            PrintStyledContent(styled(STYLE_TABLE_HEADER, header)),
+            Print(" ")
+        )?;
+    }
+
+    for y in 1..height {
+        queue!(
+            out,
+            cursor::MoveTo(offset.0 as u16, (offset.1 + y) as u16),
+            terminal::Clear(terminal::ClearType::CurrentLine)
+        )?;
+
+        let position = table.top + y - 1;
+        let (Some(&index), Some(Value::Array(array))) = (table.rows.get(position), array) else {
+            continue;
+        };
+        let is_selected = position == table.row;
+        let element = &array[index];
+
+        let index_label = format!("{:>width$} ", index, width = gutter_width - 3);
+        if is_selected {
+            let style = if table.column.is_none() {
+                STYLE_SELECTED_ENTRY
+            } else {
+                STYLE_LINE_NUMBER
+            };
+            queue!(
+                out,
+                PrintStyledContent(styled(STYLE_SELECTION_BAR, "┃")),
+                PrintStyledContent(styled(style, index_label)),
+                Print(" ")
+            )?;
+        } else {
+            queue!(
+                out,
+                Print(" "),
+                PrintStyledContent(styled(STYLE_LINE_NUMBER, index_label)),
+                Print(" ")
+            )?;
+        }
+
+        let mut col = gutter_width;
+        for (c, column) in &columns {
+            if col >= width {
+                break;
+            }
+            let (text, class) = cell_text(cell(element, &column.key));
+            let text = take_width(&fit(&text, column.width), width - col).to_string();
+            col += column.width + 1;
+
+            if is_selected && table.column == Some(*c) {
+                queue!(out, PrintStyledContent(styled(STYLE_SELECTED_ENTRY, text)))?;
+            } else {
+                queue!(out, PrintStyledContent(class.apply(text)))?;
+            }
+            queue!(out, Print(" "))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The value of a column in an element: a member of objects, or plain values themselves.
+fn cell<'a>(element: &'a Value, key: &Option<String>) -> Option<&'a Value> {
+    match (element, key) {
+        (Value::Object(o), Some(key)) => o.get(key),
+        (Value::Object(_), None) => None,
+        (_, Some(_)) => None,
+        (value, None) => Some(value),
+    }
+}
+
+/// A one-line summary of a cell, with nested values collapsed.
+fn cell_text(value: Option<&Value>) -> (String, StyleClass) {
+    match value {
+        Some(value) => {
+            let StyledString(text, class) = summarize(value);
+            (text, class)
+        }
+        None => (String::new(), StyleClass::Whitespace),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn table_selection() {
+        let mut json = Json::from(json!({"a": [{"id": 1, "tags": [1, 2]}, {"name": "x"}, 5]}));
+        json.set_selection(vec![Token::Key("a".into()), Token::Index(1)]);
+
+        let mut table = Table::new(&json).unwrap();
+        let keys: Vec<Option<&str>> = table.columns.iter().map(|c| c.key.as_deref()).collect();
+        assert_eq!(keys, vec![None, Some("id"), Some("tags"), Some("name")]);
+        assert_eq!(table.row, 1);
+        assert_eq!(Pointer::json_pointer(&table.selection(&json.value)), "/a/1");
+
+        // Missing cells select their row, present ones their member
+        table.select(Direction::Right, 2);
+        assert_eq!(Pointer::json_pointer(&table.selection(&json.value)), "/a/1");
+        table.select(Direction::Up, 1);
+        assert_eq!(
+            Pointer::json_pointer(&table.selection(&json.value)),
+            "/a/0/id"
+        );
+
+        assert!(table.hide_column());
+        assert_eq!(table.column, Some(2));
+        table.select(Direction::Left, 1);
+        assert_eq!(table.column, Some(0));
+        assert!(table.resize(Direction::Right));
+        assert_eq!(table.columns[0].width, MIN_COLUMN_WIDTH + RESIZE_STEP);
+
+        assert_eq!(cell_text(Some(&json!({"a": 1}))).0, "{1}");
+        assert_eq!(fit("abcdef", 4), "abc…");
+        // Wide characters take two columns
+        assert_eq!(fit("日本", 4), "日本");
+        assert_eq!(fit("日本語", 4), "日… ");
+        assert_eq!(take_width("日本", 3), "日");
+    }
+}
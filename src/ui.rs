@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use std::time::{Duration, Instant};
 
 use crossterm::{
@@ -5,6 +6,42 @@ use crossterm::{
     style::{ContentStyle, Print, PrintStyledContent, ResetColor},
     terminal,
 };
+use unicode_width::UnicodeWidthStr;
+
+/// A panel shown in place of the footer.
+pub enum Panel<'a> {
+    Finder(&'a Finder),
+    /// The list of search matches
+    Results,
+}
+
+/// How the document is rendered.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum View {
+    /// Formatted JSON
+    Json,
+    /// Miller columns around the selection
+    Columns,
+    /// Keys and values only, with `tree`-like connectors
+    Outline,
+}
+
+impl View {
+    pub fn next(self) -> Self {
+        match self {
+            View::Json => View::Columns,
+            View::Columns => View::Outline,
+            View::Outline => View::Json,
+        }
+    }
+}
+
+/// What's shown in the body area.
+pub enum Body<'a> {
+    /// The formatted document
+    Tree,
+    Table(&'a Table),
+}
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum FlashMode {
@@ -13,19 +50,29 @@ pub enum FlashMode {
 }
 
 use crate::{
+    columns::render_columns,
+    docs::{render_docs, DOCS_MAX_WIDTH},
+    finder::{render_finder, Finder},
     help::render_help,
-    json::{bracket_fold, curly_fold, Json, PointerData, PointerValue},
+    json::{bracket_fold, curly_fold, Json, PointerData, PointerValue, Token},
+    outline::{render_outline, Outline},
+    prompt::Prompt,
+    results::render_results,
+    schema::Annotation,
     screen::Screen,
     search::SearchResults,
     style::{
-        styled, StyledLine, FLASH_DURATION_MS, STYLE_COPY_FLASH, STYLE_HEADER, STYLE_LINE_NUMBER,
-        STYLE_SEARCH_MATCH, STYLE_SEARCH_MATCH_CURRENT, STYLE_SEARCH_PROMPT, STYLE_SEARCH_STATUS,
-        STYLE_SELECTION_BAR,
+        styled, truncate, StyleClass, StyledLine, FLASH_DURATION_MS, STYLE_BROKEN_REF,
+        STYLE_COPY_FLASH, STYLE_ERROR_MARK, STYLE_HEADER, STYLE_LINE_NUMBER, STYLE_MESSAGE,
+        STYLE_SEARCH_MATCH, STYLE_SEARCH_MATCH_CURRENT, STYLE_SEARCH_STATUS, STYLE_SELECTION_BAR,
+        STYLE_SORTED_INDEX,
     },
+    table::{render_table, Table},
     InputSource,
 };
 
 static SELECTION_SYM: &str = "┃";
+static ERROR_SYM: &str = "●";
 static SELECTION_COL_WIDTH: usize = 1;
 
 /// Builds the UI and sends it off to be rendered.
@@ -33,11 +80,24 @@ pub struct UI {
     screen: Screen,
     header_height: usize,
     pub footer_height: usize,
+    pub header_info: Option<String>,
+    /// Whether the help overlay is shown over everything else
+    pub help_visible: bool,
+    pub view: View,
+    /// Nodes marked in the gutter as having schema errors
+    pub marks: HashSet<Vec<Token>>,
+    /// `$ref` members that point to nothing
+    pub broken_refs: HashSet<Vec<Token>>,
+    /// The schema panel on the right of the body, with what the schema says about the
+    /// selection if anything
+    pub docs: Option<Option<Annotation>>,
+    outline: Outline,
     scroll_x: usize,
     scroll_y: usize,
     line_wrap: bool,
     no_numbers: bool,
     flash_state: Option<(Instant, FlashMode)>,
+    message: Option<String>,
     gutter_width: usize,
 }
 
@@ -47,17 +107,33 @@ impl UI {
             screen: Screen::new()?,
             header_height: 1,
             footer_height: 0,
+            header_info: None,
+            help_visible: false,
+            view: View::Json,
+            marks: HashSet::new(),
+            broken_refs: HashSet::new(),
+            docs: None,
+            outline: Outline::default(),
             scroll_x: 0,
             scroll_y: 0,
             line_wrap: false,
             no_numbers,
             flash_state: None,
+            message: None,
             gutter_width: SELECTION_COL_WIDTH,
         })
     }
 
     fn usable_content_width(&self) -> usize {
-        self.screen.size.0.saturating_sub(self.gutter_width)
+        self.body_size().0.saturating_sub(self.gutter_width)
+    }
+
+    /// Width taken from the body by the schema panel.
+    fn docs_width(&self) -> usize {
+        match self.docs {
+            Some(_) => (self.screen.size.0 / 3).min(DOCS_MAX_WIDTH),
+            None => 0,
+        }
     }
 
     pub fn toggle_line_numbers(&mut self) {
@@ -69,6 +145,11 @@ impl UI {
         self.scroll_x = 0; // Reset horizontal scroll when toggling wrap
     }
 
+    /// Height of a bottom panel, capped at half the screen.
+    pub fn panel_height(&self, max: usize) -> usize {
+        max.min(self.screen.size.1 / 2)
+    }
+
     pub fn body_height(&self) -> usize {
         self.screen
             .size
@@ -76,6 +157,13 @@ impl UI {
             .saturating_sub(self.header_height + self.footer_height)
     }
 
+    pub fn body_size(&self) -> (usize, usize) {
+        (
+            self.screen.size.0.saturating_sub(self.docs_width()),
+            self.body_height(),
+        )
+    }
+
     pub fn scroll_x_by(&mut self, dx: isize, max: usize) -> bool {
         if self.line_wrap {
             return false;
@@ -92,7 +180,11 @@ impl UI {
         self.scroll_x != old
     }
 
+    /// Scrolls the body by `dy` lines, the outline view by nodes of its own.
     pub fn scroll_y_by(&mut self, dy: isize, max_lines: usize) -> bool {
+        if self.view == View::Outline {
+            return self.outline.scroll_by(dy, self.body_height());
+        }
         let old = self.scroll_y;
         let max_offset = max_lines.saturating_sub(self.screen.size.1 - 1);
         self.scroll_y = if dy < 0 {
@@ -115,6 +207,9 @@ impl UI {
     }
 
     pub fn scroll_y_min(&mut self) -> bool {
+        if self.view == View::Outline {
+            return self.outline.scroll_by(isize::MIN, self.body_height());
+        }
         let old = self.scroll_y;
         self.scroll_y = 0;
 
@@ -122,6 +217,9 @@ impl UI {
     }
 
     pub fn scroll_y_max(&mut self, max_lines: usize) -> bool {
+        if self.view == View::Outline {
+            return self.outline.scroll_by(isize::MAX, self.body_height());
+        }
         let old = self.scroll_y;
         self.scroll_y = max_lines.saturating_sub(self.body_height());
 
@@ -193,27 +291,51 @@ impl UI {
         })
     }
 
+    /// Shows a message in the footer until the next key press.
+    pub fn show_message(&mut self, message: impl Into<String>) {
+        self.message = Some(message.into());
+        self.footer_height = self.footer_height.max(1);
+    }
+
+    pub fn clear_message(&mut self) -> bool {
+        self.message.take().is_some()
+    }
+
     pub fn render(
         &mut self,
         source: &InputSource,
         json: &Json,
-        search_input: Option<&str>,
+        body: Body,
+        prompt: Option<(&str, &Prompt)>,
         search_results: Option<&SearchResults>,
-        help_visible: bool,
+        panel: Option<Panel>,
     ) -> anyhow::Result<()> {
         self.screen.clear()?;
 
         self.render_header(source)?;
-        let body_height = self.screen.size.1 - self.header_height - self.footer_height;
-        self.render_body(
-            json,
-            (0, self.header_height),
-            (self.screen.size.0, body_height),
-            search_results,
-        )?;
-        self.render_footer(search_input, search_results)?;
+        let (offset, size) = ((0, self.header_height), self.body_size());
+        match body {
+            Body::Tree => match self.view {
+                View::Json => self.render_body(json, offset, size, search_results)?,
+                View::Columns => render_columns(&mut self.screen.out, json, offset, size)?,
+                View::Outline => {
+                    render_outline(&mut self.screen.out, &mut self.outline, json, offset, size)?
+                }
+            },
+            Body::Table(table) => render_table(&mut self.screen.out, json, table, offset, size)?,
+        }
+        let docs_size = (self.docs_width(), size.1);
+        if let Some(ref annotation) = self.docs {
+            render_docs(
+                &mut self.screen.out,
+                annotation.as_ref(),
+                (size.0, self.header_height),
+                docs_size,
+            )?;
+        }
+        self.render_footer(json, prompt, search_results, panel)?;
 
-        if help_visible {
+        if self.help_visible {
             render_help(&mut self.screen.out, self.screen.size)?;
         }
 
@@ -234,6 +356,14 @@ impl UI {
             InputSource::Clipboard => "clipboard".to_string(),
         };
 
+        let label = match self.header_info {
+            Some(ref info) => format!("{} {}", label, info)
+                .chars()
+                .take(self.screen.size.0)
+                .collect(),
+            None => label,
+        };
+
         let header = format!("{label:<width$}", width = self.screen.size.0);
 
         queue!(
@@ -306,24 +436,47 @@ impl UI {
                 terminal::Clear(terminal::ClearType::CurrentLine)
             )?;
 
+            // Errors inside a fold are marked on it
+            let is_marked = if is_folded {
+                self.marks.iter().any(|m| m.starts_with(pointer))
+            } else {
+                self.marks.contains(pointer)
+                    && json
+                        .pointer_map
+                        .get(pointer)
+                        .is_some_and(|data| data.bounds.0 == *line_number)
+            };
+
             if !self.no_numbers {
                 let line_num_str = format!("{:>width$} ", line_number + 1, width = col_numbers_w);
+                let style = if is_marked {
+                    STYLE_ERROR_MARK
+                } else {
+                    STYLE_LINE_NUMBER
+                };
                 queue!(
                     self.screen.out,
                     cursor::MoveTo(col_numbers as u16, cursor_y as u16),
-                    PrintStyledContent(styled(STYLE_LINE_NUMBER, line_num_str))
+                    PrintStyledContent(styled(style, line_num_str))
                 )?;
             }
 
             let is_selected =
                 selection_bounds.0 <= *line_number && *line_number <= selection_bounds.1;
 
+            // The selection bar takes precedence over the mark, which the line number keeps
             if is_selected {
                 queue!(
                     self.screen.out,
                     cursor::MoveTo(col_selection as u16, cursor_y as u16),
                     PrintStyledContent(styled(STYLE_SELECTION_BAR, SELECTION_SYM))
                 )?;
+            } else if is_marked {
+                queue!(
+                    self.screen.out,
+                    cursor::MoveTo(col_selection as u16, cursor_y as u16),
+                    PrintStyledContent(styled(STYLE_ERROR_MARK, ERROR_SYM))
+                )?;
             }
 
             // Position cursor at JSON column + indent (accounting for horizontal scroll)
@@ -338,6 +491,22 @@ impl UI {
 
             let mut col = *indent; // Absolute column position
 
+            // Elements of sorted arrays are labeled with their original index
+            if let Some(index) = json.sorted_index(*line_number) {
+                for ch in format!("#{} ", index).chars() {
+                    if col >= col_max {
+                        break;
+                    }
+                    if col >= self.scroll_x {
+                        queue!(
+                            self.screen.out,
+                            PrintStyledContent(styled(STYLE_SORTED_INDEX, ch))
+                        )?;
+                    }
+                    col += 1;
+                }
+            }
+
             if let Some(PointerData {
                 value,
                 bounds,
@@ -345,9 +514,11 @@ impl UI {
             }) = fold_data
             {
                 let key = pointer.last().and_then(|t| t.as_key());
+                let matches =
+                    search_results.map_or(0, |sr| sr.count_within(bounds.0 + 1, bounds.1));
                 let fold_string = match value {
-                    PointerValue::Object => curly_fold(key.as_deref(), *children),
-                    PointerValue::Array => bracket_fold(*children),
+                    PointerValue::Object => curly_fold(key.as_deref(), *children, matches),
+                    PointerValue::Array => bracket_fold(*children, matches),
                     PointerValue::Primitive => panic!("should not fold primitives"),
                 };
                 for el in &fold_string {
@@ -386,22 +557,33 @@ impl UI {
                     .position(|el| el.0 == ":")
                     .map(|i| i + 1)
                     .unwrap_or(0);
+                let is_broken_ref = self.broken_refs.contains(pointer);
 
                 for (elem_idx, el) in elements.iter().enumerate() {
                     let text = &el.0;
+                    let style = if is_broken_ref
+                        && elem_idx >= value_start_idx
+                        && matches!(el.1, StyleClass::String)
+                    {
+                        STYLE_BROKEN_REF
+                    } else {
+                        el.1.style()
+                    };
 
-                    // Get search match info (style and which chars to highlight)
-                    let (search_style, match_positions) = search_results
-                        .map(|sr| {
-                            if let Some(m) = sr.get_current(*line_number, elem_idx) {
-                                (Some(STYLE_SEARCH_MATCH_CURRENT), Some(&m.char_positions))
-                            } else if let Some(m) = sr.get_match(*line_number, elem_idx) {
-                                (Some(STYLE_SEARCH_MATCH), Some(&m.char_positions))
-                            } else {
-                                (None, None)
-                            }
-                        })
-                        .unwrap_or((None, None));
+                    // Get search match info (which chars to highlight, and how)
+                    let current_match =
+                        search_results.and_then(|sr| sr.get_current(*line_number, elem_idx));
+                    let element_matches =
+                        search_results.map_or(&[][..], |sr| sr.matches_in(*line_number, elem_idx));
+                    let search_style = |char_idx: usize| {
+                        if current_match.is_some_and(|m| m.contains(char_idx)) {
+                            Some(STYLE_SEARCH_MATCH_CURRENT)
+                        } else if element_matches.iter().any(|m| m.contains(char_idx)) {
+                            Some(STYLE_SEARCH_MATCH)
+                        } else {
+                            None
+                        }
+                    };
 
                     // Determine if this element should flash based on mode
                     let should_flash = if !is_selected {
@@ -432,15 +614,12 @@ impl UI {
                                     cursor::MoveTo((col + gutter_width) as u16, cursor_y as u16)
                                 )?;
                             }
-                            let should_highlight = match_positions
-                                .map(|pos| pos.contains(&char_idx))
-                                .unwrap_or(false);
                             let styled = if should_flash {
-                                apply_with_bg(el.1.apply(ch), STYLE_COPY_FLASH)
-                            } else if should_highlight {
-                                apply_with_bg(el.1.apply(ch), search_style.unwrap())
+                                apply_with_bg(styled(style, ch), STYLE_COPY_FLASH)
+                            } else if let Some(search) = search_style(char_idx) {
+                                apply_with_bg(styled(style, ch), search)
                             } else {
-                                el.1.apply(ch)
+                                styled(style, ch)
                             };
                             queue!(self.screen.out, Print(styled))?;
                             col += 1;
@@ -452,15 +631,12 @@ impl UI {
                                 break;
                             }
                             if col >= self.scroll_x {
-                                let should_highlight = match_positions
-                                    .map(|pos| pos.contains(&char_idx))
-                                    .unwrap_or(false);
                                 let styled = if should_flash {
-                                    apply_with_bg(el.1.apply(ch), STYLE_COPY_FLASH)
-                                } else if should_highlight {
-                                    apply_with_bg(el.1.apply(ch), search_style.unwrap())
+                                    apply_with_bg(styled(style, ch), STYLE_COPY_FLASH)
+                                } else if let Some(search) = search_style(char_idx) {
+                                    apply_with_bg(styled(style, ch), search)
                                 } else {
-                                    el.1.apply(ch)
+                                    styled(style, ch)
                                 };
                                 queue!(self.screen.out, Print(styled))?;
                             }
@@ -490,14 +666,28 @@ impl UI {
 
     fn render_footer(
         &mut self,
-        search_input: Option<&str>,
+        json: &Json,
+        prompt: Option<(&str, &Prompt)>,
         search_results: Option<&SearchResults>,
+        panel: Option<Panel>,
     ) -> anyhow::Result<()> {
         if self.footer_height == 0 {
             return Ok(());
         }
 
         let footer_y = self.screen.size.1 - self.footer_height;
+
+        let panel_size = (self.screen.size.0, self.footer_height);
+        match (panel, search_results) {
+            (Some(Panel::Finder(finder)), _) => {
+                return render_finder(&mut self.screen.out, finder, footer_y, panel_size);
+            }
+            (Some(Panel::Results), Some(results)) => {
+                return render_results(&mut self.screen.out, json, results, footer_y, panel_size);
+            }
+            _ => {}
+        }
+
         queue!(
             self.screen.out,
             cursor::MoveTo(0, footer_y as u16),
@@ -505,20 +695,27 @@ impl UI {
             ResetColor
         )?;
 
-        // Render search prompt or status
-        if let Some(input) = search_input {
-            // Active search mode: show /input
+        // Render the active prompt, or any pending message, leaving room for the match count
+        let status_width =
+            search_results.map_or(0, |r| r.footer_status(&json.tokens()).width() + 2);
+        if let Some((symbol, input)) = prompt {
+            input.render(
+                &mut self.screen.out,
+                symbol,
+                self.screen.size.0.saturating_sub(status_width),
+            )?;
+        } else if let Some(ref message) = self.message {
+            let width = self.screen.size.0.saturating_sub(status_width.max(1));
             queue!(
                 self.screen.out,
-                PrintStyledContent(styled(STYLE_SEARCH_PROMPT, "/")),
-                Print(input)
+                PrintStyledContent(styled(STYLE_MESSAGE, truncate(message, width)))
             )?;
         }
 
         // Render match count on the right
         if let Some(results) = search_results {
-            let status = results.status_text();
-            let status_col = self.screen.size.0.saturating_sub(status.len() + 1);
+            let status = results.footer_status(&json.tokens());
+            let status_col = self.screen.size.0.saturating_sub(status.width() + 1);
             queue!(
                 self.screen.out,
                 cursor::MoveTo(status_col as u16, footer_y as u16),